@@ -0,0 +1,334 @@
+use std::{
+    fs::{self, OpenOptions},
+    io::Write,
+    path::{Path, PathBuf},
+    sync::Mutex,
+    time::Duration,
+};
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::{config::AuditConfig, mcp::ToolProvider};
+
+/// Placeholder written instead of secret values
+pub const REDACTED: &str = "[REDACTED]";
+
+// Serializes writes and rotations between concurrent tool calls
+static WRITE_LOCK: Mutex<()> = Mutex::new(());
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Outcome {
+    /// The tool ran and reported success
+    Success,
+    /// The tool ran but reported a failure in its response
+    Failure,
+    /// The call was rejected or the tool returned an error
+    Error,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditEntry {
+    pub timestamp: DateTime<Utc>,
+    pub tool: String,
+    pub arguments: serde_json::Value,
+    pub outcome: Outcome,
+    pub result: Option<serde_json::Value>,
+    pub error: Option<String>,
+    pub duration_ms: u64,
+}
+
+impl AuditEntry {
+    /// Build an entry for a finished call, redacting the tool's secrets
+    pub fn new<T: ToolProvider>(
+        arguments: &serde_json::Value,
+        result: &Result<serde_json::Value>,
+        duration: Duration,
+    ) -> Self {
+        let mut arguments = arguments.clone();
+        redact(&mut arguments, T::SECRET_PARAMS);
+
+        let (outcome, result, error) = match result {
+            Ok(value) => {
+                let mut value = value.clone();
+                redact(&mut value, T::SECRET_RESULT_FIELDS);
+                let error = value
+                    .get("error")
+                    .and_then(|e| e.as_str())
+                    .map(ToOwned::to_owned);
+                let failed = value.get("success").and_then(|s| s.as_bool()) == Some(false)
+                    || error.is_some();
+                let outcome = if failed {
+                    Outcome::Failure
+                } else {
+                    Outcome::Success
+                };
+                (outcome, Some(value), error)
+            }
+            Err(err) => (Outcome::Error, None, Some(err.to_string())),
+        };
+
+        Self {
+            timestamp: Utc::now(),
+            tool: T::NAME.to_owned(),
+            arguments,
+            outcome,
+            result,
+            error,
+            duration_ms: duration.as_millis() as u64,
+        }
+    }
+}
+
+/// Replace the value of every field named in `keys`, at any depth
+fn redact(value: &mut serde_json::Value, keys: &[&str]) {
+    if keys.is_empty() {
+        return;
+    }
+    match value {
+        serde_json::Value::Object(map) => {
+            for (key, field) in map.iter_mut() {
+                if keys.contains(&key.as_str()) {
+                    *field = serde_json::Value::String(REDACTED.to_owned());
+                } else {
+                    redact(field, keys);
+                }
+            }
+        }
+        serde_json::Value::Array(items) => {
+            for item in items {
+                redact(item, keys);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Size-rotated JSONL audit log
+pub struct AuditLog {
+    path: PathBuf,
+    max_size: u64,
+    max_files: u32,
+}
+
+impl AuditLog {
+    pub fn new(path: impl Into<PathBuf>, max_size: u64, max_files: u32) -> Self {
+        Self {
+            path: path.into(),
+            max_size,
+            max_files,
+        }
+    }
+
+    /// Log stored under `$XDG_STATE_HOME/gnome-mcp/audit.jsonl`
+    pub fn from_config(config: &AuditConfig) -> Self {
+        let state_dir = std::env::var_os("XDG_STATE_HOME")
+            .map(PathBuf::from)
+            .filter(|dir| dir.is_absolute())
+            .unwrap_or_else(|| gio::glib::home_dir().join(".local/state"));
+        Self::new(
+            state_dir.join("gnome-mcp/audit.jsonl"),
+            config.max_size_kb * 1024,
+            config.max_files,
+        )
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn rotated_path(&self, index: u32) -> PathBuf {
+        let mut path = self.path.clone().into_os_string();
+        path.push(format!(".{index}"));
+        PathBuf::from(path)
+    }
+
+    pub fn append(&self, entry: &AuditEntry) -> Result<()> {
+        let mut line = serde_json::to_string(entry)?;
+        line.push('\n');
+
+        let _guard = WRITE_LOCK.lock().unwrap_or_else(|err| err.into_inner());
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create {}", parent.display()))?;
+        }
+
+        let current_size = fs::metadata(&self.path).map(|m| m.len()).unwrap_or(0);
+        if current_size > 0 && current_size + line.len() as u64 > self.max_size {
+            self.rotate()?;
+        }
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .with_context(|| format!("Failed to open {}", self.path.display()))?;
+        file.write_all(line.as_bytes())?;
+        Ok(())
+    }
+
+    /// Shift `audit.jsonl.N` to `.N+1`, dropping the oldest file
+    fn rotate(&self) -> Result<()> {
+        if self.max_files == 0 {
+            fs::remove_file(&self.path)?;
+            return Ok(());
+        }
+
+        let oldest = self.rotated_path(self.max_files);
+        if oldest.exists() {
+            fs::remove_file(&oldest)?;
+        }
+        for index in (1..self.max_files).rev() {
+            let from = self.rotated_path(index);
+            if from.exists() {
+                fs::rename(&from, self.rotated_path(index + 1))?;
+            }
+        }
+        fs::rename(&self.path, self.rotated_path(1))?;
+        Ok(())
+    }
+
+    /// Most recent entries, newest first, across the current and rotated
+    /// files
+    pub fn recent(&self, limit: usize) -> Result<Vec<AuditEntry>> {
+        let mut entries = Vec::new();
+        let files = std::iter::once(self.path.clone())
+            .chain((1..=self.max_files).map(|i| self.rotated_path(i)));
+
+        for path in files {
+            if entries.len() >= limit {
+                break;
+            }
+            let content = match fs::read_to_string(&path) {
+                Ok(content) => content,
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound => break,
+                Err(err) => {
+                    return Err(err).with_context(|| format!("Failed to read {}", path.display()))
+                }
+            };
+            entries.extend(
+                content
+                    .lines()
+                    .rev()
+                    .filter_map(|line| serde_json::from_str::<AuditEntry>(line).ok())
+                    .take(limit - entries.len()),
+            );
+        }
+
+        Ok(entries)
+    }
+}
+
+/// Append a finished tool call to the audit log, if enabled
+pub fn record<T: ToolProvider>(
+    arguments: &serde_json::Value,
+    result: &Result<serde_json::Value>,
+    duration: Duration,
+) {
+    let config = &crate::config::CONFIG.audit;
+    if !config.enabled {
+        return;
+    }
+
+    let entry = AuditEntry::new::<T>(arguments, result, duration);
+    if let Err(err) = AuditLog::from_config(config).append(&entry) {
+        tracing::warn!("Failed to write audit log entry: {:#}", err);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn temp_log(name: &str, max_size: u64, max_files: u32) -> AuditLog {
+        let dir =
+            std::env::temp_dir().join(format!("gnome-mcp-audit-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        AuditLog::new(dir.join("audit.jsonl"), max_size, max_files)
+    }
+
+    #[test]
+    fn test_keyring_secrets_are_redacted() {
+        let arguments = json!({
+            "action": "store",
+            "label": "GitHub Token",
+            "secret": "ghp_xxx"
+        });
+        let result = Ok(json!({
+            "success": true,
+            "secret": "ghp_xxx",
+            "label": "GitHub Token"
+        }));
+
+        let entry = AuditEntry::new::<crate::tools::keyring::Keyring>(
+            &arguments,
+            &result,
+            Duration::from_millis(12),
+        );
+
+        assert_eq!(entry.tool, "keyring_management");
+        assert_eq!(entry.outcome, Outcome::Success);
+        assert_eq!(entry.arguments["secret"], REDACTED);
+        assert_eq!(entry.arguments["label"], "GitHub Token");
+        assert_eq!(entry.result.as_ref().unwrap()["secret"], REDACTED);
+        assert_eq!(entry.duration_ms, 12);
+        assert!(!serde_json::to_string(&entry).unwrap().contains("ghp_xxx"));
+    }
+
+    #[test]
+    fn test_outcome_classification() {
+        let arguments = json!({});
+        let failure = Ok(json!({"success": false, "error": "App not found"}));
+        let entry = AuditEntry::new::<crate::tools::applications::Applications>(
+            &arguments,
+            &failure,
+            Duration::ZERO,
+        );
+        assert_eq!(entry.outcome, Outcome::Failure);
+        assert_eq!(entry.error.as_deref(), Some("App not found"));
+
+        let error = Err(anyhow::anyhow!("Missing required parameter: app_name"));
+        let entry = AuditEntry::new::<crate::tools::applications::Applications>(
+            &arguments,
+            &error,
+            Duration::ZERO,
+        );
+        assert_eq!(entry.outcome, Outcome::Error);
+        assert!(entry.result.is_none());
+    }
+
+    #[test]
+    fn test_append_rotates_and_reads_recent() {
+        let log = temp_log("rotate", 400, 2);
+        for i in 0..10 {
+            let entry = AuditEntry::new::<crate::tools::notifications::Notifications>(
+                &json!({"summary": format!("n{i}"), "body": ""}),
+                &Ok(json!({"success": true})),
+                Duration::ZERO,
+            );
+            log.append(&entry).unwrap();
+        }
+
+        assert!(log.rotated_path(1).exists());
+        assert!(log.rotated_path(2).exists());
+        assert!(!log.rotated_path(3).exists());
+        assert!(fs::metadata(log.path()).unwrap().len() <= 400);
+
+        let recent = log.recent(3).unwrap();
+        assert_eq!(recent.len(), 3);
+        assert_eq!(recent[0].arguments["summary"], "n9");
+        assert_eq!(recent[2].arguments["summary"], "n7");
+
+        let _ = fs::remove_dir_all(log.path().parent().unwrap());
+    }
+
+    #[test]
+    fn test_recent_without_log_file() {
+        let log = temp_log("missing", 1024, 1);
+        assert!(log.recent(10).unwrap().is_empty());
+    }
+}
@@ -1,4 +1,5 @@
 use std::{
+    collections::HashMap,
     path::{Path, PathBuf},
     sync::LazyLock,
 };
@@ -6,7 +7,10 @@ use std::{
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
 
-use crate::mcp::{ResourceProvider, ToolProvider};
+use crate::{
+    gnome::evolution::SourceFilter,
+    mcp::{ResourceProvider, ToolProvider},
+};
 
 // Resource-specific configuration structs
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -17,6 +21,9 @@ pub struct CalendarConfig {
     /// Number of days to look behind for events
     #[serde(default)]
     pub days_behind: u32,
+    /// Calendars to read, by UID or display name
+    #[serde(flatten)]
+    pub sources: SourceFilter,
 }
 
 impl Default for CalendarConfig {
@@ -24,6 +31,7 @@ impl Default for CalendarConfig {
         Self {
             days_ahead: 30,
             days_behind: 0,
+            sources: SourceFilter::default(),
         }
     }
 }
@@ -39,6 +47,9 @@ pub struct TasksConfig {
     /// Only show tasks due within X days (0 = all tasks)
     #[serde(default)]
     pub due_within_days: u32,
+    /// Task lists to read, by UID or display name
+    #[serde(flatten)]
+    pub sources: SourceFilter,
 }
 
 impl Default for TasksConfig {
@@ -47,17 +58,60 @@ impl Default for TasksConfig {
             include_completed: true,
             include_cancelled: false,
             due_within_days: 0,
+            sources: SourceFilter::default(),
         }
     }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct MemosConfig {
+    /// Memo lists to read, by UID or display name
+    #[serde(flatten)]
+    pub sources: SourceFilter,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RemindersConfig {
+    /// Number of hours to look ahead for alarms
+    #[serde(default)]
+    pub hours_ahead: u32,
+}
+
+impl Default for RemindersConfig {
+    fn default() -> Self {
+        Self { hours_ahead: 24 }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ContactsConfig {
     /// Include only contacts with email addresses
     #[serde(default)]
     pub email_only: bool,
+    /// Number of days to look ahead for birthdays and anniversaries
+    #[serde(default)]
+    pub upcoming_days: u32,
+    /// Address books to read, by UID or display name
+    #[serde(flatten)]
+    pub sources: SourceFilter,
+}
+
+impl Default for ContactsConfig {
+    fn default() -> Self {
+        Self {
+            email_only: false,
+            upcoming_days: 30,
+            sources: SourceFilter::default(),
+        }
+    }
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct AgendaConfig {}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct SourcesConfig {}
+
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct SystemInfoConfig {}
 
@@ -67,6 +121,22 @@ pub struct ApplicationsResourceConfig {}
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct AudioResourceConfig {}
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AuditResourceConfig {
+    /// Maximum number of audit entries to return, newest first
+    pub limit: usize,
+}
+
+impl Default for AuditResourceConfig {
+    fn default() -> Self {
+        Self { limit: 50 }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct UndoHistoryConfig {}
+
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct NotificationsConfig {}
 
@@ -108,6 +178,57 @@ pub struct WindowManagementConfig {}
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct KeyringConfig {}
 
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct CalendarToolConfig {}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct TaskManagementConfig {}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct MemoManagementConfig {}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct SourceManagementConfig {}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct ContactsToolConfig {}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct FreeBusyConfig {}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct SearchToolConfig {}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct ImportExportConfig {}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UndoConfig {
+    /// Number of changes kept on the undo stack
+    pub max_entries: usize,
+}
+
+impl Default for UndoConfig {
+    fn default() -> Self {
+        Self { max_entries: 50 }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EvolutionCacheConfig {
+    /// Keep live views on the Evolution sources and answer the calendar,
+    /// task and contact reads from memory
+    pub enabled: bool,
+}
+
+impl Default for EvolutionCacheConfig {
+    fn default() -> Self {
+        Self { enabled: true }
+    }
+}
+
 // Container structs
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ResourcesConfig {
@@ -115,8 +236,14 @@ pub struct ResourcesConfig {
     pub applications: Option<ApplicationsResourceConfig>,
     pub calendar: Option<CalendarConfig>,
     pub tasks: Option<TasksConfig>,
+    pub memos: Option<MemosConfig>,
+    pub reminders: Option<RemindersConfig>,
+    pub agenda: Option<AgendaConfig>,
     pub contacts: Option<ContactsConfig>,
+    pub sources: Option<SourcesConfig>,
     pub audio: Option<AudioResourceConfig>,
+    pub audit: Option<AuditResourceConfig>,
+    pub undo_history: Option<UndoHistoryConfig>,
 }
 
 impl Default for ResourcesConfig {
@@ -126,8 +253,14 @@ impl Default for ResourcesConfig {
             applications: Some(ApplicationsResourceConfig::default()),
             calendar: Some(CalendarConfig::default()),
             tasks: Some(TasksConfig::default()),
+            memos: Some(MemosConfig::default()),
+            reminders: Some(RemindersConfig::default()),
+            agenda: Some(AgendaConfig::default()),
             contacts: Some(ContactsConfig::default()),
+            sources: Some(SourcesConfig::default()),
             audio: Some(AudioResourceConfig::default()),
+            audit: Some(AuditResourceConfig::default()),
+            undo_history: Some(UndoHistoryConfig::default()),
         }
     }
 }
@@ -143,6 +276,15 @@ pub struct ToolsConfig {
     pub screenshot: Option<ScreenshotConfig>,
     pub window_management: Option<WindowManagementConfig>,
     pub keyring: Option<KeyringConfig>,
+    pub calendar: Option<CalendarToolConfig>,
+    pub tasks: Option<TaskManagementConfig>,
+    pub memos: Option<MemoManagementConfig>,
+    pub sources: Option<SourceManagementConfig>,
+    pub contacts: Option<ContactsToolConfig>,
+    pub free_busy: Option<FreeBusyConfig>,
+    pub search: Option<SearchToolConfig>,
+    pub import_export: Option<ImportExportConfig>,
+    pub undo: Option<UndoConfig>,
 }
 
 impl Default for ToolsConfig {
@@ -157,10 +299,96 @@ impl Default for ToolsConfig {
             screenshot: Some(ScreenshotConfig::default()),
             window_management: Some(WindowManagementConfig::default()),
             keyring: Some(KeyringConfig::default()),
+            calendar: Some(CalendarToolConfig::default()),
+            tasks: Some(TaskManagementConfig::default()),
+            memos: Some(MemoManagementConfig::default()),
+            sources: Some(SourceManagementConfig::default()),
+            contacts: Some(ContactsToolConfig::default()),
+            free_busy: Some(FreeBusyConfig::default()),
+            search: Some(SearchToolConfig::default()),
+            import_export: Some(ImportExportConfig::default()),
+            undo: Some(UndoConfig::default()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AuditConfig {
+    /// Record every tool invocation in the audit log
+    pub enabled: bool,
+    /// Rotate the log once it grows past this size, in kilobytes
+    pub max_size_kb: u64,
+    /// Number of rotated log files to keep around
+    pub max_files: u32,
+}
+
+impl Default for AuditConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_size_kb: 1024,
+            max_files: 5,
         }
     }
 }
 
+/// Token bucket and quota settings; unset fields fall back to the defaults
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct RateLimit {
+    /// Sustained number of calls allowed per minute
+    pub per_minute: Option<f64>,
+    /// Calls that can be made back to back before throttling (default: 1)
+    pub burst: Option<u32>,
+    /// Maximum number of calls for the lifetime of the server process
+    pub session_quota: Option<u32>,
+}
+
+impl RateLimit {
+    pub fn is_unlimited(&self) -> bool {
+        self.per_minute.is_none() && self.session_quota.is_none()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct RateLimitsConfig {
+    /// Limits applied to every tool
+    pub default: RateLimit,
+    /// Per-tool limits, keyed by tool name (e.g. `send_notification`)
+    pub tools: HashMap<String, RateLimit>,
+}
+
+impl RateLimitsConfig {
+    pub fn limit_for(&self, tool: &str) -> RateLimit {
+        let Some(limit) = self.tools.get(tool) else {
+            return self.default.clone();
+        };
+        RateLimit {
+            per_minute: limit.per_minute.or(self.default.per_minute),
+            burst: limit.burst.or(self.default.burst),
+            session_quota: limit.session_quota.or(self.default.session_quota),
+        }
+    }
+}
+
+/// A named set of overrides applied on top of the base configuration.
+///
+/// Sections use the same shape as the top-level `resources`/`tools` objects.
+/// Setting a section to `null` disables it, while an object is merged into the
+/// inherited value.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct Profile {
+    /// Name of another profile to inherit from instead of the base config
+    #[serde(default)]
+    pub inherits: Option<String>,
+    #[serde(default)]
+    pub resources: serde_json::Map<String, serde_json::Value>,
+    #[serde(default)]
+    pub tools: serde_json::Map<String, serde_json::Value>,
+}
+
 // Main configuration struct
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct Config {
@@ -168,6 +396,20 @@ pub struct Config {
     pub resources: ResourcesConfig,
     #[serde(default)]
     pub tools: ToolsConfig,
+    #[serde(default)]
+    pub audit: AuditConfig,
+    #[serde(default)]
+    pub rate_limits: RateLimitsConfig,
+    #[serde(default)]
+    pub evolution_cache: EvolutionCacheConfig,
+    /// Describe the effect of state-changing tools instead of running them
+    #[serde(default)]
+    pub dry_run: bool,
+    #[serde(default)]
+    pub profiles: HashMap<String, Profile>,
+    /// Name of the profile this configuration was resolved for
+    #[serde(skip)]
+    pub active_profile: Option<String>,
 }
 
 impl Config {
@@ -205,6 +447,42 @@ impl Config {
         Ok(Config::default())
     }
 
+    /// Resolve the named profile into a standalone configuration.
+    ///
+    /// Profiles are applied from the root of their `inherits` chain down to
+    /// the requested one, each on top of the base configuration.
+    pub fn with_profile(&self, name: &str) -> Result<Self> {
+        let mut chain: Vec<&str> = Vec::new();
+        let mut current = Some(name);
+        while let Some(profile_name) = current {
+            if chain.contains(&profile_name) {
+                anyhow::bail!("Profile '{}' inherits from itself", profile_name);
+            }
+            let profile = self
+                .profiles
+                .get(profile_name)
+                .with_context(|| format!("Unknown profile: {profile_name}"))?;
+            chain.push(profile_name);
+            current = profile.inherits.as_deref();
+        }
+
+        let mut value = serde_json::to_value(Config {
+            profiles: HashMap::new(),
+            ..self.clone()
+        })?;
+        for profile_name in chain.iter().rev() {
+            let profile = &self.profiles[*profile_name];
+            merge_sections(&mut value["resources"], &profile.resources);
+            merge_sections(&mut value["tools"], &profile.tools);
+        }
+
+        let mut config: Config = serde_json::from_value(value)
+            .with_context(|| format!("Invalid configuration for profile '{name}'"))?;
+        config.profiles = self.profiles.clone();
+        config.active_profile = Some(name.to_owned());
+        Ok(config)
+    }
+
     // Resource enablement checks
     pub fn is_resource_enabled<T: ResourceProvider>(&self) -> bool {
         match T::NAME {
@@ -214,8 +492,16 @@ impl Config {
             }
             crate::resources::calendar::Calendar::NAME => self.resources.calendar.is_some(),
             crate::resources::tasks::Tasks::NAME => self.resources.tasks.is_some(),
-            crate::resources::contacts::Contacts::NAME => self.resources.contacts.is_some(),
+            crate::resources::memos::Memos::NAME => self.resources.memos.is_some(),
+            crate::resources::reminders::Reminders::NAME => self.resources.reminders.is_some(),
+            crate::resources::agenda::Agenda::NAME => self.resources.agenda.is_some(),
+            crate::resources::contacts::Contacts::NAME
+            | crate::resources::contacts::ContactPhoto::NAME
+            | crate::resources::contacts::UpcomingDates::NAME => self.resources.contacts.is_some(),
+            crate::resources::sources::Sources::NAME => self.resources.sources.is_some(),
             crate::resources::audio::Audio::NAME => self.resources.audio.is_some(),
+            crate::resources::audit::Audit::NAME => self.resources.audit.is_some(),
+            crate::resources::undo::UndoHistory::NAME => self.resources.undo_history.is_some(),
             _ => true, // Unknown resources are enabled by default
         }
     }
@@ -237,6 +523,24 @@ impl Config {
                 self.tools.window_management.is_some()
             }
             crate::tools::keyring::Keyring::NAME => self.tools.keyring.is_some(),
+            crate::tools::calendar::CreateCalendarEvent::NAME
+            | crate::tools::calendar::UpdateCalendarEvent::NAME
+            | crate::tools::calendar::DeleteCalendarEvent::NAME
+            | crate::tools::calendar::RespondToInvitation::NAME => self.tools.calendar.is_some(),
+            crate::tools::tasks::TaskManagement::NAME => self.tools.tasks.is_some(),
+            crate::tools::memos::MemoManagement::NAME => self.tools.memos.is_some(),
+            crate::tools::sources::SourceManagement::NAME => self.tools.sources.is_some(),
+            crate::tools::contacts::CreateContact::NAME
+            | crate::tools::contacts::UpdateContact::NAME
+            | crate::tools::contacts::DeleteContact::NAME => self.tools.contacts.is_some(),
+            crate::tools::free_busy::FindFreeSlots::NAME => self.tools.free_busy.is_some(),
+            crate::tools::search::SearchContacts::NAME
+            | crate::tools::search::SearchEvents::NAME
+            | crate::tools::search::SearchTasks::NAME => self.tools.search.is_some(),
+            crate::tools::import_export::ImportCalendarFile::NAME
+            | crate::tools::import_export::ImportContactsFile::NAME
+            | crate::tools::import_export::ExportToFile::NAME => self.tools.import_export.is_some(),
+            crate::tools::undo::Undo::NAME => self.tools.undo.is_some(),
             _ => true, // Unknown tools are enabled by default
         }
     }
@@ -250,10 +554,22 @@ impl Config {
         self.resources.tasks.clone().unwrap_or_default()
     }
 
+    pub fn get_memos_config(&self) -> MemosConfig {
+        self.resources.memos.clone().unwrap_or_default()
+    }
+
+    pub fn get_reminders_config(&self) -> RemindersConfig {
+        self.resources.reminders.clone().unwrap_or_default()
+    }
+
     pub fn get_contacts_config(&self) -> ContactsConfig {
         self.resources.contacts.clone().unwrap_or_default()
     }
 
+    pub fn get_audit_resource_config(&self) -> AuditResourceConfig {
+        self.resources.audit.clone().unwrap_or_default()
+    }
+
     pub fn get_audio_tool_config(&self) -> AudioToolConfig {
         self.tools.audio.clone().unwrap_or_default()
     }
@@ -261,10 +577,59 @@ impl Config {
     pub fn get_screenshot_config(&self) -> ScreenshotConfig {
         self.tools.screenshot.clone().unwrap_or_default()
     }
+
+    pub fn get_undo_config(&self) -> UndoConfig {
+        self.tools.undo.clone().unwrap_or_default()
+    }
+}
+
+/// Merge profile overrides into a serialized config section.
+fn merge_sections(
+    target: &mut serde_json::Value,
+    overrides: &serde_json::Map<String, serde_json::Value>,
+) {
+    for (key, value) in overrides {
+        match (target.get_mut(key), value) {
+            (Some(serde_json::Value::Object(existing)), serde_json::Value::Object(fields)) => {
+                existing.extend(fields.clone());
+            }
+            _ => {
+                target[key] = value.clone();
+            }
+        }
+    }
+}
+
+/// Profile requested with `--profile <name>` or the `GNOME_MCP_PROFILE`
+/// environment variable, the command line taking precedence.
+pub fn selected_profile() -> Option<String> {
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == "--profile" {
+            return args.next();
+        }
+        if let Some(name) = arg.strip_prefix("--profile=") {
+            return Some(name.to_owned());
+        }
+    }
+    std::env::var("GNOME_MCP_PROFILE")
+        .ok()
+        .filter(|name| !name.is_empty())
 }
 
 // Global config instance
-pub static CONFIG: LazyLock<Config> = LazyLock::new(|| Config::load_default().unwrap_or_default());
+pub static CONFIG: LazyLock<Config> = LazyLock::new(|| {
+    let config = Config::load_default().unwrap_or_default();
+    let Some(profile) = selected_profile() else {
+        return config;
+    };
+    // Falling back to the base config would expose tools the profile was
+    // meant to hide, so refuse to start instead.
+    config.with_profile(&profile).unwrap_or_else(|err| {
+        tracing::error!("Failed to load profile '{}': {:#}", profile, err);
+        std::process::exit(1);
+    })
+});
 
 #[cfg(test)]
 mod tests {
@@ -298,6 +663,10 @@ mod tests {
         let calendar_config = config.get_calendar_config();
         assert_eq!(calendar_config.days_ahead, 60);
         assert_eq!(calendar_config.days_behind, 7);
+
+        let profile = config.with_profile("coding-agent").unwrap();
+        assert!(!profile.is_resource_enabled::<crate::resources::calendar::Calendar>());
+        assert!(profile.is_tool_enabled::<crate::tools::audio::Volume>());
     }
 
     #[test]
@@ -306,11 +675,13 @@ mod tests {
             "resources": {
                 "calendar": {
                     "days_ahead": 60,
-                    "days_behind": 7
+                    "days_behind": 7,
+                    "exclude_sources": ["Birthdays & Anniversaries"]
                 },
                 "tasks": {
                     "include_completed": false
-                }
+                },
+                "audit": {}
             },
             "tools": {
                 "notifications": {
@@ -327,9 +698,109 @@ mod tests {
         let calendar_config = config.get_calendar_config();
         assert_eq!(calendar_config.days_ahead, 60);
         assert_eq!(calendar_config.days_behind, 7);
+        assert!(calendar_config.sources.include.is_empty());
+        assert_eq!(
+            calendar_config.sources.exclude,
+            vec!["Birthdays & Anniversaries"]
+        );
 
         let tasks_config = config.get_tasks_config();
         assert!(!tasks_config.include_completed);
         assert!(!tasks_config.include_cancelled);
+        assert_eq!(tasks_config.sources, Default::default());
+
+        assert_eq!(config.get_audit_resource_config().limit, 50);
+    }
+
+    #[test]
+    fn test_profile_overrides_base() {
+        let json = r#"{
+            "tools": {
+                "notifications": {},
+                "keyring": {},
+                "window_management": {},
+                "audio": {
+                    "volume_step": 10
+                }
+            },
+            "profiles": {
+                "coding": {
+                    "tools": {
+                        "keyring": null,
+                        "window_management": null
+                    }
+                },
+                "quiet": {
+                    "inherits": "coding",
+                    "resources": {
+                        "calendar": {
+                            "days_ahead": 3
+                        }
+                    },
+                    "tools": {
+                        "audio": {
+                            "volume_step": 2
+                        }
+                    }
+                }
+            }
+        }"#;
+
+        let config: Config = serde_json::from_str(json).unwrap();
+        assert!(config.is_tool_enabled::<crate::tools::keyring::Keyring>());
+
+        let coding = config.with_profile("coding").unwrap();
+        assert_eq!(coding.active_profile.as_deref(), Some("coding"));
+        assert!(coding.is_tool_enabled::<crate::tools::notifications::Notifications>());
+        assert!(!coding.is_tool_enabled::<crate::tools::keyring::Keyring>());
+        assert!(!coding.is_tool_enabled::<crate::tools::window_management::WindowManagement>());
+
+        let quiet = config.with_profile("quiet").unwrap();
+        assert!(!quiet.is_tool_enabled::<crate::tools::keyring::Keyring>());
+        assert_eq!(quiet.get_audio_tool_config().volume_step, 2);
+        assert_eq!(quiet.get_calendar_config().days_ahead, 3);
+        assert_eq!(quiet.get_calendar_config().days_behind, 0);
+    }
+
+    #[test]
+    fn test_rate_limit_fallback() {
+        let json = r#"{
+            "rate_limits": {
+                "default": { "per_minute": 60, "burst": 10 },
+                "tools": {
+                    "send_notification": { "per_minute": 6, "session_quota": 50 }
+                }
+            }
+        }"#;
+
+        let config: Config = serde_json::from_str(json).unwrap();
+
+        let notifications = config.rate_limits.limit_for("send_notification");
+        assert_eq!(notifications.per_minute, Some(6.0));
+        assert_eq!(notifications.burst, Some(10));
+        assert_eq!(notifications.session_quota, Some(50));
+
+        let volume = config.rate_limits.limit_for("set_volume");
+        assert_eq!(volume.per_minute, Some(60.0));
+        assert_eq!(volume.session_quota, None);
+
+        assert!(Config::default()
+            .rate_limits
+            .limit_for("set_volume")
+            .is_unlimited());
+    }
+
+    #[test]
+    fn test_profile_errors() {
+        let json = r#"{
+            "profiles": {
+                "a": { "inherits": "b" },
+                "b": { "inherits": "a" }
+            }
+        }"#;
+
+        let config: Config = serde_json::from_str(json).unwrap();
+        assert!(config.with_profile("missing").is_err());
+        assert!(config.with_profile("a").is_err());
     }
 }
@@ -0,0 +1,226 @@
+use calcard::icalendar::{
+    timezone::TzResolver, ICalendar, ICalendarComponent, ICalendarComponentType,
+    ICalendarParameter, ICalendarProperty, ICalendarValue, Related,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::gnome::evolution::{
+    ical::{self, PropertyList},
+    present_time,
+};
+
+/// A VALARM reminder of an event or a task
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Alarm {
+    pub action: String, // AUDIO/DISPLAY/EMAIL
+    pub trigger: Trigger,
+    pub description: Option<String>,
+}
+
+/// When an alarm goes off
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Trigger {
+    /// Minutes from the start, or from the end (DTEND or DUE) when
+    /// `related_end` is set, negative before it
+    Relative {
+        offset_minutes: i64,
+        related_end: bool,
+    },
+    Absolute {
+        time: DateTime<Utc>,
+    },
+}
+
+impl Alarm {
+    /// A DISPLAY alarm going off `minutes` before the start, or before the
+    /// end when `related_end` is set
+    pub fn display(minutes: i64, related_end: bool, description: Option<String>) -> Self {
+        Alarm {
+            action: "DISPLAY".to_owned(),
+            trigger: Trigger::Relative {
+                offset_minutes: -minutes,
+                related_end,
+            },
+            description,
+        }
+    }
+
+    /// When the alarm goes off for a component spanning `start` to `end`,
+    /// either bound standing in for the other when missing
+    pub fn fire_time(
+        &self,
+        start: Option<DateTime<Utc>>,
+        end: Option<DateTime<Utc>>,
+    ) -> Option<DateTime<Utc>> {
+        match self.trigger {
+            Trigger::Relative {
+                offset_minutes,
+                related_end,
+            } => {
+                let anchor = match related_end {
+                    true => end.or(start)?,
+                    false => start.or(end)?,
+                };
+                Some(anchor + chrono::Duration::minutes(offset_minutes))
+            }
+            Trigger::Absolute { time } => Some(time),
+        }
+    }
+
+    /// JSON representation, with absolute triggers in the `local` timezone
+    pub fn to_json(&self, local: chrono_tz::Tz) -> serde_json::Value {
+        let mut json = serde_json::to_value(self).unwrap_or_default();
+        if let Trigger::Absolute { time } = self.trigger {
+            json["trigger"]["time"] = present_time(Some(time), local, false);
+        }
+        json
+    }
+
+    /// Unfolded content lines of the VALARM component
+    pub fn to_lines(&self) -> Vec<String> {
+        let mut properties = PropertyList::default();
+        properties.raw("ACTION", "", Some(&self.action));
+        match self.trigger {
+            Trigger::Relative {
+                offset_minutes,
+                related_end,
+            } => properties.raw(
+                "TRIGGER",
+                if related_end { ";RELATED=END" } else { "" },
+                Some(&format!(
+                    "{}PT{}M",
+                    if offset_minutes < 0 { "-" } else { "" },
+                    offset_minutes.abs()
+                )),
+            ),
+            Trigger::Absolute { time } => {
+                properties.raw("TRIGGER", ";VALUE=DATE-TIME", Some(&ical::format_utc(time)))
+            }
+        }
+        properties.text("DESCRIPTION", self.description.as_deref());
+        properties.lines("VALARM")
+    }
+
+    /// Read a VALARM component, resolving the TZID of an absolute trigger
+    /// with `tz_resolver`
+    fn from_component(component: &ICalendarComponent, tz_resolver: &TzResolver) -> Option<Self> {
+        let entry = component.property(&ICalendarProperty::Trigger)?;
+        let trigger = match entry.values.first()? {
+            ICalendarValue::Duration(duration) => Trigger::Relative {
+                offset_minutes: duration.as_seconds() / 60,
+                related_end: entry
+                    .params
+                    .iter()
+                    .any(|param| matches!(param, ICalendarParameter::Related(Related::End))),
+            },
+            _ => Trigger::Absolute {
+                time: ical::date_time_value(entry, tz_resolver)?.0,
+            },
+        };
+
+        let action = component
+            .property(&ICalendarProperty::Action)
+            .and_then(|p| p.values.first())
+            .and_then(|v| match v {
+                ICalendarValue::Action(action) => Some(action.as_str()),
+                v => v.as_text(),
+            })
+            .unwrap_or("DISPLAY")
+            .to_owned();
+
+        let description = component
+            .property(&ICalendarProperty::Description)
+            .and_then(|p| p.values.first())
+            .and_then(|v| v.as_text())
+            .filter(|s| !s.is_empty())
+            .map(|s| s.to_string());
+
+        Some(Alarm {
+            action,
+            trigger,
+            description,
+        })
+    }
+}
+
+/// The alarms nested in `component`, one of the components of `calendar`
+pub fn alarms(
+    calendar: &ICalendar,
+    component: &ICalendarComponent,
+    tz_resolver: &TzResolver,
+) -> Vec<Alarm> {
+    component
+        .component_ids
+        .iter()
+        .filter_map(|id| calendar.components.get(*id as usize))
+        .filter(|c| matches!(c.component_type, ICalendarComponentType::VAlarm))
+        .filter_map(|c| Alarm::from_component(c, tz_resolver))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    #[test]
+    fn test_alarm_round_trip() {
+        let start = Utc.with_ymd_and_hms(2024, 7, 10, 8, 0, 0).unwrap();
+        let end = start + chrono::Duration::hours(1);
+        let relative = Alarm::display(15, false, Some("Standup".to_owned()));
+        let before_end = Alarm::display(5, true, None);
+        let absolute = Alarm {
+            action: "AUDIO".to_owned(),
+            trigger: Trigger::Absolute {
+                time: start - chrono::Duration::days(1),
+            },
+            description: None,
+        };
+
+        let mut lines = vec![
+            "BEGIN:VCALENDAR".to_owned(),
+            "BEGIN:VEVENT".to_owned(),
+            "UID:alarm-1".to_owned(),
+        ];
+        for alarm in [&relative, &before_end, &absolute] {
+            lines.extend(alarm.to_lines());
+        }
+        lines.extend(["END:VEVENT".to_owned(), "END:VCALENDAR".to_owned()]);
+        assert!(lines.contains(&"TRIGGER:-PT15M".to_owned()));
+        assert!(lines.contains(&"TRIGGER;RELATED=END:-PT5M".to_owned()));
+        assert!(lines.contains(&"TRIGGER;VALUE=DATE-TIME:20240709T080000Z".to_owned()));
+
+        let calendar = ICalendar::parse(ical::fold(&lines)).unwrap();
+        let tz_resolver = calendar
+            .build_tz_resolver()
+            .with_default(calcard::common::timezone::Tz::UTC);
+        let event = calendar
+            .components
+            .iter()
+            .find(|c| matches!(c.component_type, ICalendarComponentType::VEvent))
+            .unwrap();
+        let parsed = alarms(&calendar, event, &tz_resolver);
+        assert_eq!(parsed, vec![relative, before_end, absolute]);
+
+        let fire_times = parsed
+            .iter()
+            .map(|alarm| alarm.fire_time(Some(start), Some(end)))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            fire_times,
+            vec![
+                Some(start - chrono::Duration::minutes(15)),
+                Some(end - chrono::Duration::minutes(5)),
+                Some(start - chrono::Duration::days(1)),
+            ]
+        );
+        assert_eq!(
+            parsed[1].fire_time(Some(start), None),
+            Some(start - chrono::Duration::minutes(5))
+        );
+        assert_eq!(parsed[0].fire_time(None, None), None);
+    }
+}
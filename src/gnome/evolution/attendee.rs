@@ -0,0 +1,159 @@
+use calcard::icalendar::{ICalendarEntry, ICalendarParameter};
+use serde::{Deserialize, Serialize};
+
+use crate::gnome::evolution::{ical::cal_address, Contact};
+
+/// An attendee or the organizer of an event, with the parameters of its
+/// CAL-ADDRESS
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Attendee {
+    pub email: String,           // Address without the mailto: scheme
+    pub name: Option<String>,    // CN
+    pub kind: Option<String>,    // CUTYPE: INDIVIDUAL/GROUP/RESOURCE/ROOM
+    pub role: Option<String>,    // CHAIR/REQ-PARTICIPANT/OPT-PARTICIPANT/NON-PARTICIPANT
+    pub status: Option<String>,  // PARTSTAT: NEEDS-ACTION/ACCEPTED/DECLINED/TENTATIVE
+    pub rsvp: bool,              // Whether a reply is expected
+    pub contact: Option<String>, // Full name of the matching contact
+}
+
+impl Attendee {
+    /// A new attendee asked to reply
+    pub fn invite(email: &str) -> Self {
+        Attendee {
+            email: strip_mailto(email).to_owned(),
+            rsvp: true,
+            ..Default::default()
+        }
+    }
+
+    /// Read an ATTENDEE or ORGANIZER property
+    pub fn from_entry(entry: &ICalendarEntry) -> Option<Self> {
+        let address = entry
+            .values
+            .first()
+            .and_then(|v| v.as_text())
+            .filter(|s| !s.is_empty())?;
+
+        let mut attendee = Attendee {
+            email: strip_mailto(address).to_owned(),
+            ..Default::default()
+        };
+        for param in &entry.params {
+            match param {
+                ICalendarParameter::Cn(name) if !name.is_empty() => {
+                    attendee.name = Some(name.clone())
+                }
+                ICalendarParameter::Cutype(kind) => attendee.kind = Some(kind.as_str().to_owned()),
+                ICalendarParameter::Role(role) => attendee.role = Some(role.as_str().to_owned()),
+                ICalendarParameter::Partstat(status) => {
+                    attendee.status = Some(status.as_str().to_owned())
+                }
+                ICalendarParameter::Rsvp(rsvp) => attendee.rsvp = *rsvp,
+                _ => {}
+            }
+        }
+        Some(attendee)
+    }
+
+    /// The CAL-ADDRESS value
+    pub fn address(&self) -> String {
+        cal_address(&self.email)
+    }
+
+    /// `;`-prefixed parameters of the property
+    pub fn params(&self) -> String {
+        let mut params = String::new();
+        if let Some(name) = &self.name {
+            // Quoted values can't hold quotes themselves
+            params.push_str(&format!(";CN=\"{}\"", name.replace('"', "")));
+        }
+        for (param, value) in [
+            ("CUTYPE", &self.kind),
+            ("ROLE", &self.role),
+            ("PARTSTAT", &self.status),
+        ] {
+            if let Some(value) = value {
+                params.push_str(&format!(";{param}={value}"));
+            }
+        }
+        if self.rsvp {
+            params.push_str(";RSVP=TRUE");
+        }
+        params
+    }
+
+    /// Whether this is `email`, compared without the scheme nor case
+    pub fn is(&self, email: &str) -> bool {
+        self.email.eq_ignore_ascii_case(strip_mailto(email.trim()))
+    }
+
+    /// Set the name of the first contact having this address
+    pub fn match_contact(&mut self, contacts: &[Contact]) {
+        self.contact = contacts
+            .iter()
+            .find(|contact| contact.emails.iter().any(|email| self.is(&email.value)))
+            .and_then(|contact| contact.full_name.clone());
+    }
+}
+
+fn strip_mailto(address: &str) -> &str {
+    match address.split_once(':') {
+        Some((scheme, email)) if scheme.eq_ignore_ascii_case("mailto") => email,
+        _ => address,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gnome::evolution::TypedValue;
+
+    #[test]
+    fn test_attendee_params() {
+        let ical = "BEGIN:VCALENDAR\r\nBEGIN:VEVENT\r\nUID:1\r\n\
+                    ATTENDEE;CUTYPE=INDIVIDUAL;ROLE=REQ-PARTICIPANT;PARTSTAT=TENTATIVE;\r\n \
+                    RSVP=TRUE;CN=\"Smith, Anna\";LANGUAGE=en:MAILTO:anna@example.com\r\n\
+                    END:VEVENT\r\nEND:VCALENDAR\r\n";
+        let calendar = calcard::icalendar::ICalendar::parse(ical).unwrap();
+        let entry = calendar.components[1]
+            .property(&calcard::icalendar::ICalendarProperty::Attendee)
+            .unwrap();
+
+        let mut attendee = Attendee::from_entry(entry).unwrap();
+        assert_eq!(
+            attendee,
+            Attendee {
+                email: "anna@example.com".to_owned(),
+                name: Some("Smith, Anna".to_owned()),
+                kind: Some("INDIVIDUAL".to_owned()),
+                role: Some("REQ-PARTICIPANT".to_owned()),
+                status: Some("TENTATIVE".to_owned()),
+                rsvp: true,
+                contact: None,
+            }
+        );
+        assert_eq!(
+            attendee.params(),
+            ";CN=\"Smith, Anna\";CUTYPE=INDIVIDUAL;ROLE=REQ-PARTICIPANT;PARTSTAT=TENTATIVE;RSVP=TRUE"
+        );
+        assert_eq!(attendee.address(), "mailto:anna@example.com");
+        assert!(attendee.is("mailto:Anna@Example.com"));
+
+        let contacts = [
+            Contact {
+                full_name: Some("Bob".to_owned()),
+                emails: vec![TypedValue::new("bob@example.com")],
+                ..Default::default()
+            },
+            Contact {
+                full_name: Some("Anna Smith".to_owned()),
+                emails: vec![TypedValue::new("ANNA@example.com")],
+                ..Default::default()
+            },
+        ];
+        attendee.match_contact(&contacts);
+        assert_eq!(attendee.contact.as_deref(), Some("Anna Smith"));
+
+        assert_eq!(Attendee::invite("bob@example.com").params(), ";RSVP=TRUE");
+    }
+}
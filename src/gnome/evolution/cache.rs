@@ -0,0 +1,657 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    str::FromStr,
+    sync::{LazyLock, Mutex, MutexGuard},
+    time::Duration,
+};
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use futures_util::StreamExt;
+use zbus::zvariant::OwnedObjectPath;
+
+use crate::gnome::evolution::{
+    ical, CalendarQuery, Contact, ContactQuery, Event, Memo, SourceError, SourceFilter, SourceInfo,
+    SourceResults, SourceType, Task,
+};
+
+static CACHE: LazyLock<Mutex<Cache>> = LazyLock::new(Default::default);
+
+/// How long to wait before opening the view of a failed source again
+const RETRY_DELAY: Duration = Duration::from_secs(60);
+
+/// A change announced by a view
+#[derive(Debug, Clone, PartialEq)]
+enum Change {
+    Added(Vec<String>),
+    Modified(Vec<String>),
+    /// Contact UIDs, or `uid\nrid` pairs for calendar objects
+    Removed(Vec<String>),
+}
+
+#[derive(Debug)]
+enum Objects {
+    /// Raw components, expanded into occurrences on each read
+    Calendar(BTreeMap<String, String>),
+    TaskList(BTreeMap<String, Task>),
+    MemoList(BTreeMap<String, Memo>),
+    AddressBook(BTreeMap<String, Contact>),
+}
+
+/// The objects of a source, as last announced by its view
+#[derive(Debug)]
+struct SourceCache {
+    info: SourceInfo,
+    objects: Objects,
+    /// Definitions of the TZIDs that don't name a known zone
+    timezones: BTreeMap<String, String>,
+    /// Set once the view delivered the initial objects
+    ready: bool,
+    /// Why the view could not be opened, until it is
+    error: Option<String>,
+    /// Dropped to close the view
+    _stop: Option<tokio::sync::oneshot::Sender<()>>,
+}
+
+impl SourceCache {
+    fn new(info: SourceInfo) -> Self {
+        let objects = match info.source_type {
+            SourceType::Calendar { .. } => Objects::Calendar(BTreeMap::new()),
+            SourceType::TaskList { .. } => Objects::TaskList(BTreeMap::new()),
+            SourceType::MemoList { .. } => Objects::MemoList(BTreeMap::new()),
+            SourceType::AddressBook { .. } => Objects::AddressBook(BTreeMap::new()),
+        };
+        Self {
+            info,
+            objects,
+            timezones: BTreeMap::new(),
+            ready: false,
+            error: None,
+            _stop: None,
+        }
+    }
+
+    /// Whether reads can be answered from this source, with its objects or
+    /// its error
+    fn is_settled(&self) -> bool {
+        self.ready || self.error.is_some()
+    }
+
+    fn apply(&mut self, change: Change) {
+        match change {
+            Change::Added(objects) | Change::Modified(objects) => {
+                for data in objects {
+                    match &mut self.objects {
+                        Objects::Calendar(events) => {
+                            events.insert(component_id(&data), data);
+                        }
+                        Objects::TaskList(tasks) => {
+                            if let Ok(task) = Task::from_str(&data) {
+                                tasks.insert(component_id(&data), task);
+                            }
+                        }
+                        Objects::MemoList(memos) => {
+                            if let Ok(memo) = Memo::from_str(&data) {
+                                memos.insert(component_id(&data), memo);
+                            }
+                        }
+                        Objects::AddressBook(contacts) => {
+                            if let Ok(contact) = Contact::from_str(&data) {
+                                contacts.insert(contact.uid.clone(), contact);
+                            }
+                        }
+                    }
+                }
+            }
+            Change::Removed(ids) => {
+                for id in ids {
+                    match &mut self.objects {
+                        Objects::Calendar(events) => remove_component(events, &id),
+                        Objects::TaskList(tasks) => remove_component(tasks, &id),
+                        Objects::MemoList(memos) => remove_component(memos, &id),
+                        Objects::AddressBook(contacts) => {
+                            contacts.remove(&id);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn error(&self) -> Option<SourceError> {
+        self.error.as_ref().map(|error| SourceError {
+            uid: self.info.uid.clone(),
+            display_name: self.info.display_name.clone(),
+            error: error.clone(),
+        })
+    }
+}
+
+/// `uid\nrid` of the component, the way views identify removed objects
+fn component_id(data: &str) -> String {
+    let lines = ical::unfold(data);
+    let value = |name: &str| {
+        lines
+            .iter()
+            .find(|line| ical::property_name(line) == name)
+            .and_then(|line| line.split_once(':'))
+            .map(|(_, value)| value.to_owned())
+            .unwrap_or_default()
+    };
+    format!("{}\n{}", value("UID"), value("RECURRENCE-ID"))
+}
+
+/// Remove a component, or a whole series when no RECURRENCE-ID is given
+fn remove_component<T>(objects: &mut BTreeMap<String, T>, id: &str) {
+    match id.split_once('\n') {
+        Some((_, rid)) if !rid.is_empty() => {
+            objects.remove(id);
+        }
+        _ => {
+            let prefix = format!("{}\n", id.trim_end_matches('\n'));
+            objects.retain(|key, _| !key.starts_with(&prefix));
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct Cache {
+    running: bool,
+    sources: HashMap<String, SourceCache>,
+}
+
+impl Cache {
+    /// The sources of a kind that `filter` allows, if they can all be read
+    /// from memory
+    fn select(
+        &self,
+        filter: &SourceFilter,
+        is_kind: fn(&SourceType) -> bool,
+    ) -> Option<Vec<&SourceCache>> {
+        if !self.running {
+            return None;
+        }
+        let mut selected = self
+            .sources
+            .values()
+            .filter(|source| is_kind(&source.info.source_type) && filter.allows(&source.info))
+            .collect::<Vec<_>>();
+        // Let a direct read report selectors that name nothing
+        if selected.is_empty() && !filter.include.is_empty() {
+            return None;
+        }
+        if !selected.iter().all(|source| source.is_settled()) {
+            return None;
+        }
+        selected.sort_by(|a, b| a.info.display_name.cmp(&b.info.display_name));
+        Some(selected)
+    }
+
+    /// Bring the cached sources in line with the enabled `sources`: drop
+    /// the ones that are gone or disabled, which closes their views, take
+    /// in renames and recolors, and return the new ones, whose views are to
+    /// be opened until the receiver fires
+    fn update(
+        &mut self,
+        sources: HashMap<String, SourceInfo>,
+    ) -> Vec<(SourceInfo, tokio::sync::oneshot::Receiver<()>)> {
+        self.sources.retain(|uid, _| sources.contains_key(uid));
+        let mut added = Vec::new();
+        for (uid, info) in sources {
+            match self.sources.get_mut(&uid) {
+                Some(source) => source.info = info,
+                None => {
+                    let (stop, stopped) = tokio::sync::oneshot::channel();
+                    let mut source = SourceCache::new(info.clone());
+                    source._stop = Some(stop);
+                    self.sources.insert(uid, source);
+                    added.push((info, stopped));
+                }
+            }
+        }
+        added
+    }
+}
+
+fn cache() -> MutexGuard<'static, Cache> {
+    CACHE.lock().unwrap_or_else(|err| err.into_inner())
+}
+
+/// Occurrences within the time range of the cached events, or `None` when
+/// the calendars have to be read directly
+pub fn events(
+    start_time: DateTime<Utc>,
+    end_time: DateTime<Utc>,
+    filter: &SourceFilter,
+) -> Option<SourceResults<Event>> {
+    let mut calendars = Vec::new();
+    let mut errors = Vec::new();
+    {
+        let cache = cache();
+        for source in cache.select(filter, |kind| matches!(kind, SourceType::Calendar { .. }))? {
+            match (source.error(), &source.objects) {
+                (Some(error), _) => errors.push(error),
+                (None, Objects::Calendar(objects)) => calendars.push(
+                    objects
+                        .values()
+                        .chain(source.timezones.values())
+                        .cloned()
+                        .collect::<Vec<_>>(),
+                ),
+                _ => {}
+            }
+        }
+    }
+
+    // Expand outside of the lock, this is the expensive part
+    let mut items = calendars
+        .iter()
+        .flat_map(|objects| Event::expand(objects, start_time, end_time))
+        .collect::<Vec<_>>();
+    items.sort_by_key(|event| event.start_time);
+    Some(SourceResults { items, errors })
+}
+
+/// The cached tasks, or `None` when the task lists have to be read directly
+pub fn tasks(filter: &SourceFilter) -> Option<SourceResults<Task>> {
+    let cache = cache();
+    let mut results = SourceResults {
+        items: Vec::new(),
+        errors: Vec::new(),
+    };
+    for source in cache.select(filter, |kind| matches!(kind, SourceType::TaskList { .. }))? {
+        match (source.error(), &source.objects) {
+            (Some(error), _) => results.errors.push(error),
+            (None, Objects::TaskList(tasks)) => results.items.extend(tasks.values().cloned()),
+            _ => {}
+        }
+    }
+    Some(results)
+}
+
+/// The cached memos, or `None` when the memo lists have to be read directly
+pub fn memos(filter: &SourceFilter) -> Option<SourceResults<Memo>> {
+    let cache = cache();
+    let mut results = SourceResults {
+        items: Vec::new(),
+        errors: Vec::new(),
+    };
+    for source in cache.select(filter, |kind| matches!(kind, SourceType::MemoList { .. }))? {
+        match (source.error(), &source.objects) {
+            (Some(error), _) => results.errors.push(error),
+            (None, Objects::MemoList(memos)) => results.items.extend(memos.values().cloned()),
+            _ => {}
+        }
+    }
+    Some(results)
+}
+
+/// The cached contacts, or `None` when the address books have to be read
+/// directly
+pub fn contacts(filter: &SourceFilter) -> Option<SourceResults<Contact>> {
+    let cache = cache();
+    let mut results = SourceResults {
+        items: Vec::new(),
+        errors: Vec::new(),
+    };
+    for source in cache.select(filter, |kind| {
+        matches!(kind, SourceType::AddressBook { .. })
+    })? {
+        match (source.error(), &source.objects) {
+            (Some(error), _) => results.errors.push(error),
+            (None, Objects::AddressBook(contacts)) => {
+                results.items.extend(contacts.values().cloned())
+            }
+            _ => {}
+        }
+    }
+    Some(results)
+}
+
+/// Start keeping the sources in memory, in the background
+pub fn spawn() {
+    {
+        let mut cache = cache();
+        if cache.running {
+            return;
+        }
+        cache.running = true;
+    }
+
+    tokio::spawn(async {
+        if let Err(err) = run().await {
+            tracing::warn!("Evolution cache stopped: {:#}", err);
+        }
+        // Go back to direct reads
+        let mut cache = cache();
+        cache.running = false;
+        cache.sources.clear();
+    });
+}
+
+/// Follow the sources the SourceManager adds and removes, and the changes
+/// to their settings, which is how sources get disabled, enabled or renamed
+async fn run() -> Result<()> {
+    let connection = zbus::Connection::session().await?;
+    let manager = zbus::fdo::ObjectManagerProxy::builder(&connection)
+        .destination("org.gnome.evolution.dataserver.Sources5")?
+        .path("/org/gnome/evolution/dataserver/SourceManager")?
+        .build()
+        .await?;
+    let mut added = manager.receive_interfaces_added().await?;
+    let mut removed = manager.receive_interfaces_removed().await?;
+    let rule = zbus::MatchRule::builder()
+        .msg_type(zbus::message::Type::Signal)
+        .sender("org.gnome.evolution.dataserver.Sources5")?
+        .interface("org.freedesktop.DBus.Properties")?
+        .member("PropertiesChanged")?
+        .path_namespace("/org/gnome/evolution/dataserver/SourceManager")?
+        .add_arg("org.gnome.evolution.dataserver.Source")?
+        .build();
+    let mut changed = zbus::MessageStream::for_match_rule(rule, &connection, None).await?;
+
+    sync_sources(&connection).await?;
+    loop {
+        tokio::select! {
+            Some(_) = added.next() => sync_sources(&connection).await?,
+            Some(_) = removed.next() => sync_sources(&connection).await?,
+            Some(_) = changed.next() => sync_sources(&connection).await?,
+            else => anyhow::bail!("The source manager went away"),
+        }
+    }
+}
+
+/// Open views on new and enabled sources and close those of the sources
+/// that are gone or disabled
+async fn sync_sources(connection: &zbus::Connection) -> Result<()> {
+    let sources = crate::gnome::evolution::get_evolution_sources(connection)
+        .await?
+        .into_values()
+        .map(|(info, _proxy)| (info.uid.clone(), info))
+        .collect::<HashMap<_, _>>();
+
+    for (info, stopped) in cache().update(sources) {
+        tokio::spawn(watch_source(connection.clone(), info, stopped));
+    }
+    Ok(())
+}
+
+/// Keep the view of a source open until the source goes away, reopening it
+/// after failures
+async fn watch_source(
+    connection: zbus::Connection,
+    info: SourceInfo,
+    mut stopped: tokio::sync::oneshot::Receiver<()>,
+) {
+    loop {
+        let error = match follow_view(&connection, &info, &mut stopped).await {
+            Ok(()) => return,
+            Err(err) => format!("{:#}", err),
+        };
+        tracing::warn!("View of '{}' failed: {}", info.display_name, error);
+        match cache().sources.get_mut(&info.uid) {
+            Some(source) => {
+                source.ready = false;
+                source.error = Some(error);
+            }
+            None => return,
+        }
+
+        tokio::select! {
+            _ = &mut stopped => return,
+            _ = tokio::time::sleep(RETRY_DELAY) => {}
+        }
+    }
+}
+
+/// Open a live view on every object of the source and apply the changes it
+/// announces, until `stopped` fires
+async fn follow_view(
+    connection: &zbus::Connection,
+    info: &SourceInfo,
+    stopped: &mut tokio::sync::oneshot::Receiver<()>,
+) -> Result<()> {
+    let (backend, query, view_interface) = match info.source_type {
+        SourceType::AddressBook { .. } => (
+            crate::gnome::evolution::open_address_book(connection, info).await?,
+            ContactQuery::All.to_string(),
+            "org.gnome.evolution.dataserver.AddressBookView",
+        ),
+        _ => (
+            crate::gnome::evolution::open_calendar(connection, info).await?,
+            CalendarQuery::All.to_string(),
+            "org.gnome.evolution.dataserver.CalendarView",
+        ),
+    };
+
+    let response = backend.call_method("GetView", &(query,)).await?;
+    let view_path = response.body().deserialize::<OwnedObjectPath>()?;
+    let view = zbus::Proxy::new(
+        connection,
+        backend.destination().to_owned(),
+        view_path,
+        view_interface,
+    )
+    .await?;
+    let mut signals = view.receive_all_signals().await?;
+    // Announce the existing objects as added
+    view.call_method("SetFlags", &(1u32,)).await?;
+    view.call_method("Start", &()).await?;
+
+    loop {
+        let message = tokio::select! {
+            _ = &mut *stopped => {
+                let _ = view.call_method("Dispose", &()).await;
+                return Ok(());
+            }
+            message = signals.next() => message,
+        };
+        let Some(message) = message else {
+            anyhow::bail!("The view was closed");
+        };
+
+        let header = message.header();
+        let change = match header.member().map(|member| member.as_str()) {
+            Some("ObjectsAdded") => Change::Added(message.body().deserialize()?),
+            Some("ObjectsModified") => Change::Modified(message.body().deserialize()?),
+            Some("ObjectsRemoved") => Change::Removed(message.body().deserialize()?),
+            Some("Complete") => {
+                let error = message.body().deserialize::<Vec<String>>()?;
+                if let Some(source) = cache().sources.get_mut(&info.uid) {
+                    source.ready = true;
+                    source.error = error.into_iter().rfind(|part| !part.is_empty());
+                }
+                continue;
+            }
+            _ => continue,
+        };
+
+        let timezones = match (&change, &info.source_type) {
+            (Change::Added(objects) | Change::Modified(objects), SourceType::Calendar { .. }) => {
+                missing_timezones(&backend, &info.uid, objects).await
+            }
+            _ => Vec::new(),
+        };
+        if let Some(source) = cache().sources.get_mut(&info.uid) {
+            source.timezones.extend(timezones);
+            source.apply(change);
+        }
+    }
+}
+
+/// Definitions of the TZIDs used by `objects` that neither name a known
+/// zone nor are cached yet
+async fn missing_timezones(
+    backend: &zbus::Proxy<'_>,
+    source_uid: &str,
+    objects: &[String],
+) -> Vec<(String, String)> {
+    let tzids = {
+        let cache = cache();
+        let known = cache
+            .sources
+            .get(source_uid)
+            .map(|source| &source.timezones);
+        objects
+            .iter()
+            .flat_map(|data| ical::tzids(&ical::unfold(data)))
+            .filter(|tzid| calcard::common::timezone::Tz::from_str(tzid).is_err())
+            .filter(|tzid| known.map_or(true, |known| !known.contains_key(tzid)))
+            .collect::<std::collections::BTreeSet<_>>()
+    };
+
+    let mut timezones = Vec::new();
+    for tzid in tzids {
+        match backend.call_method("GetTimezone", &(tzid.as_str(),)).await {
+            Ok(response) => {
+                if let Ok(timezone) = response.body().deserialize::<String>() {
+                    timezones.push((tzid, timezone));
+                }
+            }
+            Err(err) => tracing::debug!("Failed to get timezone '{}': {}", tzid, err),
+        }
+    }
+    timezones
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn calendar() -> SourceCache {
+        SourceCache::new(SourceInfo {
+            uid: "work".to_string(),
+            path: OwnedObjectPath::try_from(
+                "/org/gnome/evolution/dataserver/SourceManager/Source_1",
+            )
+            .unwrap(),
+            display_name: "Work".to_string(),
+            enabled: true,
+            source_type: SourceType::Calendar {
+                backend_name: "local".to_string(),
+            },
+            color: None,
+            parent: None,
+            account: None,
+        })
+    }
+
+    fn vevent(uid: &str, extra: &str) -> String {
+        format!(
+            "BEGIN:VEVENT\r\nUID:{uid}\r\nSUMMARY:{uid}\r\n{extra}DTSTART:20240710T090000Z\r\n\
+             DTEND:20240710T100000Z\r\nEND:VEVENT\r\n"
+        )
+    }
+
+    #[test]
+    fn test_apply_changes() {
+        let mut source = calendar();
+        source.apply(Change::Added(vec![
+            vevent("standup", "RRULE:FREQ=DAILY;COUNT=3\r\n"),
+            vevent("lunch", ""),
+        ]));
+        source.apply(Change::Added(vec![vevent(
+            "standup",
+            "RECURRENCE-ID:20240711T090000Z\r\n",
+        )]));
+        source.apply(Change::Modified(vec![vevent(
+            "lunch",
+            "LOCATION:Cafeteria\r\n",
+        )]));
+
+        let Objects::Calendar(objects) = &source.objects else {
+            panic!("expected calendar objects");
+        };
+        assert_eq!(
+            objects.keys().collect::<Vec<_>>(),
+            ["lunch\n", "standup\n", "standup\n20240711T090000Z"]
+        );
+        assert!(objects["lunch\n"].contains("Cafeteria"));
+
+        // Removing the detached instance keeps the series
+        source.apply(Change::Removed(vec![
+            "standup\n20240711T090000Z".to_string()
+        ]));
+        let Objects::Calendar(objects) = &source.objects else {
+            panic!("expected calendar objects");
+        };
+        assert_eq!(objects.len(), 2);
+
+        source.apply(Change::Removed(vec!["standup\n".to_string()]));
+        let Objects::Calendar(objects) = &source.objects else {
+            panic!("expected calendar objects");
+        };
+        assert_eq!(objects.keys().collect::<Vec<_>>(), ["lunch\n"]);
+    }
+
+    #[test]
+    fn test_select_waits_for_sources() {
+        let mut cache = Cache::default();
+        let is_calendar = |kind: &SourceType| matches!(kind, SourceType::Calendar { .. });
+        assert!(cache
+            .select(&SourceFilter::default(), is_calendar)
+            .is_none());
+
+        cache.running = true;
+        cache.sources.insert("work".to_string(), calendar());
+        assert!(cache
+            .select(&SourceFilter::default(), is_calendar)
+            .is_none());
+
+        cache.sources.get_mut("work").unwrap().ready = true;
+        assert_eq!(
+            cache
+                .select(&SourceFilter::default(), is_calendar)
+                .unwrap()
+                .len(),
+            1
+        );
+        assert!(cache
+            .select(&SourceFilter::only(Some("Personal")), is_calendar)
+            .is_none());
+    }
+
+    #[test]
+    fn test_update_sources() {
+        let mut cache = Cache {
+            running: true,
+            ..Default::default()
+        };
+        let info = calendar().info;
+        let mut added = cache.update(HashMap::from([("work".to_string(), info.clone())]));
+        assert_eq!(added.len(), 1);
+        cache.sources.get_mut("work").unwrap().ready = true;
+
+        // A renamed source keeps its view and objects
+        let renamed = SourceInfo {
+            display_name: "Office".to_string(),
+            ..info.clone()
+        };
+        assert!(cache
+            .update(HashMap::from([("work".to_string(), renamed)]))
+            .is_empty());
+        assert!(cache.sources["work"].ready);
+        assert!(cache
+            .select(&SourceFilter::only(Some("Office")), |kind| {
+                matches!(kind, SourceType::Calendar { .. })
+            })
+            .is_some());
+
+        // A disabled source is dropped and its view closed, and it gets a
+        // new view once enabled again
+        let (_, mut stopped) = added.remove(0);
+        assert!(cache.update(HashMap::new()).is_empty());
+        assert!(cache.sources.is_empty());
+        assert_eq!(
+            stopped.try_recv(),
+            Err(tokio::sync::oneshot::error::TryRecvError::Closed)
+        );
+        assert_eq!(
+            cache
+                .update(HashMap::from([("work".to_string(), info)]))
+                .len(),
+            1
+        );
+        assert!(!cache.sources["work"].ready);
+    }
+}
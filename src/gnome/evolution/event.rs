@@ -2,67 +2,426 @@ use std::str::FromStr;
 
 use anyhow::Result;
 use chrono::{DateTime, Utc};
+use gio::glib;
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+use crate::gnome::evolution::{
+    alarm,
+    ical::{self, escape_text, PropertyList},
+    present_time, Alarm, Attendee, CalendarQuery, Contact, SourceFilter, SourceInfo, SourceResults,
+    SourceType,
+};
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Event {
     pub summary: Option<String>,
     pub description: Option<String>,
     pub start_time: Option<DateTime<Utc>>,
     pub end_time: Option<DateTime<Utc>>,
+    pub all_day: bool, // Starts and ends on dates rather than times
     pub uid: String,
     pub location: Option<String>,
     pub categories: Vec<String>,
     pub priority: Option<u32>,
-    pub organizer: Option<String>,
-    pub attendees: Vec<String>,
+    pub organizer: Option<Attendee>,
+    pub attendees: Vec<Attendee>,
     pub status: Option<String>,
     pub transparency: Option<String>, // OPAQUE/TRANSPARENT
     pub class: Option<String>,        // PUBLIC/PRIVATE/CONFIDENTIAL
     pub created: Option<DateTime<Utc>>,
     pub last_modified: Option<DateTime<Utc>>,
     pub url: Option<String>,
-    pub rrule: Option<String>, // Recurrence rule
+    pub rrule: Option<String>,                // Recurrence rule
+    pub timezone: Option<String>,             // TZID of the start time
+    pub recurrence_id: Option<DateTime<Utc>>, // Set on a single occurrence
+    pub series_uid: Option<String>,           // Set on occurrences of a recurring event
+    pub alarms: Vec<Alarm>,
 }
 
+/// Upper bound on the occurrences generated for a series from the start of
+/// the requested range, so that dense unbounded rules don't expand forever
+const MAX_OCCURRENCES: usize = 20_000;
+
 impl Event {
-    pub fn to_json(&self) -> serde_json::Value {
-        serde_json::to_value(self).unwrap_or_default()
+    /// JSON representation, with times in the `local` timezone and dates
+    /// for all-day events
+    pub fn to_json(&self, local: chrono_tz::Tz) -> serde_json::Value {
+        let mut json = serde_json::to_value(self).unwrap_or_default();
+        for (field, value, all_day) in [
+            ("start_time", self.start_time, self.all_day),
+            ("end_time", self.end_time, self.all_day),
+            ("recurrence_id", self.recurrence_id, self.all_day),
+            ("created", self.created, false),
+            ("last_modified", self.last_modified, false),
+        ] {
+            json[field] = present_time(value, local, all_day);
+        }
+        json["alarms"] = self.alarms.iter().map(|a| a.to_json(local)).collect();
+        json
     }
 
-    /// Fetch all calendar events from Evolution Data Server within the
-    /// specified time range
-    pub async fn all(start_time: DateTime<Utc>, end_time: DateTime<Utc>) -> Result<Vec<Event>> {
-        let connection = zbus::Connection::session().await?;
+    /// The IANA zone of the start time's TZID, which may also be an alias
+    /// such as a Windows zone name or a `/`-prefixed global ID
+    fn tz(&self) -> Option<chrono_tz::Tz> {
+        match calcard::common::timezone::Tz::from_str(self.timezone.as_deref()?) {
+            Ok(calcard::common::timezone::Tz::Tz(tz)) => Some(tz),
+            _ => None,
+        }
+    }
 
-        // Step 1: Get managed objects from SourceManager
-        let sources = crate::gnome::evolution::get_evolution_sources(&connection).await?;
-
-        // Step 2: Find calendar sources and get their events
-        let mut all_events = Vec::new();
-
-        for (_source_path, (info, _proxy)) in sources {
-            if matches!(
-                info.source_type,
-                crate::gnome::evolution::SourceType::Calendar { .. }
-            ) {
-                let (calendar_path, bus_name) =
-                    crate::gnome::evolution::open_calendar_source(&connection, &info.uid).await?;
-                if let Ok(events) = Self::fetch_from_source(
-                    &connection,
-                    &calendar_path,
-                    &bus_name,
-                    start_time,
-                    end_time,
-                )
-                .await
-                {
-                    all_events.extend(events);
+    /// The modeled iCalendar properties, with times written in the event's
+    /// timezone when it is a known IANA zone and in UTC otherwise, and as
+    /// dates for all-day events
+    fn properties(&self) -> PropertyList {
+        let tz = self.tz();
+        let categories = self
+            .categories
+            .iter()
+            .map(|c| escape_text(c))
+            .collect::<Vec<_>>();
+
+        let mut properties = PropertyList::default();
+        properties.text("UID", Some(&self.uid));
+        if self.all_day {
+            let date = |value: Option<DateTime<Utc>>| value.map(|value| value.date_naive());
+            properties.date("RECURRENCE-ID", date(self.recurrence_id));
+            properties.date("DTSTART", date(self.start_time));
+            properties.date("DTEND", date(self.end_time));
+        } else {
+            properties.date_time("RECURRENCE-ID", self.recurrence_id, tz);
+            properties.date_time("DTSTART", self.start_time, tz);
+            properties.date_time("DTEND", self.end_time, tz);
+        }
+        properties.text("SUMMARY", self.summary.as_deref());
+        properties.text("DESCRIPTION", self.description.as_deref());
+        properties.text("LOCATION", self.location.as_deref());
+        properties.raw(
+            "CATEGORIES",
+            "",
+            (!categories.is_empty())
+                .then(|| categories.join(","))
+                .as_deref(),
+        );
+        properties.raw(
+            "PRIORITY",
+            "",
+            self.priority.map(|p| p.to_string()).as_deref(),
+        );
+        properties.remove(&["ORGANIZER", "ATTENDEE"]);
+        if let Some(organizer) = &self.organizer {
+            properties.raw("ORGANIZER", &organizer.params(), Some(&organizer.address()));
+        }
+        for attendee in &self.attendees {
+            properties.raw("ATTENDEE", &attendee.params(), Some(&attendee.address()));
+        }
+        properties.raw("STATUS", "", self.status.as_deref());
+        properties.raw("TRANSP", "", self.transparency.as_deref());
+        properties.raw("CLASS", "", self.class.as_deref());
+        properties.date_time("CREATED", self.created, None);
+        properties.date_time("LAST-MODIFIED", self.last_modified, None);
+        properties.raw("URL", "", self.url.as_deref());
+        properties.raw("RRULE", "", self.rrule.as_deref());
+        properties
+    }
+
+    /// Serialize the event as a new VEVENT component, with its alarms
+    pub fn to_ical(&self) -> String {
+        let mut properties = self.properties();
+        properties.date_time("DTSTAMP", Some(Utc::now()), None);
+        let alarms = self
+            .alarms
+            .iter()
+            .flat_map(Alarm::to_lines)
+            .collect::<Vec<_>>();
+        properties.to_component_with("VEVENT", &alarms)
+    }
+
+    /// When each of the event's alarms goes off
+    pub fn alarm_times(&self) -> Vec<(&Alarm, DateTime<Utc>)> {
+        self.alarms
+            .iter()
+            .filter_map(|alarm| Some((alarm, alarm.fire_time(self.start_time, self.end_time)?)))
+            .collect()
+    }
+
+    /// Name the organizer and attendees after the contacts having their
+    /// address
+    pub fn match_contacts(&mut self, contacts: &[Contact]) {
+        for attendee in self.organizer.iter_mut().chain(&mut self.attendees) {
+            attendee.match_contact(contacts);
+        }
+    }
+
+    /// Name the attendees of `events` after the contacts of the address
+    /// books selected by `sources`, leaving them unnamed when none can be read
+    pub async fn name_attendees(events: &mut [Event], sources: &SourceFilter) {
+        if events
+            .iter()
+            .all(|event| event.organizer.is_none() && event.attendees.is_empty())
+        {
+            return;
+        }
+        match Contact::all(true, sources).await {
+            Ok(results) => {
+                for event in events {
+                    event.match_contacts(&results.items);
                 }
             }
+            Err(err) => tracing::warn!("Failed to read contacts for attendee names: {}", err),
         }
+    }
 
-        Ok(all_events)
+    /// Apply the changes made to this event to the VEVENT stored in EDS,
+    /// preserving alarms, exceptions and other properties the model doesn't
+    /// cover. When the event is a single occurrence, the detached instance
+    /// is patched, or derived from the series if there is none yet.
+    pub fn patch_ical(&self, stored: &str) -> Result<String> {
+        let components = ical::components(&ical::unfold(stored), "VEVENT");
+        let recurrence_id = |component: &Vec<String>| {
+            Event::from_str(&ical::fold(component))
+                .ok()
+                .and_then(|event| event.recurrence_id)
+        };
+
+        let master = components
+            .iter()
+            .find(|component| recurrence_id(component).is_none())
+            .ok_or_else(|| anyhow::anyhow!("No VEVENT found for '{}'", self.uid))?;
+        let base = match self.recurrence_id {
+            Some(occurrence) => match components
+                .iter()
+                .find(|component| recurrence_id(component) == Some(occurrence))
+            {
+                Some(instance) => instance.clone(),
+                None => {
+                    let mut series = PropertyList::default();
+                    series.remove(&["RRULE", "RDATE", "EXRULE", "EXDATE"]);
+                    series.apply_to(master)
+                }
+            },
+            None => master.clone(),
+        };
+
+        let previous = Event::from_str(&ical::fold(&base))?;
+        ical::patch(&base, &previous.properties(), &self.properties())
+            .ok_or_else(|| anyhow::anyhow!("No changes to apply to '{}'", self.uid))
+    }
+
+    /// Whether the events stored in `stored` form a series: one repeated by
+    /// an RRULE or RDATEs, or with detached instances
+    pub fn is_recurring(stored: &str) -> bool {
+        ical::components(&ical::unfold(stored), "VEVENT")
+            .iter()
+            .flatten()
+            .any(|line| {
+                matches!(
+                    ical::property_name(line).as_str(),
+                    "RRULE" | "RDATE" | "RECURRENCE-ID"
+                )
+            })
+    }
+
+    /// The detached instance in `stored` overriding the occurrence of the
+    /// series at `occurrence`, if it was already changed on its own
+    pub fn detached_instance(stored: &str, occurrence: DateTime<Utc>) -> Option<Event> {
+        let ical = calcard::icalendar::ICalendar::parse(stored).ok()?;
+        let tz_resolver = ical
+            .build_tz_resolver()
+            .with_default(calcard::common::timezone::Tz::UTC);
+        ical.components
+            .iter()
+            .filter(|c| {
+                matches!(
+                    c.component_type,
+                    calcard::icalendar::ICalendarComponentType::VEvent
+                )
+            })
+            .map(|c| Event::from_component(&ical, c, &tz_resolver))
+            .find(|event| event.recurrence_id == Some(occurrence))
+    }
+
+    /// Check that the event can be stored: it needs a start, must not end
+    /// before it starts, and its RRULE must parse
+    pub fn validate(&self) -> Result<()> {
+        let start_time = self
+            .start_time
+            .ok_or_else(|| anyhow::anyhow!("Event has no start time"))?;
+        if self.end_time.is_some_and(|end_time| end_time < start_time) {
+            anyhow::bail!("Event ends before it starts");
+        }
+        if let Some(timezone) = &self.timezone {
+            if self.tz().is_none() {
+                anyhow::bail!("Unknown timezone '{}'", timezone);
+            }
+        }
+        if let Some(rrule) = &self.rrule {
+            let parsed = Event::from_str(&self.to_ical())?;
+            if parsed.rrule.is_none() {
+                anyhow::bail!("Invalid recurrence rule '{}'", rrule);
+            }
+        }
+        Ok(())
+    }
+
+    /// Resolve a calendar by UID or display name, or the default calendar,
+    /// and open it for writing
+    pub async fn writable_calendar(
+        connection: &zbus::Connection,
+        calendar: Option<&str>,
+    ) -> Result<(SourceInfo, zbus::Proxy<'static>)> {
+        let source = crate::gnome::evolution::resolve_source(
+            connection,
+            calendar,
+            "default-calendar",
+            |kind| matches!(kind, SourceType::Calendar { .. }),
+        )
+        .await?;
+        let proxy = crate::gnome::evolution::open_writable_calendar(connection, &source).await?;
+        Ok((source, proxy))
+    }
+
+    /// Save the event as a new object in a writable calendar, picked by UID
+    /// or display name (the default calendar otherwise), returning the UID
+    /// assigned by the backend
+    pub async fn create(&self, calendar: Option<&str>) -> Result<String> {
+        self.validate()?;
+
+        let connection = zbus::Connection::session().await?;
+        let (_source, proxy) = Self::writable_calendar(&connection, calendar).await?;
+
+        let mut event = self.clone();
+        if event.uid.is_empty() {
+            event.uid = glib::uuid_string_random().to_string();
+        }
+
+        let response = proxy
+            .call_method("CreateObjects", &(vec![event.to_ical()], 0u32))
+            .await?;
+        let uids = response.body().deserialize::<Vec<String>>()?;
+        Ok(uids.into_iter().next().unwrap_or(event.uid))
+    }
+
+    /// Look up an event by UID in the given calendar, or in every calendar,
+    /// returning its source, backend and stored iCalendar data
+    pub async fn find(
+        connection: &zbus::Connection,
+        uid: &str,
+        calendar: Option<&str>,
+    ) -> Result<(SourceInfo, zbus::Proxy<'static>, String)> {
+        crate::gnome::evolution::find_calendar_object(connection, uid, calendar, |kind| {
+            matches!(kind, SourceType::Calendar { .. })
+        })
+        .await
+    }
+
+    /// Store changes to the event, found with [`Event::find`]. A single
+    /// occurrence is detached from its series; otherwise the whole series
+    /// is changed.
+    pub async fn modify(
+        &self,
+        source: &SourceInfo,
+        proxy: &zbus::Proxy<'_>,
+        stored: &str,
+    ) -> Result<()> {
+        self.validate()?;
+        crate::gnome::evolution::ensure_writable(proxy, source).await?;
+
+        let mod_type = if self.recurrence_id.is_some() {
+            "this"
+        } else {
+            "all"
+        };
+        proxy
+            .call_method(
+                "ModifyObjects",
+                &(vec![self.patch_ical(stored)?], mod_type, 0u32),
+            )
+            .await?;
+        Ok(())
+    }
+
+    /// Delete the event, or only the occurrence starting at `occurrence`
+    pub async fn remove(
+        &self,
+        source: &SourceInfo,
+        proxy: &zbus::Proxy<'_>,
+        occurrence: Option<DateTime<Utc>>,
+    ) -> Result<()> {
+        crate::gnome::evolution::ensure_writable(proxy, source).await?;
+
+        let (rid, mod_type) = match occurrence {
+            Some(occurrence) if self.all_day => (occurrence.format("%Y%m%d").to_string(), "this"),
+            Some(occurrence) => (ical::recurrence_id_string(occurrence, self.tz()), "this"),
+            None => (String::new(), "all"),
+        };
+        proxy
+            .call_method(
+                "RemoveObjects",
+                &(vec![(self.uid.as_str(), rid.as_str())], mod_type, 0u32),
+            )
+            .await?;
+        Ok(())
+    }
+
+    /// Fetch all calendar events from Evolution Data Server within the
+    /// specified time range, from the calendars `sources` allows
+    pub async fn all(
+        start_time: DateTime<Utc>,
+        end_time: DateTime<Utc>,
+        sources: &SourceFilter,
+    ) -> Result<SourceResults<Event>> {
+        if let Some(results) = crate::gnome::evolution::cache::events(start_time, end_time, sources)
+        {
+            return Ok(results);
+        }
+        Self::search(&CalendarQuery::All, start_time, end_time, sources).await
+    }
+
+    /// Fetch the occurrences within the time range of the events matching
+    /// `query`, from the calendars `sources` allows. The calendars evaluate
+    /// the query themselves and are read concurrently, so one that fails
+    /// only ends up in the errors.
+    pub async fn search(
+        query: &CalendarQuery,
+        start_time: DateTime<Utc>,
+        end_time: DateTime<Utc>,
+        sources: &SourceFilter,
+    ) -> Result<SourceResults<Event>> {
+        let connection = zbus::Connection::session().await?;
+        let sources = crate::gnome::evolution::select_sources(&connection, sources, |kind| {
+            matches!(kind, SourceType::Calendar { .. })
+        })
+        .await?;
+        let query = CalendarQuery::OccursIn(start_time, end_time).and(query.clone());
+
+        let results = crate::gnome::evolution::fetch_sources(
+            sources,
+            crate::gnome::evolution::SOURCE_TIMEOUT,
+            |info| {
+                let connection = connection.clone();
+                let query = query.clone();
+                async move {
+                    let (calendar_path, bus_name) =
+                        crate::gnome::evolution::open_calendar_source(&connection, &info.uid)
+                            .await?;
+                    Self::fetch_from_source(
+                        &connection,
+                        &calendar_path,
+                        &bus_name,
+                        &query,
+                        start_time,
+                        end_time,
+                    )
+                    .await
+                }
+            },
+        )
+        .await;
+
+        Ok(results.map_items(|mut events| {
+            events.sort_by_key(|event| event.start_time);
+            events
+        }))
     }
 
     /// Private helper to fetch events from a specific calendar source
@@ -70,11 +429,10 @@ impl Event {
         connection: &zbus::Connection,
         calendar_path: &str,
         bus_name: &str,
+        query: &CalendarQuery,
         start_time: DateTime<Utc>,
         end_time: DateTime<Utc>,
     ) -> Result<Vec<Event>> {
-        let mut events = Vec::new();
-
         let proxy = zbus::Proxy::new(
             connection,
             bus_name,
@@ -83,23 +441,232 @@ impl Event {
         )
         .await?;
 
-        let sexp_query = format!(
-            "(occur-in-time-range? (make-time \\\"{}\\\") (make-time \\\"{}\\\"))",
-            start_time.format("%Y%m%dT%H%M%SZ"),
-            end_time.format("%Y%m%dT%H%M%SZ")
-        );
+        let response = proxy
+            .call_method("GetObjectList", &(query.to_string(),))
+            .await?;
+        let mut ical_objects = response.body().deserialize::<Vec<String>>()?;
 
-        let response = proxy.call_method("GetObjectList", &(sexp_query,)).await?;
-        let ical_objects = response.body().deserialize::<Vec<String>>()?;
+        // EDS stores timezone definitions apart from the objects
+        let timezones = crate::gnome::evolution::referenced_timezones(&proxy, &ical_objects).await;
+        ical_objects.extend(timezones);
 
+        Ok(Self::expand(&ical_objects, start_time, end_time))
+    }
+
+    /// Turn the objects returned by EDS into one event per occurrence
+    /// overlapping the time range, sorted by start time. Recurring events are
+    /// expanded from their RRULE and RDATEs, skipping EXDATEs, and detached
+    /// instances replace the occurrence they override.
+    pub fn expand(
+        ical_objects: &[String],
+        start_time: DateTime<Utc>,
+        end_time: DateTime<Utc>,
+    ) -> Vec<Event> {
+        // EDS returns detached instances as separate objects, gather them
+        // with their series
+        let mut series: Vec<(String, Vec<String>)> = Vec::new();
+        let mut timezones = Vec::new();
         for ical_data in ical_objects {
-            if let Ok(event) = Event::from_str(&ical_data) {
-                events.push(event);
+            let lines = ical::unfold(ical_data);
+            timezones.extend(ical::components(&lines, "VTIMEZONE").into_iter().flatten());
+            for component in ical::components(&lines, "VEVENT") {
+                let uid = component
+                    .iter()
+                    .find(|line| ical::property_name(line) == "UID")
+                    .and_then(|line| line.split_once(':'))
+                    .map(|(_, uid)| uid.to_owned())
+                    .unwrap_or_default();
+                match series.iter_mut().find(|(series_uid, _)| *series_uid == uid) {
+                    Some((_, components)) => components.extend(component),
+                    None => series.push((uid, component)),
+                }
             }
         }
 
-        Ok(events)
+        let mut events = series
+            .iter()
+            .flat_map(|(_, components)| {
+                Self::expand_series(&timezones, components, start_time, end_time)
+            })
+            .collect::<Vec<_>>();
+        events.sort_by_key(|event| event.start_time);
+        events
     }
+
+    /// Occurrences of a series (its master and detached instances) within
+    /// the time range
+    fn expand_series(
+        timezones: &[String],
+        components: &[String],
+        start_time: DateTime<Utc>,
+        end_time: DateTime<Utc>,
+    ) -> Vec<Event> {
+        use calcard::{
+            common::timezone::Tz,
+            icalendar::{dates::TimeOrDelta, ICalendar, ICalendarProperty},
+        };
+
+        let parse = |components: &[String]| {
+            let mut lines = vec!["BEGIN:VCALENDAR".to_owned(), "VERSION:2.0".to_owned()];
+            lines.extend_from_slice(timezones);
+            lines.extend_from_slice(components);
+            lines.push("END:VCALENDAR".to_owned());
+            ICalendar::parse(ical::fold(&lines)).ok()
+        };
+        let Some(mut calendar) = parse(components) else {
+            return Vec::new();
+        };
+        // Start unbounded series close to the range, so the occurrences
+        // before it don't use up the limit
+        if let Some(skipped) = Self::skip_to_range(&calendar, components, start_time) {
+            calendar = parse(&skipped).unwrap_or(calendar);
+        }
+        let tz_resolver = calendar.build_tz_resolver().with_default(Tz::UTC);
+
+        calendar
+            .expand_dates(Tz::UTC, MAX_OCCURRENCES)
+            .events
+            .into_iter()
+            .filter_map(|occurrence| {
+                let start = occurrence.start.with_timezone(&Utc);
+                let end = match occurrence.end {
+                    TimeOrDelta::Time(end) => end.with_timezone(&Utc),
+                    TimeOrDelta::Delta(duration) => start + duration,
+                };
+                // Events without a duration still count when they start
+                // right at the beginning of the range
+                if start >= end_time || (end <= start_time && start < start_time) {
+                    return None;
+                }
+
+                let component = calendar.components.get(occurrence.comp_id as usize)?;
+                let mut event = Event::from_component(&calendar, component, &tz_resolver);
+                let recurs = event.recurrence_id.is_some()
+                    || component.property(&ICalendarProperty::Rrule).is_some()
+                    || component.property(&ICalendarProperty::Rdate).is_some();
+                if recurs {
+                    event.recurrence_id = event.recurrence_id.or(Some(start));
+                    event.series_uid = Some(event.uid.clone());
+                }
+                event.end_time = (end > start || event.end_time.is_some()).then_some(end);
+                event.start_time = Some(start);
+                Some(event)
+            })
+            .collect()
+    }
+
+    /// The components of a series with the DTSTART and DTEND of its master
+    /// moved forward by whole repetitions of its RRULE, to shortly before
+    /// `start_time`. Only rules without a COUNT whose repetitions have a
+    /// fixed length (SECONDLY to WEEKLY) are moved, as the occurrences
+    /// they produce from the new start are the same.
+    fn skip_to_range(
+        calendar: &calcard::icalendar::ICalendar,
+        components: &[String],
+        start_time: DateTime<Utc>,
+    ) -> Option<Vec<String>> {
+        use calcard::{
+            common::timezone::Tz,
+            icalendar::{dates::TimeOrDelta, ICalendarComponentType, ICalendarProperty},
+        };
+
+        let mut blocks = ical::components(components, "VEVENT");
+        let master = blocks.iter().position(|block| {
+            let has = |name: &str| block.iter().any(|line| ical::property_name(line) == name);
+            has("RRULE") && !has("RECURRENCE-ID")
+        })?;
+        let rrule = blocks[master]
+            .iter()
+            .find(|line| ical::property_name(line) == "RRULE")?
+            .split_once(':')?
+            .1;
+        let step = repetition_length(rrule)?;
+
+        let (index, component) = calendar.components.iter().enumerate().find(|(_, c)| {
+            c.component_type == ICalendarComponentType::VEvent
+                && c.property(&ICalendarProperty::Rrule).is_some()
+                && c.property(&ICalendarProperty::RecurrenceId).is_none()
+        })?;
+        let tz_resolver = calendar.build_tz_resolver().with_default(Tz::UTC);
+        let (series_start, _) = ical::date_time_value(
+            component.property(&ICalendarProperty::Dtstart)?,
+            &tz_resolver,
+        )?;
+        let first = calendar
+            .expand_dates(Tz::UTC, 1)
+            .events
+            .into_iter()
+            .find(|occurrence| occurrence.comp_id as usize == index)?;
+        let duration = match first.end {
+            TimeOrDelta::Time(end) => end.with_timezone(&Utc) - first.start.with_timezone(&Utc),
+            TimeOrDelta::Delta(duration) => duration,
+        };
+
+        // The new first occurrence ends a repetition and a day (covering any
+        // UTC offset) before the range, so it never shows in it
+        let target =
+            start_time - duration.max(chrono::Duration::zero()) - step - chrono::Duration::days(1);
+        let repetitions = (target - series_start).num_seconds() / step.num_seconds();
+        if repetitions <= 0 {
+            return None;
+        }
+        let shift = chrono::Duration::seconds(step.num_seconds().checked_mul(repetitions)?);
+
+        let mut shifted = Vec::with_capacity(blocks[master].len());
+        for line in &blocks[master] {
+            shifted.push(match ical::property_name(line).as_str() {
+                "DTSTART" | "DTEND" => shift_date_time(line, shift)?,
+                _ => line.clone(),
+            });
+        }
+        blocks[master] = shifted;
+        Some(blocks.concat())
+    }
+}
+
+/// How far apart the repetitions of an RRULE without COUNT are, when that
+/// is a fixed length
+fn repetition_length(rrule: &str) -> Option<chrono::Duration> {
+    let mut unit = None;
+    let mut interval = 1;
+    for part in rrule.split(';') {
+        let (key, value) = part.split_once('=')?;
+        match key.to_ascii_uppercase().as_str() {
+            "FREQ" => {
+                unit = match value.to_ascii_uppercase().as_str() {
+                    "SECONDLY" => Some(chrono::Duration::seconds(1)),
+                    "MINUTELY" => Some(chrono::Duration::minutes(1)),
+                    "HOURLY" => Some(chrono::Duration::hours(1)),
+                    "DAILY" => Some(chrono::Duration::days(1)),
+                    "WEEKLY" => Some(chrono::Duration::weeks(1)),
+                    _ => None,
+                }
+            }
+            "INTERVAL" => interval = value.parse::<i32>().ok().filter(|i| *i > 0)?,
+            "COUNT" => return None,
+            _ => {}
+        }
+    }
+    Some(unit? * interval)
+}
+
+/// A DATE or DATE-TIME property line with its value moved by `shift`, in
+/// the local time it is written in
+fn shift_date_time(line: &str, shift: chrono::Duration) -> Option<String> {
+    let (name, value) = line.rsplit_once(':')?;
+    let shifted = match value.len() {
+        8 if shift.num_seconds() % 86_400 == 0 => {
+            let date = chrono::NaiveDate::parse_from_str(value, "%Y%m%d").ok()?;
+            (date + shift).format("%Y%m%d").to_string()
+        }
+        _ => {
+            let local = value.strip_suffix('Z').unwrap_or(value);
+            let date_time = chrono::NaiveDateTime::parse_from_str(local, "%Y%m%dT%H%M%S").ok()?;
+            let suffix = if value.ends_with('Z') { "Z" } else { "" };
+            format!("{}{}", (date_time + shift).format("%Y%m%dT%H%M%S"), suffix)
+        }
+    };
+    Some(format!("{}:{}", name, shifted))
 }
 
 impl FromStr for Event {
@@ -110,17 +677,43 @@ impl FromStr for Event {
             .map_err(|e| anyhow::anyhow!("Failed to parse iCalendar data: {:?}", e))?;
 
         // Find the VEVENT component within the VCALENDAR
+        // Prefer the series over detached occurrences
         let event_component = ical
             .components
             .iter()
-            .find(|c| {
+            .filter(|c| {
                 matches!(
                     c.component_type,
                     calcard::icalendar::ICalendarComponentType::VEvent
                 )
             })
+            .min_by_key(|c| {
+                c.property(&calcard::icalendar::ICalendarProperty::RecurrenceId)
+                    .is_some()
+            })
             .ok_or_else(|| anyhow::anyhow!("No VEVENT component found in iCalendar data"))?;
 
+        let tz_resolver = ical
+            .build_tz_resolver()
+            .with_default(calcard::common::timezone::Tz::UTC);
+        Ok(Event::from_component(&ical, event_component, &tz_resolver))
+    }
+}
+
+impl Event {
+    /// Read the modeled properties and alarms of a VEVENT component of
+    /// `calendar`, resolving its TZIDs with `tz_resolver`
+    fn from_component(
+        calendar: &calcard::icalendar::ICalendar,
+        event_component: &calcard::icalendar::ICalendarComponent,
+        tz_resolver: &calcard::icalendar::timezone::TzResolver,
+    ) -> Self {
+        let date_time = |property| {
+            event_component
+                .property(&property)
+                .and_then(|entry| ical::date_time_value(entry, tz_resolver))
+        };
+
         let uid = event_component
             .property(&calcard::icalendar::ICalendarProperty::Uid)
             .and_then(|p| p.values.first())
@@ -141,19 +734,20 @@ impl FromStr for Event {
             .filter(|s| !s.is_empty())
             .map(|s| s.to_string());
 
-        let start_time = event_component
+        let timezone = event_component
             .property(&calcard::icalendar::ICalendarProperty::Dtstart)
-            .and_then(|p| p.values.first())
-            .and_then(|v| v.as_partial_date_time())
-            .and_then(|d| d.to_date_time_with_tz(calcard::common::timezone::Tz::UTC))
-            .map(|dt| dt.with_timezone(&Utc));
-
-        let end_time = event_component
-            .property(&calcard::icalendar::ICalendarProperty::Dtend)
-            .and_then(|p| p.values.first())
-            .and_then(|v| v.as_partial_date_time())
-            .and_then(|d| d.to_date_time_with_tz(calcard::common::timezone::Tz::UTC))
-            .map(|dt| dt.with_timezone(&Utc));
+            .and_then(|p| {
+                p.params.iter().find_map(|param| match param {
+                    calcard::icalendar::ICalendarParameter::Tzid(tzid) => Some(tzid.clone()),
+                    _ => None,
+                })
+            });
+
+        let start = date_time(calcard::icalendar::ICalendarProperty::Dtstart);
+        let all_day = start.is_some_and(|(_, is_date)| is_date);
+        let start_time = start.map(|(start_time, _)| start_time);
+        let end_time =
+            date_time(calcard::icalendar::ICalendarProperty::Dtend).map(|(end_time, _)| end_time);
 
         let location = event_component
             .property(&calcard::icalendar::ICalendarProperty::Location)
@@ -178,17 +772,11 @@ impl FromStr for Event {
 
         let organizer = event_component
             .property(&calcard::icalendar::ICalendarProperty::Organizer)
-            .and_then(|p| p.values.first())
-            .and_then(|v| v.as_text())
-            .filter(|s| !s.is_empty())
-            .map(|s| s.to_string());
+            .and_then(Attendee::from_entry);
 
-        let attendees: Vec<String> = event_component
+        let attendees: Vec<Attendee> = event_component
             .properties(&calcard::icalendar::ICalendarProperty::Attendee)
-            .flat_map(|p| &p.values)
-            .filter_map(|v| v.as_text())
-            .filter(|s| !s.is_empty())
-            .map(|s| s.to_string())
+            .filter_map(Attendee::from_entry)
             .collect();
 
         let status = event_component
@@ -212,19 +800,11 @@ impl FromStr for Event {
             .filter(|s| !s.is_empty())
             .map(|s| s.to_string());
 
-        let created = event_component
-            .property(&calcard::icalendar::ICalendarProperty::Created)
-            .and_then(|p| p.values.first())
-            .and_then(|v| v.as_partial_date_time())
-            .and_then(|d| d.to_date_time_with_tz(calcard::common::timezone::Tz::UTC))
-            .map(|dt| dt.with_timezone(&Utc));
+        let created =
+            date_time(calcard::icalendar::ICalendarProperty::Created).map(|(created, _)| created);
 
-        let last_modified = event_component
-            .property(&calcard::icalendar::ICalendarProperty::LastModified)
-            .and_then(|p| p.values.first())
-            .and_then(|v| v.as_partial_date_time())
-            .and_then(|d| d.to_date_time_with_tz(calcard::common::timezone::Tz::UTC))
-            .map(|dt| dt.with_timezone(&Utc));
+        let last_modified = date_time(calcard::icalendar::ICalendarProperty::LastModified)
+            .map(|(last_modified, _)| last_modified);
 
         let url = event_component
             .property(&calcard::icalendar::ICalendarProperty::Url)
@@ -242,11 +822,15 @@ impl FromStr for Event {
             })
             .filter(|s| !s.is_empty());
 
-        Ok(Event {
+        let recurrence_id = date_time(calcard::icalendar::ICalendarProperty::RecurrenceId)
+            .map(|(recurrence_id, _)| recurrence_id);
+
+        Event {
             summary,
             description,
             start_time,
             end_time,
+            all_day,
             uid: uid.to_string(),
             location,
             categories,
@@ -260,7 +844,11 @@ impl FromStr for Event {
             last_modified,
             url,
             rrule,
-        })
+            timezone,
+            recurrence_id,
+            series_uid: None,
+            alarms: alarm::alarms(calendar, event_component, tz_resolver),
+        }
     }
 }
 
@@ -360,6 +948,7 @@ END:VCALENDAR"#;
             description: None,
             start_time: None,
             end_time: None,
+            all_day: false,
             location: None,
             categories: vec![],
             priority: None,
@@ -372,9 +961,13 @@ END:VCALENDAR"#;
             last_modified: None,
             url: None,
             rrule: None,
+            timezone: None,
+            recurrence_id: None,
+            series_uid: None,
+            alarms: vec![],
         };
 
-        let json = event.to_json();
+        let json = event.to_json(chrono_tz::UTC);
         assert!(json.is_object());
         assert_eq!(json["uid"], "test-123");
         assert_eq!(json["summary"], "Test Summary");
@@ -399,8 +992,39 @@ END:VCALENDAR"#;
 
         assert_eq!(event.uid, "timezone-event");
         assert_eq!(event.summary, Some("Timezone Test Event".to_string()));
-        assert!(event.start_time.is_some());
-        assert!(event.end_time.is_some());
+        assert_eq!(event.timezone, Some("America/New_York".to_string()));
+        assert_eq!(event.start_time, "2024-07-10T18:00:00Z".parse().ok());
+        assert_eq!(event.end_time, "2024-07-10T19:00:00Z".parse().ok());
+        assert!(!event.all_day);
+    }
+
+    #[test]
+    fn test_event_with_embedded_timezone() {
+        let ical_data = r#"BEGIN:VCALENDAR
+VERSION:2.0
+PRODID:-//Test//Test//EN
+BEGIN:VTIMEZONE
+TZID:/freeassociation.sourceforge.net/Tzfile/Custom
+X-LIC-LOCATION:Asia/Tokyo
+BEGIN:STANDARD
+TZOFFSETFROM:+0900
+TZOFFSETTO:+0900
+TZNAME:JST
+DTSTART:19700101T000000
+END:STANDARD
+END:VTIMEZONE
+BEGIN:VEVENT
+UID:tokyo-event
+DTSTART;TZID=/freeassociation.sourceforge.net/Tzfile/Custom:20240710T090000
+END:VEVENT
+END:VCALENDAR"#;
+
+        let event = Event::from_str(ical_data).unwrap();
+        assert_eq!(event.start_time, "2024-07-10T00:00:00Z".parse().ok());
+        assert_eq!(
+            event.timezone.as_deref(),
+            Some("/freeassociation.sourceforge.net/Tzfile/Custom")
+        );
     }
 
     #[test]
@@ -419,6 +1043,7 @@ END:VCALENDAR"#;
 
         assert_eq!(event.uid, "date-only-event");
         assert_eq!(event.summary, Some("All Day Event".to_string()));
+        assert!(event.all_day);
 
         if let Some(start) = event.start_time {
             assert_eq!(start.year(), 2024);
@@ -580,16 +1205,18 @@ END:VCALENDAR"#;
         // Test all the additional fields that should work
         assert_eq!(event.priority, Some(3));
         assert_eq!(
-            event.organizer,
-            Some("MAILTO:organizer@example.com".to_string())
+            event.organizer.as_ref().map(|o| o.email.as_str()),
+            Some("organizer@example.com")
         );
         assert_eq!(event.attendees.len(), 2);
         assert!(event
             .attendees
-            .contains(&"MAILTO:attendee1@example.com".to_string()));
+            .iter()
+            .any(|a| a.is("attendee1@example.com")));
         assert!(event
             .attendees
-            .contains(&"MAILTO:attendee2@example.com".to_string()));
+            .iter()
+            .any(|a| a.is("attendee2@example.com")));
         assert_eq!(event.status, Some("CONFIRMED".to_string()));
         assert_eq!(event.transparency, Some("OPAQUE".to_string()));
         assert_eq!(event.class, Some("PUBLIC".to_string()));
@@ -598,4 +1225,249 @@ END:VCALENDAR"#;
         assert_eq!(event.url, Some("https://example.com/event".to_string()));
         assert_eq!(event.rrule, Some("FREQ=WEEKLY;COUNT=4".to_string()));
     }
+
+    #[test]
+    fn test_event_to_ical_round_trip() {
+        let event = Event {
+            uid: "new-event-1".to_string(),
+            summary: Some("Planning, Q3".to_string()),
+            description: Some("Agenda:\n- budget".to_string()),
+            start_time: "2024-07-10T08:00:00Z".parse().ok(),
+            end_time: "2024-07-10T09:30:00Z".parse().ok(),
+            location: Some("Room 4; 2nd floor".to_string()),
+            attendees: vec![
+                Attendee::invite("anna@example.com"),
+                Attendee::invite("mailto:bob@example.com"),
+            ],
+            rrule: Some("FREQ=WEEKLY;COUNT=4".to_string()),
+            timezone: Some("Europe/Paris".to_string()),
+            ..Default::default()
+        };
+
+        let ical = event.to_ical();
+        assert!(ical.contains("DTSTART;TZID=Europe/Paris:20240710T100000\r\n"));
+        assert!(ical.contains("ATTENDEE;RSVP=TRUE:mailto:anna@example.com\r\n"));
+        assert!(event.validate().is_ok());
+
+        let parsed = Event::from_str(&ical).unwrap();
+        assert_eq!(parsed.uid, "new-event-1");
+        assert_eq!(parsed.summary, event.summary);
+        assert_eq!(parsed.description, event.description);
+        assert_eq!(parsed.location, event.location);
+        assert_eq!(parsed.attendees.len(), 2);
+        assert_eq!(parsed.rrule, event.rrule);
+        assert_eq!(parsed.timezone, event.timezone);
+    }
+
+    #[test]
+    fn test_event_json_times() {
+        let timed = Event {
+            uid: "timed".to_string(),
+            start_time: "2024-07-10T08:00:00Z".parse().ok(),
+            timezone: Some("America/New_York".to_string()),
+            ..Default::default()
+        };
+        let json = timed.to_json(chrono_tz::Europe::Paris);
+        assert_eq!(json["start_time"], "2024-07-10T10:00:00+02:00");
+        assert_eq!(json["timezone"], "America/New_York");
+        assert!(json["end_time"].is_null());
+
+        let ical_data = "BEGIN:VEVENT\r\nUID:holiday\r\nDTSTART;VALUE=DATE:20240714\r\n\
+                         DTEND;VALUE=DATE:20240715\r\nSUMMARY:Holiday\r\nEND:VEVENT\r\n";
+        let all_day = Event::from_str(ical_data).unwrap();
+        let json = all_day.to_json(chrono_tz::America::Los_Angeles);
+        assert_eq!(json["all_day"], true);
+        assert_eq!(json["start_time"], "2024-07-14");
+        assert_eq!(json["end_time"], "2024-07-15");
+
+        // Unchanged dates are written back as dates
+        let mut renamed = all_day.clone();
+        renamed.summary = Some("Bastille Day".to_string());
+        let patched = renamed.patch_ical(ical_data).unwrap();
+        assert!(patched.contains("DTSTART;VALUE=DATE:20240714\r\n"));
+        assert!(renamed.to_ical().contains("DTEND;VALUE=DATE:20240715\r\n"));
+    }
+
+    #[test]
+    fn test_event_validate() {
+        let start_time = "2024-07-10T08:00:00Z".parse().ok();
+        let mut event = Event {
+            start_time,
+            end_time: "2024-07-10T07:00:00Z".parse().ok(),
+            ..Default::default()
+        };
+        assert!(event.validate().is_err());
+
+        event.end_time = None;
+        event.rrule = Some("FREQ=SOMETIMES".to_string());
+        assert!(event.validate().is_err());
+
+        event.rrule = None;
+        event.timezone = Some("Mars/Olympus_Mons".to_string());
+        assert!(event.validate().is_err());
+
+        event.start_time = None;
+        event.timezone = None;
+        assert!(event.validate().is_err());
+    }
+
+    #[test]
+    fn test_event_patch_ical() {
+        let stored = "BEGIN:VEVENT\r\nUID:weekly-1\r\nDTSTART:20240710T080000Z\r\n\
+                      DTEND:20240710T090000Z\r\nSUMMARY:Sync\r\nRRULE:FREQ=WEEKLY;COUNT=4\r\n\
+                      EXDATE:20240717T080000Z\r\nX-EVOLUTION-COLOR:red\r\nBEGIN:VALARM\r\n\
+                      ACTION:DISPLAY\r\nTRIGGER:-PT15M\r\nEND:VALARM\r\nEND:VEVENT\r\n";
+        let event = Event::from_str(stored).unwrap();
+
+        // Changing the whole series keeps exceptions, alarms and X- properties
+        let mut series = event.clone();
+        series.summary = Some("Weekly sync".to_string());
+        let patched = series.patch_ical(stored).unwrap();
+        assert!(patched.contains("SUMMARY:Weekly sync\r\n"));
+        assert!(!patched.contains("SUMMARY:Sync\r\n"));
+        assert!(patched.contains("EXDATE:20240717T080000Z\r\n"));
+        assert!(patched.contains("X-EVOLUTION-COLOR:red\r\n"));
+        assert!(patched.contains("TRIGGER:-PT15M\r\n"));
+
+        // A single occurrence becomes a detached instance of the series
+        let occurrence = "2024-07-24T08:00:00Z".parse().unwrap();
+        let mut instance = event.clone();
+        instance.recurrence_id = Some(occurrence);
+        instance.rrule = None;
+        instance.start_time = "2024-07-24T10:00:00Z".parse().ok();
+        instance.end_time = "2024-07-24T11:00:00Z".parse().ok();
+        let patched = instance.patch_ical(stored).unwrap();
+        assert!(patched.contains("RECURRENCE-ID:20240724T080000Z\r\n"));
+        assert!(patched.contains("DTSTART:20240724T100000Z\r\n"));
+        assert!(!patched.contains("RRULE"));
+        assert!(!patched.contains("EXDATE"));
+        assert!(patched.contains("TRIGGER:-PT15M\r\n"));
+
+        let parsed = Event::from_str(&patched).unwrap();
+        assert_eq!(parsed.recurrence_id, Some(occurrence));
+        assert_eq!(parsed.summary.as_deref(), Some("Sync"));
+    }
+
+    #[test]
+    fn test_expand_recurring_event() {
+        let series = "BEGIN:VEVENT\r\nUID:daily-1\r\nDTSTART:20240708T090000Z\r\n\
+                      DTEND:20240708T093000Z\r\nSUMMARY:Standup\r\nRRULE:FREQ=DAILY\r\n\
+                      EXDATE:20240710T090000Z\r\nRDATE:20240713T150000Z\r\n\
+                      BEGIN:VALARM\r\nX-EVOLUTION-ALARM-UID:a1\r\nACTION:DISPLAY\r\n\
+                      TRIGGER;VALUE=DURATION;RELATED=START:-PT15M\r\n\
+                      DESCRIPTION:Standup\r\nEND:VALARM\r\nEND:VEVENT\r\n";
+        let moved = "BEGIN:VEVENT\r\nUID:daily-1\r\nRECURRENCE-ID:20240711T090000Z\r\n\
+                     DTSTART:20240711T140000Z\r\nDTEND:20240711T143000Z\r\n\
+                     SUMMARY:Standup (moved)\r\nEND:VEVENT\r\n";
+        let single = "BEGIN:VEVENT\r\nUID:single-1\r\nDTSTART:20240712T120000Z\r\n\
+                      DTEND:20240712T130000Z\r\nSUMMARY:Lunch\r\nEND:VEVENT\r\n";
+        let objects = [series, moved, single].map(String::from);
+
+        let events = Event::expand(
+            &objects,
+            "2024-07-09T00:00:00Z".parse().unwrap(),
+            "2024-07-13T00:00:00Z".parse().unwrap(),
+        );
+        let starts = events
+            .iter()
+            .map(|event| event.start_time.unwrap().to_rfc3339())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            starts,
+            [
+                "2024-07-09T09:00:00+00:00",
+                "2024-07-11T14:00:00+00:00",
+                "2024-07-12T09:00:00+00:00",
+                "2024-07-12T12:00:00+00:00",
+            ]
+        );
+
+        assert_eq!(events[0].uid, "daily-1");
+        assert_eq!(events[0].series_uid.as_deref(), Some("daily-1"));
+        assert_eq!(events[0].recurrence_id, events[0].start_time);
+        assert_eq!(events[0].end_time, "2024-07-09T09:30:00Z".parse().ok());
+
+        // Each occurrence carries the alarms of the series
+        let alarm_times = events[2].alarm_times();
+        assert_eq!(alarm_times.len(), 1);
+        assert_eq!(alarm_times[0].0.description.as_deref(), Some("Standup"));
+        assert_eq!(alarm_times[0].1.to_rfc3339(), "2024-07-12T08:45:00+00:00");
+        assert!(events[3].alarms.is_empty());
+
+        // The detached instance keeps the start of the occurrence it replaces
+        assert_eq!(events[1].summary.as_deref(), Some("Standup (moved)"));
+        assert_eq!(events[1].recurrence_id, "2024-07-11T09:00:00Z".parse().ok());
+        assert_eq!(events[1].series_uid.as_deref(), Some("daily-1"));
+
+        assert_eq!(events[3].summary.as_deref(), Some("Lunch"));
+        assert_eq!(events[3].recurrence_id, None);
+        assert_eq!(events[3].series_uid, None);
+
+        // RDATEs add occurrences on top of the rule
+        let events = Event::expand(
+            &objects,
+            "2024-07-13T12:00:00Z".parse().unwrap(),
+            "2024-07-13T18:00:00Z".parse().unwrap(),
+        );
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].start_time, "2024-07-13T15:00:00Z".parse().ok());
+        assert_eq!(events[0].end_time, "2024-07-13T15:30:00Z".parse().ok());
+    }
+
+    #[test]
+    fn test_expand_long_running_series() {
+        let hourly = "BEGIN:VEVENT\r\nUID:hourly-1\r\nDTSTART:20200101T001500Z\r\n\
+                      DTEND:20200101T003000Z\r\nSUMMARY:Check\r\nRRULE:FREQ=HOURLY\r\n\
+                      EXDATE:20261018T051500Z\r\nEND:VEVENT\r\n";
+        let moved = "BEGIN:VEVENT\r\nUID:hourly-1\r\nRECURRENCE-ID:20261018T061500Z\r\n\
+                     DTSTART:20261018T064500Z\r\nDTEND:20261018T070000Z\r\n\
+                     SUMMARY:Check (late)\r\nEND:VEVENT\r\n";
+        let quarterly = "BEGIN:VEVENT\r\nUID:quarter-1\r\n\
+                         DTSTART;TZID=Europe/Paris:20190301T100000\r\n\
+                         DTEND;TZID=Europe/Paris:20190301T100500\r\n\
+                         RRULE:FREQ=MINUTELY;INTERVAL=15;BYHOUR=10\r\nEND:VEVENT\r\n";
+        let objects = [hourly, moved, quarterly].map(String::from);
+
+        let events = Event::expand(
+            &objects,
+            "2026-10-18T04:00:00Z".parse().unwrap(),
+            "2026-10-18T09:00:00Z".parse().unwrap(),
+        );
+        let starts = events
+            .iter()
+            .map(|event| (event.uid.as_str(), event.start_time.unwrap().to_rfc3339()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            starts,
+            [
+                ("hourly-1", "2026-10-18T04:15:00+00:00".to_string()),
+                ("hourly-1", "2026-10-18T06:45:00+00:00".to_string()),
+                ("hourly-1", "2026-10-18T07:15:00+00:00".to_string()),
+                ("quarter-1", "2026-10-18T08:00:00+00:00".to_string()),
+                ("hourly-1", "2026-10-18T08:15:00+00:00".to_string()),
+                ("quarter-1", "2026-10-18T08:15:00+00:00".to_string()),
+                ("quarter-1", "2026-10-18T08:30:00+00:00".to_string()),
+                ("quarter-1", "2026-10-18T08:45:00+00:00".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_expand_zoned_recurring_event() {
+        let series = "BEGIN:VEVENT\r\nUID:weekly-paris\r\n\
+                      DTSTART;TZID=Europe/Paris:20240301T100000\r\n\
+                      DTEND;TZID=Europe/Paris:20240301T110000\r\n\
+                      RRULE:FREQ=WEEKLY;COUNT=6\r\nEND:VEVENT\r\n";
+
+        // The occurrence after the DST change keeps its local time
+        let events = Event::expand(
+            &[series.to_string()],
+            "2024-03-01T00:00:00Z".parse().unwrap(),
+            "2024-04-10T00:00:00Z".parse().unwrap(),
+        );
+        assert_eq!(events.len(), 6);
+        assert_eq!(events[0].start_time, "2024-03-01T09:00:00Z".parse().ok());
+        assert_eq!(events[5].start_time, "2024-04-05T08:00:00Z".parse().ok());
+        assert_eq!(events[5].timezone.as_deref(), Some("Europe/Paris"));
+    }
 }
@@ -0,0 +1,258 @@
+use std::str::FromStr;
+
+use anyhow::{Context, Result};
+use gio::glib;
+
+use crate::gnome::evolution::{ical, Contact, Event, Task};
+
+const PRODID: &str = "-//GNOME MCP Server//EN";
+
+/// Kinds of calendar components an `.ics` file can bring in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ComponentKind {
+    Event,
+    Task,
+}
+
+impl ComponentKind {
+    fn name(self) -> &'static str {
+        match self {
+            Self::Event => "VEVENT",
+            Self::Task => "VTODO",
+        }
+    }
+}
+
+/// A series read from an `.ics` file: the components sharing a UID, in a
+/// VCALENDAR with the timezones they use, ready for `ReceiveObjects`
+#[derive(Debug, Clone, PartialEq)]
+pub struct CalendarObject {
+    pub uid: String,
+    pub kind: ComponentKind,
+    pub summary: Option<String>,
+    pub ical: String,
+}
+
+/// Split the content of an `.ics` file into its events and tasks, giving a
+/// UID to components that have none. Each series must parse as an
+/// [`Event`] or a [`Task`].
+pub fn split_calendar(data: &str) -> Result<Vec<CalendarObject>> {
+    let lines = ical::unfold(data);
+    let timezones = ical::components(&lines, "VTIMEZONE");
+
+    let mut objects = Vec::new();
+    for kind in [ComponentKind::Event, ComponentKind::Task] {
+        let mut series: Vec<(String, Vec<String>)> = Vec::new();
+        for mut component in ical::components(&lines, kind.name()) {
+            let uid = match component_uid(&component) {
+                Some(uid) => uid,
+                None => {
+                    let uid = glib::uuid_string_random().to_string();
+                    component.insert(1, format!("UID:{uid}"));
+                    uid
+                }
+            };
+            match series.iter_mut().find(|(series_uid, _)| *series_uid == uid) {
+                Some((_, components)) => components.extend(component),
+                None => series.push((uid, component)),
+            }
+        }
+
+        for (uid, components) in series {
+            let tzids = ical::tzids(&components);
+            let mut lines = calendar_header();
+            lines.push("METHOD:PUBLISH".to_owned());
+            lines.extend(
+                timezones
+                    .iter()
+                    .filter(|timezone| timezone_id(timezone).is_some_and(|id| tzids.contains(&id)))
+                    .flatten()
+                    .cloned(),
+            );
+            lines.extend(components);
+            lines.push("END:VCALENDAR".to_owned());
+            let ical = ical::fold(&lines);
+
+            let summary = match kind {
+                ComponentKind::Event => Event::from_str(&ical).map(|event| event.summary),
+                ComponentKind::Task => Task::from_str(&ical).map(|task| task.summary),
+            }
+            .with_context(|| format!("Invalid {} '{}'", kind.name(), uid))?;
+            objects.push(CalendarObject {
+                uid,
+                kind,
+                summary,
+                ical,
+            });
+        }
+    }
+
+    if objects.is_empty() {
+        anyhow::bail!("No VEVENT or VTODO found");
+    }
+    Ok(objects)
+}
+
+/// One `.ics` file holding the stored objects, with each timezone defined
+/// once
+pub fn join_calendar(objects: &[String]) -> String {
+    let mut timezones: Vec<Vec<String>> = Vec::new();
+    let mut components = Vec::new();
+    for object in objects {
+        let lines = ical::unfold(object);
+        for timezone in ical::components(&lines, "VTIMEZONE") {
+            let id = timezone_id(&timezone);
+            if !timezones.iter().any(|known| timezone_id(known) == id) {
+                timezones.push(timezone);
+            }
+        }
+        for kind in [ComponentKind::Event, ComponentKind::Task] {
+            components.extend(ical::components(&lines, kind.name()));
+        }
+    }
+
+    let mut lines = calendar_header();
+    lines.extend(timezones.into_iter().flatten());
+    lines.extend(components.into_iter().flatten());
+    lines.push("END:VCALENDAR".to_owned());
+    ical::fold(&lines)
+}
+
+/// Split the content of a `.vcf` file into its vCards, each of which must
+/// parse as a [`Contact`]
+pub fn split_vcards(data: &str) -> Result<Vec<(Contact, String)>> {
+    let cards = ical::components(&ical::unfold(data), "VCARD")
+        .iter()
+        .enumerate()
+        .map(|(index, card)| {
+            let vcard = ical::fold(card);
+            let contact = Contact::from_str(&vcard)
+                .with_context(|| format!("Invalid vCard #{}", index + 1))?;
+            Ok((contact, vcard))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    if cards.is_empty() {
+        anyhow::bail!("No vCard found");
+    }
+    Ok(cards)
+}
+
+/// One `.vcf` file holding the stored vCards
+pub fn join_vcards(vcards: &[String]) -> String {
+    vcards
+        .iter()
+        .map(|vcard| ical::fold(&ical::unfold(vcard)))
+        .collect()
+}
+
+fn calendar_header() -> Vec<String> {
+    vec![
+        "BEGIN:VCALENDAR".to_owned(),
+        "VERSION:2.0".to_owned(),
+        format!("PRODID:{PRODID}"),
+    ]
+}
+
+/// Value of the top-level property `name` of a component
+fn top_level_value(component: &[String], name: &str) -> Option<String> {
+    let mut depth = 0;
+    for line in component {
+        if line.starts_with("BEGIN:") {
+            depth += 1;
+        } else if line.starts_with("END:") {
+            depth -= 1;
+        } else if depth == 1 && ical::property_name(line) == name {
+            return line
+                .split_once(':')
+                .map(|(_, value)| value.trim().to_owned())
+                .filter(|value| !value.is_empty());
+        }
+    }
+    None
+}
+
+fn component_uid(component: &[String]) -> Option<String> {
+    top_level_value(component, "UID")
+}
+
+fn timezone_id(timezone: &[String]) -> Option<String> {
+    top_level_value(timezone, "TZID")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INVITE: &str = "BEGIN:VCALENDAR\r\nVERSION:2.0\r\nPRODID:-//Example//EN\r\n\
+        METHOD:REQUEST\r\n\
+        BEGIN:VTIMEZONE\r\nTZID:Custom Zone\r\nBEGIN:STANDARD\r\nDTSTART:19700101T000000\r\n\
+        TZOFFSETFROM:+0100\r\nTZOFFSETTO:+0100\r\nEND:STANDARD\r\nEND:VTIMEZONE\r\n\
+        BEGIN:VTIMEZONE\r\nTZID:Unused\r\nBEGIN:STANDARD\r\nDTSTART:19700101T000000\r\n\
+        TZOFFSETFROM:+0200\r\nTZOFFSETTO:+0200\r\nEND:STANDARD\r\nEND:VTIMEZONE\r\n\
+        BEGIN:VEVENT\r\nUID:weekly-1\r\nDTSTART;TZID=Custom Zone:20240710T100000\r\n\
+        SUMMARY:Sync\r\nRRULE:FREQ=WEEKLY\r\nBEGIN:VALARM\r\nACTION:DISPLAY\r\n\
+        TRIGGER:-PT5M\r\nEND:VALARM\r\nEND:VEVENT\r\n\
+        BEGIN:VEVENT\r\nUID:weekly-1\r\nRECURRENCE-ID:20240717T090000Z\r\n\
+        DTSTART:20240717T130000Z\r\nSUMMARY:Sync (moved)\r\nEND:VEVENT\r\n\
+        BEGIN:VTODO\r\nSUMMARY:Send minutes\r\nEND:VTODO\r\nEND:VCALENDAR\r\n";
+
+    #[test]
+    fn test_split_calendar() {
+        let objects = split_calendar(INVITE).unwrap();
+        assert_eq!(objects.len(), 2);
+
+        let series = &objects[0];
+        assert_eq!(series.uid, "weekly-1");
+        assert_eq!(series.kind, ComponentKind::Event);
+        assert_eq!(series.summary.as_deref(), Some("Sync"));
+        assert!(series.ical.contains("METHOD:PUBLISH\r\n"));
+        assert!(!series.ical.contains("METHOD:REQUEST"));
+        assert!(series.ical.contains("TZID:Custom Zone\r\n"));
+        assert!(!series.ical.contains("TZID:Unused"));
+        assert!(series.ical.contains("RECURRENCE-ID:20240717T090000Z\r\n"));
+        assert_eq!(Event::from_str(&series.ical).unwrap().alarms.len(), 1);
+
+        // Components without a UID get one
+        let task = &objects[1];
+        assert_eq!(task.kind, ComponentKind::Task);
+        assert!(!task.uid.is_empty());
+        assert!(task.ical.contains(&format!("UID:{}\r\n", task.uid)));
+
+        assert!(split_calendar("BEGIN:VCALENDAR\r\nEND:VCALENDAR\r\n").is_err());
+    }
+
+    #[test]
+    fn test_join_calendar() {
+        let objects = split_calendar(INVITE).unwrap();
+        let stored = objects
+            .iter()
+            .map(|object| object.ical.clone())
+            .collect::<Vec<_>>();
+
+        let file = join_calendar(&stored);
+        assert!(file.starts_with("BEGIN:VCALENDAR\r\nVERSION:2.0\r\n"));
+        assert_eq!(file.matches("BEGIN:VTIMEZONE").count(), 1);
+        assert_eq!(file.matches("BEGIN:VEVENT").count(), 2);
+        assert_eq!(file.matches("BEGIN:VTODO").count(), 1);
+        assert_eq!(split_calendar(&file).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_split_and_join_vcards() {
+        let data = "BEGIN:VCARD\r\nVERSION:3.0\r\nUID:anna\r\nFN:Anna\r\n\
+                    EMAIL:anna@example.com\r\nEND:VCARD\r\n\
+                    BEGIN:VCARD\r\nVERSION:3.0\r\nFN:Bob\r\nEND:VCARD\r\n";
+        let cards = split_vcards(data).unwrap();
+        assert_eq!(cards.len(), 2);
+        assert_eq!(cards[0].0.uid, "anna");
+        assert_eq!(cards[1].0.full_name.as_deref(), Some("Bob"));
+
+        let vcards = cards
+            .into_iter()
+            .map(|(_, vcard)| vcard)
+            .collect::<Vec<_>>();
+        assert_eq!(join_vcards(&vcards), data);
+        assert!(split_vcards("FN:Nobody\r\n").is_err());
+    }
+}
@@ -0,0 +1,425 @@
+use calcard::icalendar::{timezone::TzResolver, ICalendarEntry};
+use chrono::{DateTime, NaiveDate, Utc};
+
+/// Content lines of a component's properties, grouped by property name so
+/// that a stored component can be patched without losing what isn't modeled
+/// (alarms, exceptions, X- properties, parameters, ...)
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct PropertyList {
+    entries: Vec<(&'static str, Vec<String>)>,
+}
+
+impl PropertyList {
+    /// Write a TEXT property, escaping it
+    pub fn text(&mut self, name: &'static str, value: Option<&str>) {
+        self.push(
+            name,
+            value.map(|value| format!("{name}:{}", escape_text(value))),
+        );
+    }
+
+    /// Write a property whose value is already in iCalendar syntax, such as
+    /// an RRULE or a CAL-ADDRESS, with optional `;`-prefixed parameters
+    pub fn raw(&mut self, name: &'static str, params: &str, value: Option<&str>) {
+        self.push(name, value.map(|value| format!("{name}{params}:{value}")));
+    }
+
+    /// Write a DATE-TIME property, in UTC or as local time in `tz`
+    pub fn date_time(
+        &mut self,
+        name: &'static str,
+        value: Option<DateTime<Utc>>,
+        tz: Option<chrono_tz::Tz>,
+    ) {
+        self.push(
+            name,
+            value.map(|value| format!("{name}{}", format_date_time(value, tz))),
+        );
+    }
+
+    /// Write a DATE property, as used by all-day events
+    pub fn date(&mut self, name: &'static str, value: Option<NaiveDate>) {
+        self.push(
+            name,
+            value.map(|value| format!("{name};VALUE=DATE:{}", value.format("%Y%m%d"))),
+        );
+    }
+
+    /// Declare properties to drop, without replacement
+    pub fn remove(&mut self, names: &[&'static str]) {
+        for name in names {
+            self.push(name, None);
+        }
+    }
+
+    fn push(&mut self, name: &'static str, line: Option<String>) {
+        let lines = match self.entries.iter_mut().find(|(n, _)| *n == name) {
+            Some((_, lines)) => lines,
+            None => {
+                self.entries.push((name, Vec::new()));
+                &mut self.entries.last_mut().unwrap().1
+            }
+        };
+        lines.extend(line);
+    }
+
+    /// Append the properties of `other`
+    pub fn extend(&mut self, other: PropertyList) {
+        for (name, lines) in other.entries {
+            self.push(name, None);
+            for line in lines {
+                self.push(name, Some(line));
+            }
+        }
+    }
+
+    /// Properties whose content differs from `previous`
+    pub fn changes_from(&self, previous: &PropertyList) -> PropertyList {
+        let entries = self
+            .entries
+            .iter()
+            .filter(|(name, lines)| {
+                previous
+                    .entries
+                    .iter()
+                    .find(|(n, _)| n == name)
+                    .map_or(true, |(_, previous)| previous != lines)
+            })
+            .cloned()
+            .collect();
+        PropertyList { entries }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// A complete `name` component holding these properties
+    pub fn to_component(&self, name: &str) -> String {
+        self.to_component_with(name, &[])
+    }
+
+    /// A complete `name` component holding these properties, followed by
+    /// the unfolded lines of nested components such as alarms
+    pub fn to_component_with(&self, name: &str, subcomponents: &[String]) -> String {
+        let mut lines = self.lines(name);
+        let end = lines.pop();
+        lines.extend_from_slice(subcomponents);
+        lines.extend(end);
+        fold(&lines)
+    }
+
+    /// Unfolded content lines of a `name` component holding these properties
+    pub fn lines(&self, name: &str) -> Vec<String> {
+        let mut lines = vec![format!("BEGIN:{name}")];
+        lines.extend(self.entries.iter().flat_map(|(_, lines)| lines.clone()));
+        lines.push(format!("END:{name}"));
+        lines
+    }
+
+    /// Replace these properties in the unfolded lines of a stored component,
+    /// keeping its other properties and subcomponents
+    pub fn apply_to(&self, component: &[String]) -> Vec<String> {
+        let mut patched = Vec::with_capacity(component.len());
+        let mut depth = 0;
+        let mut inserted = false;
+
+        for (index, line) in component.iter().enumerate() {
+            let is_begin = line.starts_with("BEGIN:");
+            let is_end = line.starts_with("END:");
+            let is_last = index + 1 == component.len();
+
+            // New lines go after the top-level properties, before any nested
+            // component or the closing END
+            if !inserted && depth == 1 && (is_begin || is_last) {
+                patched.extend(self.entries.iter().flat_map(|(_, lines)| lines.clone()));
+                inserted = true;
+            }
+
+            if is_begin {
+                depth += 1;
+            } else if is_end {
+                depth -= 1;
+            } else if depth == 1 && self.entries.iter().any(|(n, _)| *n == property_name(line)) {
+                continue;
+            }
+            patched.push(line.clone());
+        }
+
+        patched
+    }
+}
+
+/// Rewrite the properties of a stored component that differ between
+/// `previous` and `updated`, or `None` when nothing changed
+pub fn patch(
+    component: &[String],
+    previous: &PropertyList,
+    updated: &PropertyList,
+) -> Option<String> {
+    let changes = updated.changes_from(previous);
+    (!changes.is_empty()).then(|| fold(&changes.apply_to(component)))
+}
+
+/// `;TZID=...:local` or `:utcZ` suffix of a DATE-TIME property
+fn format_date_time(value: DateTime<Utc>, tz: Option<chrono_tz::Tz>) -> String {
+    match tz {
+        Some(tz) => format!(
+            ";TZID={}:{}",
+            tz.name(),
+            value.with_timezone(&tz).format("%Y%m%dT%H%M%S")
+        ),
+        None => format!(":{}", format_utc(value)),
+    }
+}
+
+/// A DATE or DATE-TIME value in UTC, resolving its TZID against the
+/// calendar's timezones, and whether it is a plain DATE. Dates are kept at
+/// midnight UTC, as they don't designate an instant.
+pub fn date_time_value(
+    entry: &ICalendarEntry,
+    tz_resolver: &TzResolver,
+) -> Option<(DateTime<Utc>, bool)> {
+    let value = entry.values.first()?.as_partial_date_time()?;
+    if !value.has_time() {
+        let date =
+            NaiveDate::from_ymd_opt(value.year?.into(), value.month?.into(), value.day?.into())?;
+        return Some((date.and_hms_opt(0, 0, 0)?.and_utc(), true));
+    }
+    let date_time = value.to_date_time_with_tz(tz_resolver.resolve(entry.tz_id()))?;
+    Some((date_time.with_timezone(&Utc), false))
+}
+
+pub fn format_utc(value: DateTime<Utc>) -> String {
+    value.format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+/// The DATE-TIME value as EDS spells it in a recurrence ID: local time for
+/// zoned events, UTC otherwise
+pub fn recurrence_id_string(value: DateTime<Utc>, tz: Option<chrono_tz::Tz>) -> String {
+    match tz {
+        Some(tz) => value.with_timezone(&tz).format("%Y%m%dT%H%M%S").to_string(),
+        None => format_utc(value),
+    }
+}
+
+pub fn escape_text(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            ';' => escaped.push_str("\\;"),
+            ',' => escaped.push_str("\\,"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Normalize an attendee or organizer to a `mailto:` CAL-ADDRESS
+pub fn cal_address(value: &str) -> String {
+    let value = value.trim();
+    match value.split_once(':') {
+        Some((scheme, address)) if scheme.eq_ignore_ascii_case("mailto") => {
+            format!("mailto:{address}")
+        }
+        _ => format!("mailto:{value}"),
+    }
+}
+
+/// Join content lines, folding them at 75 octets
+pub fn fold(lines: &[String]) -> String {
+    let mut out = String::new();
+    for line in lines {
+        let mut width = 0;
+        for c in line.chars() {
+            if width + c.len_utf8() > 75 {
+                out.push_str("\r\n ");
+                width = 1;
+            }
+            out.push(c);
+            width += c.len_utf8();
+        }
+        out.push_str("\r\n");
+    }
+    out
+}
+
+/// Split iCalendar data into unfolded content lines
+pub fn unfold(data: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for line in data.lines() {
+        match (line.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(continuation), Some(last)) => last.push_str(continuation),
+            _ if line.is_empty() => {}
+            _ => lines.push(line.to_owned()),
+        }
+    }
+    lines
+}
+
+/// Upper-cased name of a content line's property
+pub fn property_name(line: &str) -> String {
+    line.split([';', ':'])
+        .next()
+        .unwrap_or_default()
+        .to_ascii_uppercase()
+}
+
+/// TZID parameters referenced by the properties in `lines`
+pub fn tzids(lines: &[String]) -> Vec<String> {
+    let mut tzids = Vec::new();
+    for line in lines {
+        // Parameters end at the first colon outside of a quoted value
+        let mut quoted = false;
+        let params_end = line
+            .char_indices()
+            .find(|&(_, c)| {
+                if c == '"' {
+                    quoted = !quoted;
+                }
+                c == ':' && !quoted
+            })
+            .map_or(line.len(), |(index, _)| index);
+
+        for param in line[..params_end].split(';').skip(1) {
+            if let Some((name, value)) = param.split_once('=') {
+                let value = value.trim_matches('"').to_owned();
+                if name.eq_ignore_ascii_case("TZID") && !tzids.contains(&value) {
+                    tzids.push(value);
+                }
+            }
+        }
+    }
+    tzids
+}
+
+/// Every `name` component in `lines`, including its subcomponents
+pub fn components(lines: &[String], name: &str) -> Vec<Vec<String>> {
+    let begin = format!("BEGIN:{name}");
+    let end = format!("END:{name}");
+    let mut components = Vec::new();
+    let mut current: Option<Vec<String>> = None;
+
+    for line in lines {
+        if current.is_none() && line.eq_ignore_ascii_case(&begin) {
+            current = Some(Vec::new());
+        }
+        if let Some(component) = current.as_mut() {
+            component.push(line.clone());
+            if line.eq_ignore_ascii_case(&end) {
+                components.extend(current.take());
+            }
+        }
+    }
+
+    components
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    #[test]
+    fn test_property_list_serialization() {
+        let start = Utc.with_ymd_and_hms(2024, 7, 10, 8, 0, 0).unwrap();
+        let mut properties = PropertyList::default();
+        properties.text("SUMMARY", Some("Lunch; with Anna, Bob\nand \\ friends"));
+        properties.date_time("DTSTART", Some(start), None);
+        properties.date_time("DTEND", Some(start), Some(chrono_tz::Europe::Paris));
+        properties.text("LOCATION", None);
+        properties.text("DESCRIPTION", Some(&"x".repeat(100)));
+        let ical = properties.to_component("VEVENT");
+
+        let lines = ical.split("\r\n").collect::<Vec<_>>();
+        assert_eq!(lines[0], "BEGIN:VEVENT");
+        assert_eq!(
+            lines[1],
+            "SUMMARY:Lunch\\; with Anna\\, Bob\\nand \\\\ friends"
+        );
+        assert_eq!(lines[2], "DTSTART:20240710T080000Z");
+        assert_eq!(lines[3], "DTEND;TZID=Europe/Paris:20240710T100000");
+        assert_eq!(lines[4].len(), 75);
+        assert!(lines[5].starts_with(' '));
+        assert_eq!(lines[6], "END:VEVENT");
+
+        let unfolded = unfold(&ical);
+        assert_eq!(unfolded.len(), 6);
+        assert_eq!(unfolded[4], format!("DESCRIPTION:{}", "x".repeat(100)));
+    }
+
+    #[test]
+    fn test_patch_keeps_unmodeled_properties() {
+        let stored = "BEGIN:VCALENDAR\r\nBEGIN:VEVENT\r\nUID:1\r\nSUMMARY:Old\r\nX-CUSTOM:keep\r\n\
+                      LOCATION:Room 1\r\nBEGIN:VALARM\r\nACTION:DISPLAY\r\nSUMMARY:Alarm\r\n\
+                      END:VALARM\r\nEND:VEVENT\r\nEND:VCALENDAR\r\n";
+        let events = components(&unfold(stored), "VEVENT");
+        assert_eq!(events.len(), 1);
+
+        let mut previous = PropertyList::default();
+        previous.text("SUMMARY", Some("Old"));
+        previous.text("LOCATION", Some("Room 1"));
+        let mut updated = PropertyList::default();
+        updated.text("SUMMARY", Some("New"));
+        updated.text("LOCATION", None);
+
+        let changes = updated.changes_from(&previous);
+        let patched = changes.apply_to(&events[0]);
+        assert_eq!(
+            patched,
+            [
+                "BEGIN:VEVENT",
+                "UID:1",
+                "X-CUSTOM:keep",
+                "SUMMARY:New",
+                "BEGIN:VALARM",
+                "ACTION:DISPLAY",
+                "SUMMARY:Alarm",
+                "END:VALARM",
+                "END:VEVENT"
+            ]
+        );
+        assert!(updated.changes_from(&updated).is_empty());
+    }
+
+    #[test]
+    fn test_tzids() {
+        let lines = [
+            "DTSTART;TZID=Europe/Paris:20240710T100000",
+            "DTEND;TZID=\"/freeassociation.sourceforge.net/Europe/Paris\":20240710T110000",
+            "EXDATE;VALUE=DATE-TIME;TZID=Europe/Paris:20240717T100000",
+            "DESCRIPTION:TZID=Not/A_Param",
+        ]
+        .map(String::from);
+        assert_eq!(
+            tzids(&lines),
+            [
+                "Europe/Paris",
+                "/freeassociation.sourceforge.net/Europe/Paris"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_cal_address() {
+        assert_eq!(cal_address("anna@example.com"), "mailto:anna@example.com");
+        assert_eq!(
+            cal_address("MAILTO:anna@example.com"),
+            "mailto:anna@example.com"
+        );
+    }
+
+    #[test]
+    fn test_recurrence_id_string() {
+        let value = Utc.with_ymd_and_hms(2024, 7, 10, 8, 0, 0).unwrap();
+        assert_eq!(recurrence_id_string(value, None), "20240710T080000Z");
+        assert_eq!(
+            recurrence_id_string(value, Some(chrono_tz::Europe::Paris)),
+            "20240710T100000"
+        );
+    }
+}
@@ -0,0 +1,352 @@
+use std::str::FromStr;
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use gio::glib;
+use serde::{Deserialize, Serialize};
+
+use crate::gnome::evolution::{
+    ical::{self, escape_text, PropertyList},
+    present_time, CalendarQuery, SourceFilter, SourceInfo, SourceResults, SourceType,
+};
+
+/// A VJOURNAL note from a memo list
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Memo {
+    pub uid: String,
+    pub summary: Option<String>,
+    pub description: Option<String>,
+    pub date: Option<DateTime<Utc>>, // DTSTART, the day the memo is about
+    pub all_day: bool,               // Dated rather than timed
+    pub categories: Vec<String>,
+    pub status: Option<String>, // DRAFT/FINAL/CANCELLED
+    pub class: Option<String>,  // PUBLIC/PRIVATE/CONFIDENTIAL
+    pub url: Option<String>,
+    pub created: Option<DateTime<Utc>>,
+    pub last_modified: Option<DateTime<Utc>>,
+    pub timezone: Option<String>, // TZID of the date
+}
+
+impl Memo {
+    /// JSON representation, with times in the `local` timezone
+    pub fn to_json(&self, local: chrono_tz::Tz) -> serde_json::Value {
+        let mut json = serde_json::to_value(self).unwrap_or_default();
+        for (field, value, all_day) in [
+            ("date", self.date, self.all_day),
+            ("created", self.created, false),
+            ("last_modified", self.last_modified, false),
+        ] {
+            json[field] = present_time(value, local, all_day);
+        }
+        json
+    }
+
+    /// The IANA zone of the date's TZID
+    fn tz(&self) -> Option<chrono_tz::Tz> {
+        match calcard::common::timezone::Tz::from_str(self.timezone.as_deref()?) {
+            Ok(calcard::common::timezone::Tz::Tz(tz)) => Some(tz),
+            _ => None,
+        }
+    }
+
+    fn properties(&self) -> PropertyList {
+        let categories = self
+            .categories
+            .iter()
+            .map(|c| escape_text(c))
+            .collect::<Vec<_>>();
+
+        let mut properties = PropertyList::default();
+        properties.text("UID", Some(&self.uid));
+        if self.all_day {
+            properties.date("DTSTART", self.date.map(|d| d.date_naive()));
+        } else {
+            properties.date_time("DTSTART", self.date, self.tz());
+        }
+        properties.text("SUMMARY", self.summary.as_deref());
+        properties.text("DESCRIPTION", self.description.as_deref());
+        properties.raw(
+            "CATEGORIES",
+            "",
+            (!categories.is_empty())
+                .then(|| categories.join(","))
+                .as_deref(),
+        );
+        properties.raw("STATUS", "", self.status.as_deref());
+        properties.raw("CLASS", "", self.class.as_deref());
+        properties.raw("URL", "", self.url.as_deref());
+        properties.date_time("CREATED", self.created, None);
+        properties.date_time("LAST-MODIFIED", self.last_modified, None);
+        properties
+    }
+
+    /// Serialize the memo as a new VJOURNAL component
+    pub fn to_ical(&self) -> String {
+        let mut properties = self.properties();
+        properties.date_time("DTSTAMP", Some(Utc::now()), None);
+        properties.to_component("VJOURNAL")
+    }
+
+    /// Apply the changes made to this memo to the VJOURNAL stored in EDS,
+    /// preserving the properties the model doesn't cover
+    pub fn patch_ical(&self, stored: &str) -> Result<String> {
+        let component = ical::components(&ical::unfold(stored), "VJOURNAL")
+            .into_iter()
+            .next()
+            .ok_or_else(|| anyhow::anyhow!("No VJOURNAL found for '{}'", self.uid))?;
+        let previous = Memo::from_str(&ical::fold(&component))?;
+        ical::patch(&component, &previous.properties(), &self.properties())
+            .ok_or_else(|| anyhow::anyhow!("No changes to apply to '{}'", self.uid))
+    }
+
+    /// Resolve a memo list by UID or display name, or the default memo
+    /// list, and open it for writing
+    pub async fn writable_memo_list(
+        connection: &zbus::Connection,
+        memo_list: Option<&str>,
+    ) -> Result<(SourceInfo, zbus::Proxy<'static>)> {
+        let source = crate::gnome::evolution::resolve_source(
+            connection,
+            memo_list,
+            "default-memo-list",
+            |kind| matches!(kind, SourceType::MemoList { .. }),
+        )
+        .await?;
+        let proxy = crate::gnome::evolution::open_writable_calendar(connection, &source).await?;
+        Ok((source, proxy))
+    }
+
+    /// Save the memo as a new object in a writable memo list, picked by UID
+    /// or display name (the default memo list otherwise), returning the UID
+    /// assigned by the backend
+    pub async fn create(&self, memo_list: Option<&str>) -> Result<String> {
+        let connection = zbus::Connection::session().await?;
+        let (_source, proxy) = Self::writable_memo_list(&connection, memo_list).await?;
+
+        let mut memo = self.clone();
+        if memo.uid.is_empty() {
+            memo.uid = glib::uuid_string_random().to_string();
+        }
+
+        let response = proxy
+            .call_method("CreateObjects", &(vec![memo.to_ical()], 0u32))
+            .await?;
+        let uids = response.body().deserialize::<Vec<String>>()?;
+        Ok(uids.into_iter().next().unwrap_or(memo.uid))
+    }
+
+    /// Look up a memo by UID in the given memo list, or in every memo list,
+    /// returning its source, backend and stored iCalendar data
+    pub async fn find(
+        connection: &zbus::Connection,
+        uid: &str,
+        memo_list: Option<&str>,
+    ) -> Result<(SourceInfo, zbus::Proxy<'static>, String)> {
+        crate::gnome::evolution::find_calendar_object(connection, uid, memo_list, |kind| {
+            matches!(kind, SourceType::MemoList { .. })
+        })
+        .await
+    }
+
+    /// Store changes to the memo, found with [`Memo::find`]
+    pub async fn modify(
+        &self,
+        source: &SourceInfo,
+        proxy: &zbus::Proxy<'_>,
+        stored: &str,
+    ) -> Result<()> {
+        crate::gnome::evolution::ensure_writable(proxy, source).await?;
+        proxy
+            .call_method(
+                "ModifyObjects",
+                &(vec![self.patch_ical(stored)?], "all", 0u32),
+            )
+            .await?;
+        Ok(())
+    }
+
+    /// Delete the memo, found with [`Memo::find`]
+    pub async fn remove(&self, source: &SourceInfo, proxy: &zbus::Proxy<'_>) -> Result<()> {
+        crate::gnome::evolution::ensure_writable(proxy, source).await?;
+        proxy
+            .call_method(
+                "RemoveObjects",
+                &(vec![(self.uid.as_str(), "")], "all", 0u32),
+            )
+            .await?;
+        Ok(())
+    }
+
+    /// Fetch every memo of the memo lists `sources` allows, newest first
+    pub async fn all(sources: &SourceFilter) -> Result<SourceResults<Memo>> {
+        let results = match crate::gnome::evolution::cache::memos(sources) {
+            Some(results) => results,
+            None => Self::search(&CalendarQuery::All, sources).await?,
+        };
+        Ok(results.map_items(|mut memos| {
+            memos.sort_by(|a, b| {
+                (b.date, b.last_modified, b.created).cmp(&(a.date, a.last_modified, a.created))
+            });
+            memos
+        }))
+    }
+
+    /// Fetch the memos matching `query` from the memo lists `sources`
+    /// allows, reading them concurrently
+    pub async fn search(
+        query: &CalendarQuery,
+        sources: &SourceFilter,
+    ) -> Result<SourceResults<Memo>> {
+        let connection = zbus::Connection::session().await?;
+        let sources = crate::gnome::evolution::select_sources(&connection, sources, |kind| {
+            matches!(kind, SourceType::MemoList { .. })
+        })
+        .await?;
+        let results = crate::gnome::evolution::fetch_sources(
+            sources,
+            crate::gnome::evolution::SOURCE_TIMEOUT,
+            |info| {
+                let connection = connection.clone();
+                let query = query.to_string();
+                async move {
+                    let proxy = crate::gnome::evolution::open_calendar(&connection, &info).await?;
+                    let response = proxy.call_method("GetObjectList", &(query,)).await?;
+                    let ical_objects = response.body().deserialize::<Vec<String>>()?;
+                    Ok(ical_objects
+                        .iter()
+                        .filter_map(|ical_data| Memo::from_str(ical_data).ok())
+                        .collect())
+                }
+            },
+        )
+        .await;
+
+        Ok(results)
+    }
+}
+
+impl FromStr for Memo {
+    type Err = anyhow::Error;
+
+    fn from_str(ical_data: &str) -> Result<Self, Self::Err> {
+        let ical = calcard::icalendar::ICalendar::parse(ical_data)
+            .map_err(|e| anyhow::anyhow!("Failed to parse iCalendar data: {:?}", e))?;
+
+        let journal_component = ical
+            .components
+            .iter()
+            .find(|c| {
+                matches!(
+                    c.component_type,
+                    calcard::icalendar::ICalendarComponentType::VJournal
+                )
+            })
+            .ok_or_else(|| anyhow::anyhow!("No VJOURNAL component found in iCalendar data"))?;
+
+        let tz_resolver = ical
+            .build_tz_resolver()
+            .with_default(calcard::common::timezone::Tz::UTC);
+        let date_time = |property| {
+            journal_component
+                .property(&property)
+                .and_then(|entry| ical::date_time_value(entry, &tz_resolver))
+        };
+        let text = |property| {
+            journal_component
+                .property(&property)
+                .and_then(|p| p.values.first())
+                .and_then(|v| v.as_text())
+                .filter(|s| !s.is_empty())
+                .map(|s| s.to_string())
+        };
+
+        let categories = journal_component
+            .properties(&calcard::icalendar::ICalendarProperty::Categories)
+            .flat_map(|p| &p.values)
+            .filter_map(|v| v.as_text())
+            .filter(|s| !s.is_empty())
+            .map(|s| s.to_string())
+            .collect();
+
+        let date = date_time(calcard::icalendar::ICalendarProperty::Dtstart);
+        let timezone = journal_component
+            .property(&calcard::icalendar::ICalendarProperty::Dtstart)
+            .and_then(|entry| entry.tz_id())
+            .map(ToOwned::to_owned);
+
+        Ok(Memo {
+            uid: text(calcard::icalendar::ICalendarProperty::Uid).unwrap_or_default(),
+            summary: text(calcard::icalendar::ICalendarProperty::Summary),
+            description: text(calcard::icalendar::ICalendarProperty::Description),
+            date: date.map(|(date, _)| date),
+            all_day: date.is_some_and(|(_, is_date)| is_date),
+            categories,
+            status: text(calcard::icalendar::ICalendarProperty::Status),
+            class: text(calcard::icalendar::ICalendarProperty::Class),
+            url: text(calcard::icalendar::ICalendarProperty::Url),
+            created: date_time(calcard::icalendar::ICalendarProperty::Created).map(|(t, _)| t),
+            last_modified: date_time(calcard::icalendar::ICalendarProperty::LastModified)
+                .map(|(t, _)| t),
+            timezone,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_memo_from_str() {
+        let ical_data = "BEGIN:VCALENDAR\r\nVERSION:2.0\r\nBEGIN:VJOURNAL\r\nUID:memo-1\r\n\
+                         DTSTART;VALUE=DATE:20240710\r\nSUMMARY:Retro notes\r\n\
+                         DESCRIPTION:Ship smaller\\, ship often\\nWrite tests first\r\n\
+                         CATEGORIES:Work,Team\r\nCLASS:PRIVATE\r\n\
+                         LAST-MODIFIED:20240711T080000Z\r\nEND:VJOURNAL\r\nEND:VCALENDAR\r\n";
+        let memo = Memo::from_str(ical_data).unwrap();
+
+        assert_eq!(memo.uid, "memo-1");
+        assert_eq!(memo.summary.as_deref(), Some("Retro notes"));
+        assert_eq!(
+            memo.description.as_deref(),
+            Some("Ship smaller, ship often\nWrite tests first")
+        );
+        assert!(memo.all_day);
+        assert_eq!(memo.categories, ["Work", "Team"]);
+        assert_eq!(memo.class.as_deref(), Some("PRIVATE"));
+
+        let json = memo.to_json(chrono_tz::Europe::Paris);
+        assert_eq!(json["date"], "2024-07-10");
+        assert_eq!(json["last_modified"], "2024-07-11T10:00:00+02:00");
+
+        assert!(Memo::from_str("BEGIN:VTODO\r\nUID:1\r\nEND:VTODO\r\n").is_err());
+    }
+
+    #[test]
+    fn test_memo_round_trip_and_patch() {
+        let memo = Memo {
+            uid: "memo-2".to_string(),
+            summary: Some("Packing list".to_string()),
+            description: Some("Passport; charger".to_string()),
+            categories: vec!["Travel".to_string()],
+            ..Default::default()
+        };
+        let stored = memo.to_ical();
+        assert!(stored.starts_with("BEGIN:VJOURNAL\r\n"));
+        let parsed = Memo::from_str(&stored).unwrap();
+        assert_eq!(parsed.summary, memo.summary);
+        assert_eq!(parsed.description, memo.description);
+        assert_eq!(parsed.categories, memo.categories);
+
+        let stored = stored.replace("END:VJOURNAL", "X-EVOLUTION-KEEP:1\r\nEND:VJOURNAL");
+        let mut edited = parsed;
+        edited.description = Some("Passport; charger; adapter".to_string());
+        let patched = edited.patch_ical(&stored).unwrap();
+        assert!(patched.contains("DESCRIPTION:Passport\\; charger\\; adapter\r\n"));
+        assert!(patched.contains("X-EVOLUTION-KEEP:1\r\n"));
+        assert!(Memo::from_str(&stored)
+            .unwrap()
+            .patch_ical(&stored)
+            .is_err());
+    }
+}
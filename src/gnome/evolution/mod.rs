@@ -1,15 +1,29 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, future::Future, str::FromStr, time::Duration};
 
-use anyhow::Result;
-use gio::glib;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use gio::{glib, prelude::*};
+use serde::{Deserialize, Serialize};
 use zbus::zvariant::OwnedObjectPath;
 
+pub mod alarm;
+pub mod attendee;
+pub mod cache;
 pub mod contact;
 pub mod event;
+pub mod files;
+pub mod ical;
+pub mod memo;
+pub mod query;
+pub mod source_manager;
 pub mod task;
 
-pub use contact::Contact;
+pub use alarm::Alarm;
+pub use attendee::Attendee;
+pub use contact::{photo_uri, Contact, ContactDate, TypedValue};
 pub use event::Event;
+pub use memo::Memo;
+pub use query::{CalendarQuery, ComponentField, ContactField, ContactQuery};
 pub use task::Task;
 
 #[derive(Debug, Clone)]
@@ -20,22 +34,110 @@ pub struct SourceInfo {
     pub display_name: String,
     pub enabled: bool,
     pub source_type: SourceType,
+    /// Color picked for the calendar, task list or memo list, as `#rrggbb`
+    pub color: Option<String>,
+    /// UID of the source this one belongs to, usually an account
+    pub parent: Option<String>,
+    /// Display name of the parent source
+    pub account: Option<String>,
 }
 
 #[derive(Debug, Clone)]
-#[allow(dead_code)]
 pub enum SourceType {
     Calendar { backend_name: String },
     TaskList { backend_name: String },
+    MemoList { backend_name: String },
     AddressBook { backend_name: String },
 }
 
-fn parse_source_data(path: OwnedObjectPath, uid: String, data: String) -> Option<SourceInfo> {
-    let key_file = glib::KeyFile::new();
-    key_file
-        .load_from_data(&data, glib::KeyFileFlags::NONE)
-        .ok()?;
+impl SourceType {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Calendar { .. } => "calendar",
+            Self::TaskList { .. } => "task_list",
+            Self::MemoList { .. } => "memo_list",
+            Self::AddressBook { .. } => "address_book",
+        }
+    }
+
+    pub fn backend_name(&self) -> &str {
+        match self {
+            Self::Calendar { backend_name }
+            | Self::TaskList { backend_name }
+            | Self::MemoList { backend_name }
+            | Self::AddressBook { backend_name } => backend_name,
+        }
+    }
+
+    /// Group of the source's keyfile describing this kind of source
+    pub fn group(&self) -> &'static str {
+        match self {
+            Self::Calendar { .. } => "Calendar",
+            Self::TaskList { .. } => "Task List",
+            Self::MemoList { .. } => "Memo List",
+            Self::AddressBook { .. } => "Address Book",
+        }
+    }
+
+    /// Key of the user's default source of this kind
+    fn default_key(&self) -> &'static str {
+        match self {
+            Self::Calendar { .. } => "default-calendar",
+            Self::TaskList { .. } => "default-task-list",
+            Self::MemoList { .. } => "default-memo-list",
+            Self::AddressBook { .. } => "default-address-book",
+        }
+    }
+}
+
+impl SourceInfo {
+    /// Whether `selector` is this source's UID or display name
+    pub fn matches(&self, selector: &str) -> bool {
+        self.uid == selector || self.display_name.eq_ignore_ascii_case(selector)
+    }
+
+    /// Whether this is the user's default source of its kind
+    pub fn is_default(&self) -> bool {
+        self.uid == default_source_uid(self.source_type.default_key())
+    }
+}
+
+/// Which sources to read, by UID or display name. An empty include list
+/// stands for every source.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SourceFilter {
+    #[serde(rename = "include_sources")]
+    pub include: Vec<String>,
+    #[serde(rename = "exclude_sources")]
+    pub exclude: Vec<String>,
+}
+
+impl SourceFilter {
+    /// Only the source named by `selector`, or every source when unset
+    pub fn only(selector: Option<&str>) -> Self {
+        Self::default().narrowed(selector)
+    }
+
+    /// Restrict the filter to the source named by `selector`, if any
+    pub fn narrowed(mut self, selector: Option<&str>) -> Self {
+        if let Some(selector) = selector {
+            self.include = vec![selector.to_owned()];
+        }
+        self
+    }
+
+    pub fn allows(&self, info: &SourceInfo) -> bool {
+        (self.include.is_empty() || self.include.iter().any(|selector| info.matches(selector)))
+            && !self.exclude.iter().any(|selector| info.matches(selector))
+    }
+}
 
+fn parse_source_data(
+    path: OwnedObjectPath,
+    uid: String,
+    key_file: &glib::KeyFile,
+) -> Option<SourceInfo> {
     // Check if source is enabled
     let enabled = key_file.boolean("Data Source", "Enabled").unwrap_or(false);
     if !enabled {
@@ -46,6 +148,12 @@ fn parse_source_data(path: OwnedObjectPath, uid: String, data: String) -> Option
         .string("Data Source", "DisplayName")
         .unwrap_or_else(|_| "Unknown".into());
 
+    let parent = key_file
+        .string("Data Source", "Parent")
+        .ok()
+        .map(|parent| parent.to_string())
+        .filter(|parent| !parent.is_empty());
+
     // Check what type of source this is
     let source_type = if key_file.has_group("Calendar") {
         let backend_name = key_file
@@ -61,6 +169,13 @@ fn parse_source_data(path: OwnedObjectPath, uid: String, data: String) -> Option
         SourceType::TaskList {
             backend_name: backend_name.to_string(),
         }
+    } else if key_file.has_group("Memo List") {
+        let backend_name = key_file
+            .string("Memo List", "BackendName")
+            .unwrap_or_else(|_| "unknown".into());
+        SourceType::MemoList {
+            backend_name: backend_name.to_string(),
+        }
     } else if key_file.has_group("Address Book") {
         let backend_name = key_file
             .string("Address Book", "BackendName")
@@ -72,24 +187,53 @@ fn parse_source_data(path: OwnedObjectPath, uid: String, data: String) -> Option
         return None;
     };
 
+    let color = match source_type {
+        SourceType::AddressBook { .. } => None,
+        _ => key_file.string(source_type.group(), "Color").ok(),
+    }
+    .map(|color| color.to_string())
+    .filter(|color| !color.is_empty());
+
     Some(SourceInfo {
         uid,
         path,
         display_name: display_name.to_string(),
         enabled,
         source_type,
+        color,
+        parent,
+        account: None,
     })
 }
 
-pub async fn get_evolution_sources(
-    connection: &zbus::Connection,
-) -> Result<HashMap<OwnedObjectPath, (SourceInfo, zbus::Proxy<'static>)>> {
+/// A source object exported by the registry, enabled or not
+pub struct SourceObject {
+    pub path: OwnedObjectPath,
+    pub uid: String,
+    /// Keyfile describing the source
+    pub data: String,
+    pub proxy: zbus::Proxy<'static>,
+}
+
+impl SourceObject {
+    pub fn key_file(&self) -> Option<glib::KeyFile> {
+        let key_file = glib::KeyFile::new();
+        key_file
+            .load_from_data(&self.data, glib::KeyFileFlags::KEEP_COMMENTS)
+            .ok()?;
+        Some(key_file)
+    }
+}
+
+/// Every source of the registry whose keyfile can be read, accounts and
+/// disabled sources included
+pub async fn source_objects(connection: &zbus::Connection) -> Result<Vec<SourceObject>> {
     let proxy = zbus::fdo::ObjectManagerProxy::builder(connection)
         .destination("org.gnome.evolution.dataserver.Sources5")?
         .path("/org/gnome/evolution/dataserver/SourceManager")?
         .build()
         .await?;
-    let mut sources = HashMap::new();
+    let mut sources = Vec::new();
 
     // Get all managed objects
     let objects = proxy.get_managed_objects().await?;
@@ -104,11 +248,43 @@ pub async fn get_evolution_sources(
         let data = proxy.get_property::<String>("Data").await?;
         let uid = proxy.get_property::<String>("UID").await?;
 
-        if let Some(source_info) = parse_source_data(object_path.clone(), uid, data) {
-            sources.insert(object_path, (source_info, proxy));
+        sources.push(SourceObject {
+            path: object_path,
+            uid,
+            data,
+            proxy,
+        });
+    }
+    Ok(sources)
+}
+
+pub async fn get_evolution_sources(
+    connection: &zbus::Connection,
+) -> Result<HashMap<OwnedObjectPath, (SourceInfo, zbus::Proxy<'static>)>> {
+    let mut sources = HashMap::new();
+    // Display names of every source, including accounts and other parents
+    let mut names = HashMap::new();
+
+    for object in source_objects(connection).await? {
+        let Some(key_file) = object.key_file() else {
+            continue;
+        };
+        if let Ok(name) = key_file.string("Data Source", "DisplayName") {
+            names.insert(object.uid.clone(), name.to_string());
+        }
+
+        if let Some(source_info) = parse_source_data(object.path.clone(), object.uid, &key_file) {
+            sources.insert(object.path, (source_info, object.proxy));
         }
     }
 
+    for (info, _proxy) in sources.values_mut() {
+        info.account = info
+            .parent
+            .as_ref()
+            .and_then(|parent| names.get(parent).cloned());
+    }
+
     Ok(sources)
 }
 
@@ -146,6 +322,23 @@ pub async fn open_task_list_source(
     Ok((task_list_path, bus_name))
 }
 
+pub async fn open_memo_list_source(
+    connection: &zbus::Connection,
+    source_uid: &str,
+) -> Result<(String, String)> {
+    let proxy = zbus::Proxy::new(
+        connection,
+        "org.gnome.evolution.dataserver.Calendar8",
+        "/org/gnome/evolution/dataserver/CalendarFactory",
+        "org.gnome.evolution.dataserver.CalendarFactory",
+    )
+    .await?;
+
+    let response = proxy.call_method("OpenMemoList", &(source_uid,)).await?;
+    let (memo_list_path, bus_name) = response.body().deserialize::<(String, String)>()?;
+    Ok((memo_list_path, bus_name))
+}
+
 pub async fn open_address_book_source(
     connection: &zbus::Connection,
     source_uid: &str,
@@ -162,3 +355,455 @@ pub async fn open_address_book_source(
     let (address_book_path, bus_name) = response.body().deserialize::<(String, String)>()?;
     Ok((address_book_path, bus_name))
 }
+
+const DEFAULT_SOURCES_SCHEMA: &str = "org.gnome.Evolution.DefaultSources";
+
+/// The user's timezone, as set in timedate1, falling back to the zone of
+/// the system and to UTC
+pub async fn local_timezone() -> chrono_tz::Tz {
+    let configured = match zbus::Connection::system().await {
+        Ok(connection) => match zbus::Proxy::new(
+            &connection,
+            "org.freedesktop.timedate1",
+            "/org/freedesktop/timedate1",
+            "org.freedesktop.timedate1",
+        )
+        .await
+        {
+            Ok(proxy) => proxy.get_property::<String>("Timezone").await.ok(),
+            Err(_) => None,
+        },
+        Err(_) => None,
+    };
+
+    configured
+        .into_iter()
+        .chain(iana_time_zone::get_timezone().ok())
+        .find_map(|timezone| timezone.parse::<chrono_tz::Tz>().ok())
+        .unwrap_or(chrono_tz::UTC)
+}
+
+/// JSON representation of a time: RFC 3339 in the `local` timezone, or a
+/// plain date for all-day values
+pub fn present_time(
+    value: Option<DateTime<Utc>>,
+    local: chrono_tz::Tz,
+    all_day: bool,
+) -> serde_json::Value {
+    match value {
+        Some(value) if all_day => value.format("%Y-%m-%d").to_string().into(),
+        Some(value) => value.with_timezone(&local).to_rfc3339().into(),
+        None => serde_json::Value::Null,
+    }
+}
+
+/// UID of the user's default source for `key` (e.g. `default-calendar`),
+/// falling back to the built-in `system-*` source
+fn default_source_uid(key: &str) -> String {
+    gio::SettingsSchemaSource::default()
+        .and_then(|source| source.lookup(DEFAULT_SOURCES_SCHEMA, true))
+        .map(|_| {
+            gio::Settings::new(DEFAULT_SOURCES_SCHEMA)
+                .string(key)
+                .to_string()
+        })
+        .filter(|uid| !uid.is_empty())
+        .unwrap_or_else(|| key.replacen("default-", "system-", 1))
+}
+
+/// Find a source by UID or display name, or the user's default source of
+/// that kind when no selector is given
+pub async fn resolve_source(
+    connection: &zbus::Connection,
+    selector: Option<&str>,
+    default_key: &str,
+    is_kind: fn(&SourceType) -> bool,
+) -> Result<SourceInfo> {
+    let sources = get_evolution_sources(connection).await?;
+    let mut candidates = sources
+        .into_values()
+        .map(|(info, _proxy)| info)
+        .filter(|info| is_kind(&info.source_type));
+
+    match selector {
+        Some(selector) => candidates
+            .find(|info| info.matches(selector))
+            .ok_or_else(|| anyhow::anyhow!("No enabled source named '{}'", selector)),
+        None => {
+            let uid = default_source_uid(default_key);
+            candidates
+                .find(|info| info.uid == uid)
+                .ok_or_else(|| anyhow::anyhow!("Default source '{}' is not available", uid))
+        }
+    }
+}
+
+/// The sources of a kind that `filter` allows
+pub async fn select_sources(
+    connection: &zbus::Connection,
+    filter: &SourceFilter,
+    is_kind: fn(&SourceType) -> bool,
+) -> Result<Vec<SourceInfo>> {
+    let sources = get_evolution_sources(connection).await?;
+    let selected = sources
+        .into_values()
+        .map(|(info, _proxy)| info)
+        .filter(|info| is_kind(&info.source_type))
+        .filter(|info| filter.allows(info))
+        .collect::<Vec<_>>();
+
+    if selected.is_empty() && !filter.include.is_empty() {
+        anyhow::bail!("No enabled source named '{}'", filter.include.join("', '"));
+    }
+    Ok(selected)
+}
+
+/// How long a single source may take to answer a read
+pub const SOURCE_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// A source that could not be read
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SourceError {
+    pub uid: String,
+    pub display_name: String,
+    pub error: String,
+}
+
+/// What was read from several sources, along with the sources that failed
+#[derive(Debug)]
+pub struct SourceResults<T> {
+    pub items: Vec<T>,
+    pub errors: Vec<SourceError>,
+}
+
+impl<T> SourceResults<T> {
+    pub fn map_items(self, f: impl FnOnce(Vec<T>) -> Vec<T>) -> Self {
+        Self {
+            items: f(self.items),
+            errors: self.errors,
+        }
+    }
+}
+
+/// Read every source at once, giving each `timeout` to answer. Items are
+/// kept in the order of the sources, and a failing source only adds to the
+/// errors.
+pub async fn fetch_sources<T, F, Fut>(
+    sources: Vec<SourceInfo>,
+    timeout: Duration,
+    fetch: F,
+) -> SourceResults<T>
+where
+    T: Send + 'static,
+    F: Fn(SourceInfo) -> Fut,
+    Fut: Future<Output = Result<Vec<T>>> + Send + 'static,
+{
+    let mut tasks = tokio::task::JoinSet::new();
+    for (index, info) in sources.into_iter().enumerate() {
+        let fetched = fetch(info.clone());
+        tasks.spawn(async move {
+            let result = match tokio::time::timeout(timeout, fetched).await {
+                Ok(result) => result,
+                Err(_) => Err(anyhow::anyhow!(
+                    "No answer within {} seconds",
+                    timeout.as_secs_f64()
+                )),
+            };
+            (index, info, result)
+        });
+    }
+
+    let mut results = Vec::new();
+    while let Some(joined) = tasks.join_next().await {
+        match joined {
+            Ok(result) => results.push(result),
+            Err(err) => tracing::warn!("Source read task failed: {}", err),
+        }
+    }
+    results.sort_by_key(|(index, _, _)| *index);
+
+    let mut fetched = SourceResults {
+        items: Vec::new(),
+        errors: Vec::new(),
+    };
+    for (_, info, result) in results {
+        match result {
+            Ok(items) => fetched.items.extend(items),
+            Err(err) => {
+                tracing::warn!("Failed to read '{}': {:#}", info.display_name, err);
+                fetched.errors.push(SourceError {
+                    uid: info.uid,
+                    display_name: info.display_name,
+                    error: format!("{:#}", err),
+                });
+            }
+        }
+    }
+    fetched
+}
+
+/// The objects matching `query` in the sources of the kind `filter`
+/// allows, as EDS stores them: calendar components along with the
+/// definitions of the custom timezones they use, or vCards
+pub async fn stored_objects(
+    filter: &SourceFilter,
+    is_kind: fn(&SourceType) -> bool,
+    query: String,
+) -> Result<SourceResults<String>> {
+    let connection = zbus::Connection::session().await?;
+    let sources = select_sources(&connection, filter, is_kind).await?;
+    let results = fetch_sources(sources, SOURCE_TIMEOUT, |info| {
+        let connection = connection.clone();
+        let query = query.clone();
+        async move {
+            if let SourceType::AddressBook { .. } = info.source_type {
+                let proxy = open_address_book(&connection, &info).await?;
+                let response = proxy.call_method("GetContactList", &(query,)).await?;
+                return Ok(response.body().deserialize::<Vec<String>>()?);
+            }
+            let proxy = open_calendar(&connection, &info).await?;
+            let response = proxy.call_method("GetObjectList", &(query,)).await?;
+            let mut objects = response.body().deserialize::<Vec<String>>()?;
+            let timezones = referenced_timezones(&proxy, &objects).await;
+            objects.extend(timezones);
+            Ok(objects)
+        }
+    })
+    .await;
+    Ok(results)
+}
+
+/// Definitions of the TZIDs used by `objects` that don't name a known zone,
+/// as stored by the calendar backend
+pub async fn referenced_timezones(proxy: &zbus::Proxy<'_>, objects: &[String]) -> Vec<String> {
+    let tzids = objects
+        .iter()
+        .flat_map(|ical_data| ical::tzids(&ical::unfold(ical_data)))
+        .filter(|tzid| calcard::common::timezone::Tz::from_str(tzid).is_err())
+        .collect::<std::collections::BTreeSet<_>>();
+
+    let mut timezones = Vec::new();
+    for tzid in tzids {
+        match proxy.call_method("GetTimezone", &(tzid.as_str(),)).await {
+            Ok(response) => timezones.extend(response.body().deserialize::<String>().ok()),
+            Err(err) => tracing::debug!("Failed to get timezone '{}': {}", tzid, err),
+        }
+    }
+    timezones
+}
+
+/// Whether the backend of a source accepts changes, `None` when it can't
+/// be opened
+pub async fn is_writable(connection: &zbus::Connection, info: &SourceInfo) -> Option<bool> {
+    let proxy = match info.source_type {
+        SourceType::AddressBook { .. } => open_address_book(connection, info).await,
+        _ => open_calendar(connection, info).await,
+    }
+    .ok()?;
+    proxy.get_property::<bool>("Writable").await.ok()
+}
+
+/// Address of the user on an open calendar backend, as the account
+/// appears among the attendees of invitations
+pub async fn calendar_email_address(proxy: &zbus::Proxy<'_>) -> Option<String> {
+    proxy
+        .get_property::<String>("CalEmailAddress")
+        .await
+        .ok()
+        .filter(|address| !address.is_empty())
+}
+
+/// Open the calendar backend of a calendar, task list or memo list source
+pub async fn open_calendar(
+    connection: &zbus::Connection,
+    info: &SourceInfo,
+) -> Result<zbus::Proxy<'static>> {
+    let (path, bus_name) = match info.source_type {
+        SourceType::Calendar { .. } => open_calendar_source(connection, &info.uid).await?,
+        SourceType::TaskList { .. } => open_task_list_source(connection, &info.uid).await?,
+        SourceType::MemoList { .. } => open_memo_list_source(connection, &info.uid).await?,
+        SourceType::AddressBook { .. } => {
+            anyhow::bail!(
+                "'{}' is not a calendar, task list or memo list",
+                info.display_name
+            )
+        }
+    };
+
+    let proxy = zbus::Proxy::new(
+        connection,
+        bus_name,
+        path,
+        "org.gnome.evolution.dataserver.Calendar",
+    )
+    .await?;
+    Ok(proxy)
+}
+
+/// Open the backend of an address book source
+pub async fn open_address_book(
+    connection: &zbus::Connection,
+    info: &SourceInfo,
+) -> Result<zbus::Proxy<'static>> {
+    let (path, bus_name) = open_address_book_source(connection, &info.uid).await?;
+    let proxy = zbus::Proxy::new(
+        connection,
+        bus_name,
+        path,
+        "org.gnome.evolution.dataserver.AddressBook",
+    )
+    .await?;
+    proxy.call_method("Open", &()).await?;
+    Ok(proxy)
+}
+
+/// Refuse to change objects stored in a read-only source
+pub async fn ensure_writable(proxy: &zbus::Proxy<'_>, info: &SourceInfo) -> Result<()> {
+    let writable = proxy
+        .get_property::<bool>("Writable")
+        .await
+        .with_context(|| format!("Failed to query '{}'", info.display_name))?;
+    if !writable {
+        anyhow::bail!("'{}' is read-only", info.display_name);
+    }
+    Ok(())
+}
+
+/// Open the calendar backend of a calendar, task list or memo list source,
+/// refusing read-only ones
+pub async fn open_writable_calendar(
+    connection: &zbus::Connection,
+    info: &SourceInfo,
+) -> Result<zbus::Proxy<'static>> {
+    let proxy = open_calendar(connection, info).await?;
+    ensure_writable(&proxy, info).await?;
+    Ok(proxy)
+}
+
+/// Find the calendar, task list or memo list holding the object `uid`,
+/// searching every source of the kind unless `selector` names one, and return
+/// the source, its backend and the stored iCalendar data
+pub async fn find_calendar_object(
+    connection: &zbus::Connection,
+    uid: &str,
+    selector: Option<&str>,
+    is_kind: fn(&SourceType) -> bool,
+) -> Result<(SourceInfo, zbus::Proxy<'static>, String)> {
+    for info in select_sources(connection, &SourceFilter::only(selector), is_kind).await? {
+        let Ok(proxy) = open_calendar(connection, &info).await else {
+            continue;
+        };
+        if let Ok(response) = proxy.call_method("GetObject", &(uid, "")).await {
+            let ical_data = response.body().deserialize::<String>()?;
+            return Ok((info, proxy, ical_data));
+        }
+    }
+
+    match selector {
+        Some(selector) => anyhow::bail!("No object with UID '{}' in '{}'", uid, selector),
+        None => anyhow::bail!("No object with UID '{}'", uid),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn source(data: &str) -> Option<SourceInfo> {
+        let key_file = glib::KeyFile::new();
+        key_file
+            .load_from_data(data, glib::KeyFileFlags::NONE)
+            .unwrap();
+        parse_source_data(
+            OwnedObjectPath::try_from("/org/gnome/evolution/dataserver/SourceManager/Source_1")
+                .unwrap(),
+            "work-calendar".to_string(),
+            &key_file,
+        )
+    }
+
+    #[test]
+    fn test_parse_source_data() {
+        let info = source(
+            "[Data Source]\nDisplayName=Work\nEnabled=true\nParent=google-account\n\n\
+             [Calendar]\nBackendName=caldav\nColor=#3465a4\n",
+        )
+        .unwrap();
+        assert_eq!(info.display_name, "Work");
+        assert_eq!(info.source_type.name(), "calendar");
+        assert_eq!(info.source_type.backend_name(), "caldav");
+        assert_eq!(info.color.as_deref(), Some("#3465a4"));
+        assert_eq!(info.parent.as_deref(), Some("google-account"));
+
+        let info = source(
+            "[Data Source]\nDisplayName=Notes\nEnabled=true\n\n\
+             [Memo List]\nBackendName=caldav\nColor=#73d216\n",
+        )
+        .unwrap();
+        assert_eq!(info.source_type.name(), "memo_list");
+        assert_eq!(info.source_type.backend_name(), "caldav");
+        assert_eq!(info.color.as_deref(), Some("#73d216"));
+
+        // Disabled sources and accounts are skipped
+        assert!(source("[Data Source]\nDisplayName=Work\nEnabled=false\n\n[Calendar]\n").is_none());
+        assert!(source(
+            "[Data Source]\nDisplayName=me@example.org\nEnabled=true\n\n[Collection]\n"
+        )
+        .is_none());
+    }
+
+    #[tokio::test]
+    async fn test_fetch_sources() {
+        let data =
+            |name: &str| format!("[Data Source]\nDisplayName={name}\nEnabled=true\n\n[Calendar]\n");
+        let sources = ["Slow", "Broken", "Work", "Personal"]
+            .iter()
+            .map(|name| source(&data(name)).unwrap())
+            .collect::<Vec<_>>();
+
+        let results = fetch_sources(sources, Duration::from_millis(100), |info| async move {
+            match info.display_name.as_str() {
+                "Slow" => {
+                    tokio::time::sleep(Duration::from_secs(10)).await;
+                    Ok(vec!["late".to_string()])
+                }
+                "Broken" => Err(anyhow::anyhow!("Authentication required")),
+                name => {
+                    // Answer out of order
+                    if name == "Work" {
+                        tokio::time::sleep(Duration::from_millis(20)).await;
+                    }
+                    Ok(vec![format!("{name} 1"), format!("{name} 2")])
+                }
+            }
+        })
+        .await;
+
+        assert_eq!(
+            results.items,
+            ["Work 1", "Work 2", "Personal 1", "Personal 2"]
+        );
+        assert_eq!(results.errors.len(), 2);
+        assert_eq!(results.errors[0].display_name, "Slow");
+        assert!(results.errors[0].error.starts_with("No answer within"));
+        assert_eq!(results.errors[1].error, "Authentication required");
+    }
+
+    #[test]
+    fn test_source_filter() {
+        let info = source(
+            "[Data Source]\nDisplayName=Work\nEnabled=true\n\n[Task List]\nBackendName=local\n",
+        )
+        .unwrap();
+        assert!(SourceFilter::default().allows(&info));
+        assert!(SourceFilter::only(Some("work")).allows(&info));
+        assert!(!SourceFilter::only(Some("Personal")).allows(&info));
+
+        let filter = SourceFilter {
+            include: Vec::new(),
+            exclude: vec!["work-calendar".to_string()],
+        };
+        assert!(!filter.allows(&info));
+        assert!(!filter.narrowed(Some("Work")).allows(&info));
+    }
+}
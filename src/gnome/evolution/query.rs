@@ -0,0 +1,233 @@
+use std::fmt;
+
+use chrono::{DateTime, Utc};
+
+/// Contact fields that address book queries can test
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContactField {
+    /// Any text field of the contact
+    AnyField,
+    FullName,
+    Email,
+    Phone,
+    Nickname,
+    Organization,
+    Category,
+}
+
+impl ContactField {
+    fn name(self) -> &'static str {
+        match self {
+            Self::AnyField => "x-evolution-any-field",
+            Self::FullName => "full_name",
+            Self::Email => "email",
+            Self::Phone => "phone",
+            Self::Nickname => "nickname",
+            Self::Organization => "org",
+            Self::Category => "category_list",
+        }
+    }
+}
+
+/// An address book search, rendered as the S-expression EDS evaluates
+#[derive(Debug, Clone, PartialEq)]
+pub enum ContactQuery {
+    All,
+    Contains(ContactField, String),
+    BeginsWith(ContactField, String),
+    Exists(ContactField),
+    And(Vec<ContactQuery>),
+    Or(Vec<ContactQuery>),
+}
+
+impl ContactQuery {
+    /// Both `self` and `other`, flattening nested conjunctions
+    pub fn and(self, other: Self) -> Self {
+        match (self, other) {
+            (Self::All, query) | (query, Self::All) => query,
+            (Self::And(mut queries), Self::And(others)) => {
+                queries.extend(others);
+                Self::And(queries)
+            }
+            (Self::And(mut queries), query) => {
+                queries.push(query);
+                Self::And(queries)
+            }
+            (query, other) => Self::And(vec![query, other]),
+        }
+    }
+}
+
+impl fmt::Display for ContactQuery {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            // An empty any-field test is how EDS spells "everything"
+            Self::All => write!(f, "(contains \"{}\" \"\")", ContactField::AnyField.name()),
+            Self::Contains(field, value) => test(f, "contains", field.name(), value),
+            Self::BeginsWith(field, value) => test(f, "beginswith", field.name(), value),
+            Self::Exists(field) => write!(f, "(exists \"{}\")", field.name()),
+            Self::And(queries) => group(f, "and", queries),
+            Self::Or(queries) => group(f, "or", queries),
+        }
+    }
+}
+
+/// Text properties of calendar components that `contains?` can search
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ComponentField {
+    /// Any text property of the component
+    Any,
+    Summary,
+    Location,
+    Attendee,
+    Organizer,
+}
+
+impl ComponentField {
+    fn name(self) -> &'static str {
+        match self {
+            Self::Any => "any",
+            Self::Summary => "summary",
+            Self::Location => "location",
+            Self::Attendee => "attendee",
+            Self::Organizer => "organizer",
+        }
+    }
+}
+
+/// A calendar or task list search, rendered as the S-expression EDS
+/// evaluates
+#[derive(Debug, Clone, PartialEq)]
+pub enum CalendarQuery {
+    All,
+    Contains(ComponentField, String),
+    HasCategory(String),
+    /// Components with an occurrence overlapping the range
+    OccursIn(DateTime<Utc>, DateTime<Utc>),
+    /// Tasks due within the range
+    DueIn(DateTime<Utc>, DateTime<Utc>),
+    Completed,
+    And(Vec<CalendarQuery>),
+    Or(Vec<CalendarQuery>),
+    Not(Box<CalendarQuery>),
+}
+
+impl CalendarQuery {
+    /// Both `self` and `other`, flattening nested conjunctions
+    pub fn and(self, other: Self) -> Self {
+        match (self, other) {
+            (Self::All, query) | (query, Self::All) => query,
+            (Self::And(mut queries), Self::And(others)) => {
+                queries.extend(others);
+                Self::And(queries)
+            }
+            (Self::And(mut queries), query) => {
+                queries.push(query);
+                Self::And(queries)
+            }
+            (query, other) => Self::And(vec![query, other]),
+        }
+    }
+
+    pub fn not(self) -> Self {
+        Self::Not(Box::new(self))
+    }
+}
+
+impl fmt::Display for CalendarQuery {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::All => write!(f, "#t"),
+            Self::Contains(field, value) => test(f, "contains?", field.name(), value),
+            Self::HasCategory(category) => write!(f, "(has-categories? {})", quote(category)),
+            Self::OccursIn(start, end) => range(f, "occur-in-time-range?", start, end),
+            Self::DueIn(start, end) => range(f, "due-in-time-range?", start, end),
+            Self::Completed => write!(f, "(is-completed?)"),
+            Self::And(queries) => group(f, "and", queries),
+            Self::Or(queries) => group(f, "or", queries),
+            Self::Not(query) => write!(f, "(not {query})"),
+        }
+    }
+}
+
+fn test(f: &mut fmt::Formatter<'_>, function: &str, field: &str, value: &str) -> fmt::Result {
+    write!(f, "({function} {} {})", quote(field), quote(value))
+}
+
+fn range(
+    f: &mut fmt::Formatter<'_>,
+    function: &str,
+    start: &DateTime<Utc>,
+    end: &DateTime<Utc>,
+) -> fmt::Result {
+    write!(
+        f,
+        "({function} (make-time \"{}\") (make-time \"{}\"))",
+        start.format("%Y%m%dT%H%M%SZ"),
+        end.format("%Y%m%dT%H%M%SZ")
+    )
+}
+
+fn group<T: fmt::Display>(
+    f: &mut fmt::Formatter<'_>,
+    function: &str,
+    queries: &[T],
+) -> fmt::Result {
+    write!(f, "({function}")?;
+    for query in queries {
+        write!(f, " {query}")?;
+    }
+    write!(f, ")")
+}
+
+/// A string literal, with quotes and backslashes escaped
+fn quote(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_contact_query() {
+        assert_eq!(
+            ContactQuery::All.to_string(),
+            r#"(contains "x-evolution-any-field" "")"#
+        );
+
+        let query = ContactQuery::All
+            .and(ContactQuery::BeginsWith(
+                ContactField::FullName,
+                "Ann".to_string(),
+            ))
+            .and(ContactQuery::Contains(
+                ContactField::Email,
+                "say \"hi\" \\o/".to_string(),
+            ))
+            .and(ContactQuery::Exists(ContactField::Phone));
+        assert_eq!(
+            query.to_string(),
+            r#"(and (beginswith "full_name" "Ann") (contains "email" "say \"hi\" \\o/") (exists "phone"))"#
+        );
+    }
+
+    #[test]
+    fn test_calendar_query() {
+        let start = "2024-07-10T00:00:00Z".parse().unwrap();
+        let end = "2024-07-11T00:00:00Z".parse().unwrap();
+        let query = CalendarQuery::OccursIn(start, end)
+            .and(CalendarQuery::Or(vec![
+                CalendarQuery::Contains(ComponentField::Summary, "standup".to_string()),
+                CalendarQuery::HasCategory("Work".to_string()),
+            ]))
+            .and(CalendarQuery::Completed.not());
+        assert_eq!(
+            query.to_string(),
+            "(and (occur-in-time-range? (make-time \"20240710T000000Z\") \
+             (make-time \"20240711T000000Z\")) (or (contains? \"summary\" \"standup\") \
+             (has-categories? \"Work\")) (not (is-completed?)))"
+        );
+        assert_eq!(CalendarQuery::All.to_string(), "#t");
+    }
+}
@@ -0,0 +1,240 @@
+use anyhow::Result;
+use gio::glib;
+
+use crate::gnome::evolution::{source_objects, SourceObject, SourceType};
+
+/// Prefix of the UIDs of the sources created by this server, the only ones
+/// it changes or removes
+pub const CREATED_SOURCE_PREFIX: &str = "gnome-mcp-";
+
+const SOURCES_BUS_NAME: &str = "org.gnome.evolution.dataserver.Sources5";
+
+pub fn is_created(uid: &str) -> bool {
+    uid.starts_with(CREATED_SOURCE_PREFIX)
+}
+
+/// Kind of a new source, kept by the local backend
+pub fn local_source_type(kind: &str) -> Result<SourceType> {
+    let backend_name = "local".to_string();
+    Ok(match kind {
+        "calendar" => SourceType::Calendar { backend_name },
+        "task_list" => SourceType::TaskList { backend_name },
+        "memo_list" => SourceType::MemoList { backend_name },
+        "address_book" => SourceType::AddressBook { backend_name },
+        _ => anyhow::bail!(
+            "Unknown kind '{}'. Available: calendar, task_list, memo_list, address_book",
+            kind
+        ),
+    })
+}
+
+/// Check that `color` is spelled `#rrggbb`, as Evolution stores it
+pub fn validate_color(color: &str) -> Result<()> {
+    match color.strip_prefix('#') {
+        Some(hex) if hex.len() == 6 && hex.bytes().all(|b| b.is_ascii_hexdigit()) => Ok(()),
+        _ => anyhow::bail!("Invalid color '{}', expected #rrggbb", color),
+    }
+}
+
+/// Keyfile of a new enabled source of the local backend, in the format the
+/// registry stores in `~/.config/evolution/sources`
+pub fn new_source_data(kind: &SourceType, display_name: &str, color: Option<&str>) -> String {
+    let key_file = glib::KeyFile::new();
+    key_file.set_string("Data Source", "DisplayName", display_name);
+    key_file.set_boolean("Data Source", "Enabled", true);
+    key_file.set_string("Data Source", "Parent", "local-stub");
+    key_file.set_string(kind.group(), "BackendName", kind.backend_name());
+    if let Some(color) = color {
+        key_file.set_string(kind.group(), "Color", color);
+    }
+    key_file.to_data().to_string()
+}
+
+/// Changes to a source created by the server, `None` leaving a setting as
+/// it is
+#[derive(Debug, Default)]
+pub struct SourceChanges {
+    pub display_name: Option<String>,
+    pub color: Option<String>,
+    pub enabled: Option<bool>,
+}
+
+/// The group describing the kind of source in a keyfile
+fn kind_group(key_file: &glib::KeyFile) -> Option<&'static str> {
+    ["Calendar", "Task List", "Memo List", "Address Book"]
+        .into_iter()
+        .find(|group| key_file.has_group(group))
+}
+
+/// Display name in a source keyfile
+pub fn display_name(object: &SourceObject) -> String {
+    object
+        .key_file()
+        .and_then(|key_file| key_file.string("Data Source", "DisplayName").ok())
+        .map_or_else(|| object.uid.clone(), |name| name.to_string())
+}
+
+/// Apply `changes` to the keyfile of a source, keeping its other settings
+pub fn apply_changes(data: &str, changes: &SourceChanges) -> Result<String> {
+    let key_file = glib::KeyFile::new();
+    key_file.load_from_data(data, glib::KeyFileFlags::KEEP_COMMENTS)?;
+    if let Some(display_name) = &changes.display_name {
+        key_file.set_string("Data Source", "DisplayName", display_name);
+    }
+    if let Some(enabled) = changes.enabled {
+        key_file.set_boolean("Data Source", "Enabled", enabled);
+    }
+    if let Some(color) = &changes.color {
+        match kind_group(&key_file) {
+            Some("Address Book") | None => anyhow::bail!("Address books have no color"),
+            Some(group) => key_file.set_string(group, "Color", color),
+        }
+    }
+    Ok(key_file.to_data().to_string())
+}
+
+/// Register a new source with the registry, returning its UID
+pub async fn create_source(connection: &zbus::Connection, data: String) -> Result<String> {
+    let uid = format!("{}{}", CREATED_SOURCE_PREFIX, glib::uuid_string_random());
+    let proxy = zbus::Proxy::new(
+        connection,
+        SOURCES_BUS_NAME,
+        "/org/gnome/evolution/dataserver/SourceManager",
+        "org.gnome.evolution.dataserver.SourceManager",
+    )
+    .await?;
+    let sources = std::collections::HashMap::from([(uid.as_str(), data.as_str())]);
+    proxy.call_method("CreateSources", &(sources,)).await?;
+    Ok(uid)
+}
+
+/// A source created by the server, enabled or not, by UID or display name
+pub async fn find_created_source(
+    connection: &zbus::Connection,
+    selector: &str,
+) -> Result<SourceObject> {
+    let mut matches = source_objects(connection)
+        .await?
+        .into_iter()
+        .filter(|object| is_created(&object.uid))
+        .filter(|object| {
+            object.uid == selector || display_name(object).eq_ignore_ascii_case(selector)
+        })
+        .collect::<Vec<_>>();
+    match matches.len() {
+        0 => anyhow::bail!(
+            "No source created by this server matches '{}'; other sources can't be changed",
+            selector
+        ),
+        1 => Ok(matches.remove(0)),
+        _ => anyhow::bail!(
+            "Several sources are named '{}', use the UID instead",
+            selector
+        ),
+    }
+}
+
+/// Store a new keyfile for a source
+pub async fn write_source(
+    connection: &zbus::Connection,
+    object: &SourceObject,
+    data: &str,
+) -> Result<()> {
+    let proxy = zbus::Proxy::new(
+        connection,
+        SOURCES_BUS_NAME,
+        object.path.clone(),
+        "org.gnome.evolution.dataserver.Source.Writable",
+    )
+    .await?;
+    proxy.call_method("Write", &(data,)).await?;
+    Ok(())
+}
+
+/// Remove a source from the registry, along with the data the local
+/// backend keeps for it
+pub async fn remove_source(connection: &zbus::Connection, object: &SourceObject) -> Result<()> {
+    let proxy = zbus::Proxy::new(
+        connection,
+        SOURCES_BUS_NAME,
+        object.path.clone(),
+        "org.gnome.evolution.dataserver.Source.Removable",
+    )
+    .await?;
+    proxy.call_method("Remove", &()).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use zbus::zvariant::OwnedObjectPath;
+
+    use super::*;
+    use crate::gnome::evolution::parse_source_data;
+
+    #[test]
+    fn test_source_data() {
+        let kind = local_source_type("task_list").unwrap();
+        let data = new_source_data(&kind, "Project X", Some("#ff7800"));
+
+        let key_file = glib::KeyFile::new();
+        key_file
+            .load_from_data(&data, glib::KeyFileFlags::NONE)
+            .unwrap();
+        let path =
+            OwnedObjectPath::try_from("/org/gnome/evolution/dataserver/SourceManager/Source_9")
+                .unwrap();
+        let uid = format!("{CREATED_SOURCE_PREFIX}1");
+        let info = parse_source_data(path.clone(), uid.clone(), &key_file).unwrap();
+        assert_eq!(info.display_name, "Project X");
+        assert_eq!(info.source_type.name(), "task_list");
+        assert_eq!(info.source_type.backend_name(), "local");
+        assert_eq!(info.color.as_deref(), Some("#ff7800"));
+        assert_eq!(info.parent.as_deref(), Some("local-stub"));
+        assert!(is_created(&info.uid));
+
+        let changes = SourceChanges {
+            display_name: Some("Project X; deadlines".to_string()),
+            color: Some("#3465a4".to_string()),
+            ..Default::default()
+        };
+        let data = apply_changes(&data, &changes).unwrap();
+        key_file
+            .load_from_data(&data, glib::KeyFileFlags::NONE)
+            .unwrap();
+        let info = parse_source_data(path.clone(), uid.clone(), &key_file).unwrap();
+        assert_eq!(info.display_name, "Project X; deadlines");
+        assert_eq!(info.color.as_deref(), Some("#3465a4"));
+
+        let disabled = SourceChanges {
+            enabled: Some(false),
+            ..Default::default()
+        };
+        let data = apply_changes(&data, &disabled).unwrap();
+        key_file
+            .load_from_data(&data, glib::KeyFileFlags::NONE)
+            .unwrap();
+        assert!(parse_source_data(path, uid, &key_file).is_none());
+
+        let address_book = new_source_data(
+            &local_source_type("address_book").unwrap(),
+            "Project X",
+            None,
+        );
+        let recolor = SourceChanges {
+            color: Some("#3465a4".to_string()),
+            ..Default::default()
+        };
+        assert!(apply_changes(&address_book, &recolor).is_err());
+        assert!(local_source_type("journal").is_err());
+    }
+
+    #[test]
+    fn test_validate_color() {
+        assert!(validate_color("#3465a4").is_ok());
+        assert!(validate_color("#3465A4").is_ok());
+        assert!(validate_color("3465a4").is_err());
+        assert!(validate_color("#3465a").is_err());
+        assert!(validate_color("blue").is_err());
+    }
+}
@@ -1,14 +1,23 @@
 use std::str::FromStr;
 
 use anyhow::Result;
+use calcard::icalendar::{ICalendarParameter, ICalendarRelationshipType};
 use chrono::{DateTime, Utc};
+use gio::glib;
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+use crate::gnome::evolution::{
+    alarm,
+    ical::{self, escape_text, PropertyList},
+    present_time, Alarm, CalendarQuery, SourceFilter, SourceInfo, SourceResults, SourceType,
+};
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Task {
     pub summary: Option<String>,
     pub description: Option<String>,
     pub due_date: Option<DateTime<Utc>>,
+    pub all_day: bool, // Due and start on dates rather than times
     pub completed_date: Option<DateTime<Utc>>,
     pub status: String,
     pub uid: String,
@@ -20,12 +29,37 @@ pub struct Task {
     pub last_modified: Option<DateTime<Utc>>,
     pub location: Option<String>,
     pub url: Option<String>,
-    pub class: Option<String>, // PUBLIC/PRIVATE/CONFIDENTIAL
+    pub class: Option<String>,    // PUBLIC/PRIVATE/CONFIDENTIAL
+    pub timezone: Option<String>, // TZID of the due or start date
+    pub parent: Option<String>,   // UID of the parent task (RELATED-TO)
+    pub children: Vec<String>,    // UIDs of subtasks listed with RELTYPE=CHILD
+    pub alarms: Vec<Alarm>,
 }
 
 impl Task {
-    pub fn to_json(&self) -> serde_json::Value {
-        serde_json::to_value(self).unwrap_or_default()
+    /// JSON representation, with times in the `local` timezone and dates
+    /// for tasks due on a day
+    pub fn to_json(&self, local: chrono_tz::Tz) -> serde_json::Value {
+        let mut json = serde_json::to_value(self).unwrap_or_default();
+        for (field, value, all_day) in [
+            ("due_date", self.due_date, self.all_day),
+            ("start_date", self.start_date, self.all_day),
+            ("completed_date", self.completed_date, false),
+            ("created", self.created, false),
+            ("last_modified", self.last_modified, false),
+        ] {
+            json[field] = present_time(value, local, all_day);
+        }
+        json["alarms"] = self.alarms.iter().map(|a| a.to_json(local)).collect();
+        json
+    }
+
+    /// The IANA zone of the due or start date's TZID
+    fn tz(&self) -> Option<chrono_tz::Tz> {
+        match calcard::common::timezone::Tz::from_str(self.timezone.as_deref()?) {
+            Ok(calcard::common::timezone::Tz::Tz(tz)) => Some(tz),
+            _ => None,
+        }
     }
 
     pub fn is_completed(&self) -> bool {
@@ -36,39 +70,258 @@ impl Task {
         self.status == "CANCELLED"
     }
 
+    /// Whether the task passes the filters of the tasks resource. Tasks
+    /// without a due date are kept by `due_limit`.
+    fn is_listed(
+        &self,
+        include_completed: bool,
+        include_cancelled: bool,
+        due_limit: Option<DateTime<Utc>>,
+    ) -> bool {
+        (include_completed || !(self.is_completed() || self.completed_date.is_some()))
+            && (include_cancelled || !self.is_cancelled())
+            && due_limit.map_or(true, |limit| self.due_date.map_or(true, |due| due < limit))
+    }
+
+    /// Mark the task as done at `now`
+    pub fn complete(&mut self, now: DateTime<Utc>) {
+        self.status = "COMPLETED".to_string();
+        self.completed_date = Some(now);
+        self.percent_complete = Some(100);
+    }
+
+    fn properties(&self) -> PropertyList {
+        let categories = self
+            .categories
+            .iter()
+            .map(|c| escape_text(c))
+            .collect::<Vec<_>>();
+
+        let mut properties = PropertyList::default();
+        properties.text("UID", Some(&self.uid));
+        if self.all_day {
+            properties.date("DTSTART", self.start_date.map(|d| d.date_naive()));
+            properties.date("DUE", self.due_date.map(|d| d.date_naive()));
+        } else {
+            properties.date_time("DTSTART", self.start_date, self.tz());
+            properties.date_time("DUE", self.due_date, self.tz());
+        }
+        properties.date_time("COMPLETED", self.completed_date, None);
+        properties.text("SUMMARY", self.summary.as_deref());
+        properties.text("DESCRIPTION", self.description.as_deref());
+        properties.raw(
+            "STATUS",
+            "",
+            Some(&self.status)
+                .filter(|s| !s.is_empty())
+                .map(String::as_str),
+        );
+        properties.raw(
+            "PRIORITY",
+            "",
+            self.priority.map(|p| p.to_string()).as_deref(),
+        );
+        properties.raw(
+            "PERCENT-COMPLETE",
+            "",
+            self.percent_complete.map(|p| p.to_string()).as_deref(),
+        );
+        properties.raw(
+            "CATEGORIES",
+            "",
+            (!categories.is_empty())
+                .then(|| categories.join(","))
+                .as_deref(),
+        );
+        properties.date_time("CREATED", self.created, None);
+        properties.date_time("LAST-MODIFIED", self.last_modified, None);
+        properties.text("LOCATION", self.location.as_deref());
+        properties.raw("URL", "", self.url.as_deref());
+        properties.raw("CLASS", "", self.class.as_deref());
+        properties.text("RELATED-TO", self.parent.as_deref());
+        for child in &self.children {
+            properties.raw("RELATED-TO", ";RELTYPE=CHILD", Some(&escape_text(child)));
+        }
+        properties
+    }
+
+    /// Serialize the task as a new VTODO component, with its alarms
+    pub fn to_ical(&self) -> String {
+        let mut properties = self.properties();
+        properties.date_time("DTSTAMP", Some(Utc::now()), None);
+        let alarms = self
+            .alarms
+            .iter()
+            .flat_map(Alarm::to_lines)
+            .collect::<Vec<_>>();
+        properties.to_component_with("VTODO", &alarms)
+    }
+
+    /// When each of the task's alarms goes off, relative to its start or
+    /// due date
+    pub fn alarm_times(&self) -> Vec<(&Alarm, DateTime<Utc>)> {
+        self.alarms
+            .iter()
+            .filter_map(|alarm| Some((alarm, alarm.fire_time(self.start_date, self.due_date)?)))
+            .collect()
+    }
+
+    /// Apply the changes made to this task to the VTODO stored in EDS,
+    /// preserving alarms and other properties the model doesn't cover
+    pub fn patch_ical(&self, stored: &str) -> Result<String> {
+        let component = ical::components(&ical::unfold(stored), "VTODO")
+            .into_iter()
+            .next()
+            .ok_or_else(|| anyhow::anyhow!("No VTODO found for '{}'", self.uid))?;
+        let previous = Task::from_str(&ical::fold(&component))?;
+        ical::patch(&component, &previous.properties(), &self.properties())
+            .ok_or_else(|| anyhow::anyhow!("No changes to apply to '{}'", self.uid))
+    }
+
+    /// Resolve a task list by UID or display name, or the default task
+    /// list, and open it for writing
+    pub async fn writable_task_list(
+        connection: &zbus::Connection,
+        task_list: Option<&str>,
+    ) -> Result<(SourceInfo, zbus::Proxy<'static>)> {
+        let source = crate::gnome::evolution::resolve_source(
+            connection,
+            task_list,
+            "default-task-list",
+            |kind| matches!(kind, SourceType::TaskList { .. }),
+        )
+        .await?;
+        let proxy = crate::gnome::evolution::open_writable_calendar(connection, &source).await?;
+        Ok((source, proxy))
+    }
+
+    /// Save the task as a new object in a writable task list, picked by UID
+    /// or display name (the default task list otherwise), returning the UID
+    /// assigned by the backend
+    pub async fn create(&self, task_list: Option<&str>) -> Result<String> {
+        let connection = zbus::Connection::session().await?;
+        let (_source, proxy) = Self::writable_task_list(&connection, task_list).await?;
+
+        let mut task = self.clone();
+        if task.uid.is_empty() {
+            task.uid = glib::uuid_string_random().to_string();
+        }
+
+        let response = proxy
+            .call_method("CreateObjects", &(vec![task.to_ical()], 0u32))
+            .await?;
+        let uids = response.body().deserialize::<Vec<String>>()?;
+        Ok(uids.into_iter().next().unwrap_or(task.uid))
+    }
+
+    /// Look up a task by UID in the given task list, or in every task list,
+    /// returning its source, backend and stored iCalendar data
+    pub async fn find(
+        connection: &zbus::Connection,
+        uid: &str,
+        task_list: Option<&str>,
+    ) -> Result<(SourceInfo, zbus::Proxy<'static>, String)> {
+        crate::gnome::evolution::find_calendar_object(connection, uid, task_list, |kind| {
+            matches!(kind, SourceType::TaskList { .. })
+        })
+        .await
+    }
+
+    /// Store changes to the task, found with [`Task::find`]
+    pub async fn modify(
+        &self,
+        source: &SourceInfo,
+        proxy: &zbus::Proxy<'_>,
+        stored: &str,
+    ) -> Result<()> {
+        crate::gnome::evolution::ensure_writable(proxy, source).await?;
+        proxy
+            .call_method(
+                "ModifyObjects",
+                &(vec![self.patch_ical(stored)?], "all", 0u32),
+            )
+            .await?;
+        Ok(())
+    }
+
+    /// Delete the task, found with [`Task::find`]
+    pub async fn remove(&self, source: &SourceInfo, proxy: &zbus::Proxy<'_>) -> Result<()> {
+        crate::gnome::evolution::ensure_writable(proxy, source).await?;
+        proxy
+            .call_method(
+                "RemoveObjects",
+                &(vec![(self.uid.as_str(), "")], "all", 0u32),
+            )
+            .await?;
+        Ok(())
+    }
+
     /// Fetch all tasks from Evolution Data Server with filtering options
     pub async fn all(
         include_completed: bool,
         include_cancelled: bool,
         due_within_days: u32,
-    ) -> Result<Vec<Task>> {
-        let connection = zbus::Connection::session().await?;
-        let sources = crate::gnome::evolution::get_evolution_sources(&connection).await?;
-        let mut all_tasks = Vec::new();
-
-        for (_source_path, (info, _proxy)) in sources {
-            if matches!(
-                info.source_type,
-                crate::gnome::evolution::SourceType::TaskList { .. }
-            ) {
-                let (task_list_path, bus_name) =
-                    crate::gnome::evolution::open_task_list_source(&connection, &info.uid).await?;
-                if let Ok(tasks) = Self::fetch_from_source(
-                    &connection,
-                    &task_list_path,
-                    &bus_name,
-                    include_completed,
-                    include_cancelled,
-                    due_within_days,
-                )
-                .await
-                {
-                    all_tasks.extend(tasks);
+        sources: &SourceFilter,
+    ) -> Result<SourceResults<Task>> {
+        let due_limit = (due_within_days > 0)
+            .then(|| Utc::now() + chrono::Duration::days(due_within_days as i64));
+
+        let results = match crate::gnome::evolution::cache::tasks(sources) {
+            Some(results) => results,
+            None => {
+                let mut query = CalendarQuery::All;
+                if !include_completed {
+                    query = query.and(CalendarQuery::Completed.not());
+                }
+                if let Some(limit) = due_limit {
+                    // Keep tasks without a due date, and those due before the limit
+                    let far_future = limit + chrono::Duration::days(100 * 365);
+                    query = query.and(CalendarQuery::DueIn(limit, far_future).not());
                 }
+                Self::search(&query, sources).await?
             }
-        }
+        };
 
-        Ok(all_tasks)
+        // Queries can't test the status, and `is-completed?` only looks at
+        // the completion date, so the status is checked here. Cached tasks
+        // are only filtered here.
+        Ok(results.map_items(|tasks| {
+            tasks
+                .into_iter()
+                .filter(|task| task.is_listed(include_completed, include_cancelled, due_limit))
+                .collect()
+        }))
+    }
+
+    /// Fetch the tasks matching `query` from the task lists `sources`
+    /// allows. The task lists evaluate the query themselves and are read
+    /// concurrently, so one that fails only ends up in the errors.
+    pub async fn search(
+        query: &CalendarQuery,
+        sources: &SourceFilter,
+    ) -> Result<SourceResults<Task>> {
+        let connection = zbus::Connection::session().await?;
+        let sources = crate::gnome::evolution::select_sources(&connection, sources, |kind| {
+            matches!(kind, SourceType::TaskList { .. })
+        })
+        .await?;
+        let results = crate::gnome::evolution::fetch_sources(
+            sources,
+            crate::gnome::evolution::SOURCE_TIMEOUT,
+            |info| {
+                let connection = connection.clone();
+                let query = query.clone();
+                async move {
+                    let (task_list_path, bus_name) =
+                        crate::gnome::evolution::open_task_list_source(&connection, &info.uid)
+                            .await?;
+                    Self::fetch_from_source(&connection, &task_list_path, &bus_name, &query).await
+                }
+            },
+        )
+        .await;
+
+        Ok(results)
     }
 
     /// Private helper to fetch tasks from a specific task list source
@@ -76,12 +329,8 @@ impl Task {
         connection: &zbus::Connection,
         task_list_path: &str,
         bus_name: &str,
-        include_completed: bool,
-        include_cancelled: bool,
-        due_within_days: u32,
+        query: &CalendarQuery,
     ) -> Result<Vec<Task>> {
-        let mut tasks = Vec::new();
-
         let proxy = zbus::Proxy::new(
             connection,
             bus_name,
@@ -90,35 +339,15 @@ impl Task {
         )
         .await?;
 
-        let response = proxy.call_method("GetObjectList", &("#t",)).await?;
+        let response = proxy
+            .call_method("GetObjectList", &(query.to_string(),))
+            .await?;
         let ical_objects = response.body().deserialize::<Vec<String>>()?;
 
-        for ical_data in ical_objects {
-            if let Ok(task) = Task::from_str(&ical_data) {
-                // Apply filtering
-                if !include_completed && task.is_completed() {
-                    continue;
-                }
-                if !include_cancelled && task.is_cancelled() {
-                    continue;
-                }
-
-                // Filter by due date if configured
-                if due_within_days > 0 {
-                    if let Some(due_date) = task.due_date {
-                        let now = chrono::Utc::now();
-                        let due_limit = now + chrono::Duration::days(due_within_days as i64);
-                        if due_date > due_limit {
-                            continue;
-                        }
-                    }
-                }
-
-                tasks.push(task);
-            }
-        }
-
-        Ok(tasks)
+        Ok(ical_objects
+            .iter()
+            .filter_map(|ical_data| Task::from_str(ical_data).ok())
+            .collect())
     }
 }
 
@@ -141,6 +370,15 @@ impl FromStr for Task {
             })
             .ok_or_else(|| anyhow::anyhow!("No VTODO component found in iCalendar data"))?;
 
+        let tz_resolver = ical
+            .build_tz_resolver()
+            .with_default(calcard::common::timezone::Tz::UTC);
+        let date_time = |property| {
+            todo_component
+                .property(&property)
+                .and_then(|entry| ical::date_time_value(entry, &tz_resolver))
+        };
+
         let uid = todo_component
             .property(&calcard::icalendar::ICalendarProperty::Uid)
             .and_then(|p| p.values.first())
@@ -161,19 +399,9 @@ impl FromStr for Task {
             .filter(|s| !s.is_empty())
             .map(|s| s.to_string());
 
-        let due_date = todo_component
-            .property(&calcard::icalendar::ICalendarProperty::Due)
-            .and_then(|p| p.values.first())
-            .and_then(|v| v.as_partial_date_time())
-            .and_then(|d| d.to_date_time_with_tz(calcard::common::timezone::Tz::UTC))
-            .map(|dt| dt.with_timezone(&Utc));
+        let due_date = date_time(calcard::icalendar::ICalendarProperty::Due);
 
-        let completed_date = todo_component
-            .property(&calcard::icalendar::ICalendarProperty::Completed)
-            .and_then(|p| p.values.first())
-            .and_then(|v| v.as_partial_date_time())
-            .and_then(|d| d.to_date_time_with_tz(calcard::common::timezone::Tz::UTC))
-            .map(|dt| dt.with_timezone(&Utc));
+        let completed_date = date_time(calcard::icalendar::ICalendarProperty::Completed);
 
         let status = todo_component
             .property(&calcard::icalendar::ICalendarProperty::Status)
@@ -182,12 +410,7 @@ impl FromStr for Task {
             .unwrap_or("NEEDS-ACTION")
             .to_string();
 
-        let start_date = todo_component
-            .property(&calcard::icalendar::ICalendarProperty::Dtstart)
-            .and_then(|p| p.values.first())
-            .and_then(|v| v.as_partial_date_time())
-            .and_then(|d| d.to_date_time_with_tz(calcard::common::timezone::Tz::UTC))
-            .map(|dt| dt.with_timezone(&Utc));
+        let start_date = date_time(calcard::icalendar::ICalendarProperty::Dtstart);
 
         let priority = todo_component
             .property(&calcard::icalendar::ICalendarProperty::Priority)
@@ -209,19 +432,9 @@ impl FromStr for Task {
             .and_then(|v| v.as_integer())
             .map(|i| i as u32);
 
-        let created = todo_component
-            .property(&calcard::icalendar::ICalendarProperty::Created)
-            .and_then(|p| p.values.first())
-            .and_then(|v| v.as_partial_date_time())
-            .and_then(|d| d.to_date_time_with_tz(calcard::common::timezone::Tz::UTC))
-            .map(|dt| dt.with_timezone(&Utc));
+        let created = date_time(calcard::icalendar::ICalendarProperty::Created);
 
-        let last_modified = todo_component
-            .property(&calcard::icalendar::ICalendarProperty::LastModified)
-            .and_then(|p| p.values.first())
-            .and_then(|v| v.as_partial_date_time())
-            .and_then(|d| d.to_date_time_with_tz(calcard::common::timezone::Tz::UTC))
-            .map(|dt| dt.with_timezone(&Utc));
+        let last_modified = date_time(calcard::icalendar::ICalendarProperty::LastModified);
 
         let location = todo_component
             .property(&calcard::icalendar::ICalendarProperty::Location)
@@ -244,22 +457,61 @@ impl FromStr for Task {
             .filter(|s| !s.is_empty())
             .map(|s| s.to_string());
 
+        // RELATED-TO points to the parent unless RELTYPE says otherwise
+        let mut parent = None;
+        let mut children = Vec::new();
+        for entry in todo_component.properties(&calcard::icalendar::ICalendarProperty::RelatedTo) {
+            let Some(uid) = entry
+                .values
+                .first()
+                .and_then(|v| v.as_text())
+                .filter(|s| !s.is_empty())
+            else {
+                continue;
+            };
+            let reltype = entry.params.iter().find_map(|param| match param {
+                ICalendarParameter::Reltype(reltype) => Some(reltype),
+                _ => None,
+            });
+            match reltype {
+                None | Some(ICalendarRelationshipType::Parent) => {
+                    parent.get_or_insert_with(|| uid.to_string());
+                }
+                Some(ICalendarRelationshipType::Child) => children.push(uid.to_string()),
+                Some(_) => {}
+            }
+        }
+
+        let timezone = [
+            calcard::icalendar::ICalendarProperty::Due,
+            calcard::icalendar::ICalendarProperty::Dtstart,
+        ]
+        .iter()
+        .filter_map(|property| todo_component.property(property))
+        .find_map(|entry| entry.tz_id())
+        .map(ToOwned::to_owned);
+
         Ok(Task {
             summary,
             description,
-            due_date,
-            completed_date,
+            due_date: due_date.map(|(due_date, _)| due_date),
+            all_day: due_date.or(start_date).is_some_and(|(_, is_date)| is_date),
+            completed_date: completed_date.map(|(completed_date, _)| completed_date),
             status,
             uid: uid.to_string(),
-            start_date,
+            start_date: start_date.map(|(start_date, _)| start_date),
             priority,
             categories,
             percent_complete,
-            created,
-            last_modified,
+            created: created.map(|(created, _)| created),
+            last_modified: last_modified.map(|(last_modified, _)| last_modified),
             location,
             url,
             class,
+            timezone,
+            parent,
+            children,
+            alarms: alarm::alarms(&ical, todo_component, &tz_resolver),
         })
     }
 }
@@ -390,9 +642,14 @@ END:VCALENDAR"#;
             location: None,
             url: None,
             class: None,
+            all_day: false,
+            timezone: None,
+            parent: None,
+            children: vec![],
+            alarms: vec![],
         };
 
-        let json = task.to_json();
+        let json = task.to_json(chrono_tz::UTC);
         assert!(json.is_object());
         assert_eq!(json["uid"], "test-123");
         assert_eq!(json["summary"], "Test Summary");
@@ -400,6 +657,31 @@ END:VCALENDAR"#;
         assert!(json["description"].is_null());
     }
 
+    #[test]
+    fn test_task_related_to() {
+        let ical_data = "BEGIN:VCALENDAR\r\nBEGIN:VTODO\r\nUID:pack\r\nSUMMARY:Pack\r\n\
+                         RELATED-TO:trip\r\nRELATED-TO;RELTYPE=CHILD:socks\r\n\
+                         RELATED-TO;RELTYPE=SIBLING:tickets\r\nEND:VTODO\r\nEND:VCALENDAR\r\n";
+        let mut task = Task::from_str(ical_data).unwrap();
+        assert_eq!(task.parent.as_deref(), Some("trip"));
+        assert_eq!(task.children, ["socks"]);
+
+        let ical = task.to_ical();
+        assert!(ical.contains("RELATED-TO:trip\r\n"));
+        assert!(ical.contains("RELATED-TO;RELTYPE=CHILD:socks\r\n"));
+
+        task.parent = Some("holidays".to_string());
+        let patched = task.patch_ical(ical_data).unwrap();
+        assert!(patched.contains("RELATED-TO:holidays\r\n"));
+        assert!(!patched.contains("RELATED-TO:trip"));
+
+        let parent = "BEGIN:VCALENDAR\r\nBEGIN:VTODO\r\nUID:trip\r\n\
+                      RELATED-TO;RELTYPE=PARENT:holidays\r\nEND:VTODO\r\nEND:VCALENDAR\r\n";
+        let task = Task::from_str(parent).unwrap();
+        assert_eq!(task.parent.as_deref(), Some("holidays"));
+        assert!(task.children.is_empty());
+    }
+
     #[test]
     fn test_task_with_various_date_formats() {
         let ical_data = r#"BEGIN:VCALENDAR
@@ -506,4 +788,70 @@ END:VCALENDAR"#;
         assert_eq!(task.url, Some("https://example.com/task".to_string()));
         assert_eq!(task.class, Some("PRIVATE".to_string()));
     }
+
+    #[test]
+    fn test_task_due_date_and_timezone() {
+        let ical_data = "BEGIN:VTODO\r\nUID:dated\r\nDUE;VALUE=DATE:20240901\r\n\
+                         SUMMARY:Renew passport\r\nEND:VTODO\r\n";
+        let task = Task::from_str(ical_data).unwrap();
+        assert!(task.all_day);
+        assert_eq!(
+            task.to_json(chrono_tz::Asia::Tokyo)["due_date"],
+            "2024-09-01"
+        );
+        assert!(task.to_ical().contains("DUE;VALUE=DATE:20240901\r\n"));
+
+        let ical_data = "BEGIN:VTODO\r\nUID:zoned\r\nDUE;TZID=Europe/Berlin:20240901T170000\r\n\
+                         END:VTODO\r\n";
+        let task = Task::from_str(ical_data).unwrap();
+        assert!(!task.all_day);
+        assert_eq!(task.timezone.as_deref(), Some("Europe/Berlin"));
+        assert_eq!(task.due_date, "2024-09-01T15:00:00Z".parse().ok());
+        assert!(task
+            .to_ical()
+            .contains("DUE;TZID=Europe/Berlin:20240901T170000\r\n"));
+    }
+
+    #[test]
+    fn test_task_complete_patch() {
+        let stored = "BEGIN:VTODO\r\nUID:todo-1\r\nSUMMARY:File taxes\r\n\
+                      DUE:20240415T170000Z\r\nSTATUS:IN-PROCESS\r\nPERCENT-COMPLETE:40\r\n\
+                      X-EVOLUTION-NOTE:keep\r\nEND:VTODO\r\n";
+        let mut task = Task::from_str(stored).unwrap();
+        task.complete("2024-04-10T12:00:00Z".parse().unwrap());
+        task.priority = Some(1);
+
+        let patched = task.patch_ical(stored).unwrap();
+        assert!(patched.contains("STATUS:COMPLETED\r\n"));
+        assert!(patched.contains("COMPLETED:20240410T120000Z\r\n"));
+        assert!(patched.contains("PERCENT-COMPLETE:100\r\n"));
+        assert!(patched.contains("PRIORITY:1\r\n"));
+        assert!(patched.contains("DUE:20240415T170000Z\r\n"));
+        assert!(patched.contains("X-EVOLUTION-NOTE:keep\r\n"));
+
+        let parsed = Task::from_str(&patched).unwrap();
+        assert!(parsed.is_completed());
+        assert_eq!(parsed.percent_complete, Some(100));
+        assert!(parsed.completed_date.is_some());
+        assert!(Task::from_str(stored).unwrap().patch_ical(stored).is_err());
+    }
+
+    #[test]
+    fn test_task_to_ical_round_trip() {
+        let task = Task {
+            uid: "todo-2".to_string(),
+            summary: Some("Buy milk, eggs".to_string()),
+            status: "NEEDS-ACTION".to_string(),
+            due_date: "2024-07-15T17:00:00Z".parse().ok(),
+            priority: Some(5),
+            ..Default::default()
+        };
+
+        let parsed = Task::from_str(&task.to_ical()).unwrap();
+        assert_eq!(parsed.uid, "todo-2");
+        assert_eq!(parsed.summary, task.summary);
+        assert_eq!(parsed.status, "NEEDS-ACTION");
+        assert_eq!(parsed.due_date, task.due_date);
+        assert_eq!(parsed.priority, Some(5));
+    }
 }
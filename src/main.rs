@@ -1,8 +1,11 @@
+mod audit;
 mod config;
 mod gnome;
 mod mcp;
+mod rate_limit;
 mod resources;
 mod tools;
+mod undo;
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
@@ -11,6 +14,11 @@ async fn main() -> anyhow::Result<()> {
         .with_max_level(tracing::Level::INFO)
         .init();
 
+    // Load the configuration up front so an invalid profile fails at startup.
+    if let Some(profile) = &config::CONFIG.active_profile {
+        tracing::info!("Using configuration profile: {}", profile);
+    }
+
     // Register as a host application, given that we use some portals.
     if let Err(err) =
         ashpd::register_host_app("com.belmoussaoui.gnome-mcp-server".try_into().unwrap()).await
@@ -18,5 +26,9 @@ async fn main() -> anyhow::Result<()> {
         tracing::warn!("Failed to register host app: {}", err);
     }
 
+    if config::CONFIG.evolution_cache.enabled {
+        gnome::evolution::cache::spawn();
+    }
+
     mcp::Server::run().await
 }
@@ -6,17 +6,29 @@ macro_rules! register_providers {
         pub fn list_resources() -> Vec<crate::mcp::Resource> {
             let mut resources = Vec::new();
             $(
-                if crate::config::CONFIG.is_resource_enabled::<$resource>() {
+                if !<$resource>::is_template() && crate::config::CONFIG.is_resource_enabled::<$resource>() {
                     resources.push(<$resource>::resource());
                 }
             )*
             resources
         }
 
+        pub fn list_resource_templates() -> Vec<crate::mcp::ResourceTemplate> {
+            let mut templates = Vec::new();
+            $(
+                if <$resource>::is_template() && crate::config::CONFIG.is_resource_enabled::<$resource>() {
+                    templates.push(<$resource>::resource_template());
+                }
+            )*
+            templates
+        }
+
         pub async fn resource_for_uri(uri: &str) -> anyhow::Result<crate::mcp::ResourceContent> {
             $(
-                if <$resource>::URI == uri && crate::config::CONFIG.is_resource_enabled::<$resource>() {
-                    return <$resource>::default().get_content().await;
+                if crate::config::CONFIG.is_resource_enabled::<$resource>() {
+                    if let Some(resource) = <$resource>::from_uri(uri) {
+                        return resource.get_content().await;
+                    }
                 }
             )*
             anyhow::bail!("Unsupported URI {uri}")
@@ -35,7 +47,13 @@ macro_rules! register_providers {
         pub async fn execute_tool(name: &str, arguments: &serde_json::Value) -> anyhow::Result<serde_json::Value> {
             $(
                 if <$tool>::NAME == name && crate::config::CONFIG.is_tool_enabled::<$tool>() {
-                    return <$tool>::default().execute(arguments).await;
+                    let started = std::time::Instant::now();
+                    let result = match crate::rate_limit::check::<$tool>() {
+                        Ok(()) => <$tool>::default().execute(arguments).await,
+                        Err(throttled) => Ok(throttled.to_json(<$tool>::NAME)),
+                    };
+                    crate::audit::record::<$tool>(arguments, &result, started.elapsed());
+                    return result;
                 }
             )*
             Err(anyhow::anyhow!("Tool not found: {}", name))
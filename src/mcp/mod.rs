@@ -4,7 +4,8 @@ mod server;
 mod types;
 
 pub use server::Server;
+pub(crate) use types::uri_params;
 pub use types::{
-    Request, Resource, ResourceContent, ResourceProvider, Response, ToolDefinition, ToolParams,
-    ToolProvider,
+    Request, Resource, ResourceBody, ResourceContent, ResourceProvider, ResourceTemplate, Response,
+    ToolDefinition, ToolParams, ToolProvider,
 };
@@ -229,6 +229,13 @@ macro_rules! tool_params {
             .ok_or_else(|| anyhow::anyhow!("Missing required parameter: {}", $name))
     };
 
+    (@extract_required i64, $args:expr, $name:expr) => {
+        $args
+            .get($name)
+            .and_then(|v| v.as_i64())
+            .ok_or_else(|| anyhow::anyhow!("Missing required parameter: {}", $name))
+    };
+
     (@extract_optional string, $args:expr, $name:expr, $default:expr) => {
         $args
             .get($name)
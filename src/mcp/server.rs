@@ -1,8 +1,11 @@
 use anyhow::Result;
+use gio::glib;
 use serde_json::json;
 use tokio::io::{self, AsyncBufReadExt, AsyncWriteExt, BufReader};
 
-use crate::mcp::{macros::register_providers, Request, ResourceProvider, Response, ToolProvider};
+use crate::mcp::{
+    macros::register_providers, Request, ResourceBody, ResourceProvider, Response, ToolProvider,
+};
 
 register_providers! {
     resources: [
@@ -10,8 +13,16 @@ register_providers! {
         crate::resources::applications::Applications,
         crate::resources::calendar::Calendar,
         crate::resources::tasks::Tasks,
+        crate::resources::memos::Memos,
+        crate::resources::reminders::Reminders,
+        crate::resources::agenda::Agenda,
         crate::resources::contacts::Contacts,
+        crate::resources::contacts::ContactPhoto,
+        crate::resources::contacts::UpcomingDates,
+        crate::resources::sources::Sources,
         crate::resources::audio::Audio,
+        crate::resources::audit::Audit,
+        crate::resources::undo::UndoHistory,
     ],
     tools: [
         crate::tools::notifications::Notifications,
@@ -24,6 +35,24 @@ register_providers! {
         crate::tools::screenshot::Screenshot,
         crate::tools::window_management::WindowManagement,
         crate::tools::keyring::Keyring,
+        crate::tools::calendar::CreateCalendarEvent,
+        crate::tools::calendar::UpdateCalendarEvent,
+        crate::tools::calendar::DeleteCalendarEvent,
+        crate::tools::calendar::RespondToInvitation,
+        crate::tools::tasks::TaskManagement,
+        crate::tools::memos::MemoManagement,
+        crate::tools::sources::SourceManagement,
+        crate::tools::contacts::CreateContact,
+        crate::tools::contacts::UpdateContact,
+        crate::tools::contacts::DeleteContact,
+        crate::tools::free_busy::FindFreeSlots,
+        crate::tools::search::SearchContacts,
+        crate::tools::search::SearchEvents,
+        crate::tools::search::SearchTasks,
+        crate::tools::import_export::ImportCalendarFile,
+        crate::tools::import_export::ImportContactsFile,
+        crate::tools::import_export::ExportToFile,
+        crate::tools::undo::Undo,
     ]
 }
 
@@ -59,6 +88,7 @@ impl Server {
         let result = match request.method.as_str() {
             "initialize" => Self::handle_initialize().await,
             "resources/list" => Self::handle_list_resources().await,
+            "resources/templates/list" => Self::handle_list_resource_templates().await,
             "resources/read" => Self::handle_read_resource(&request).await,
             "tools/list" => Self::handle_list_tools().await,
             "tools/call" => Self::handle_call_tool(&request).await,
@@ -100,6 +130,13 @@ impl Server {
         })
     }
 
+    async fn handle_list_resource_templates() -> serde_json::Value {
+        let templates = list_resource_templates();
+        json!({
+            "resourceTemplates": templates
+        })
+    }
+
     async fn handle_call_tool(request: &Request) -> serde_json::Value {
         if let Some(params) = &request.params {
             if let (Some(name), Some(arguments)) = (
@@ -129,13 +166,19 @@ impl Server {
         if let Some(params) = &request.params {
             if let Some(uri) = params.get("uri").and_then(|u| u.as_str()) {
                 match resource_for_uri(uri).await {
-                    Ok(content) => json!({
-                        "contents": [{
+                    Ok(content) => {
+                        let mut contents = json!({
                             "uri": content.uri,
-                            "mimeType": content.mime_type,
-                            "text": content.text
-                        }]
-                    }),
+                            "mimeType": content.mime_type
+                        });
+                        match content.body {
+                            ResourceBody::Text(text) => contents["text"] = text.into(),
+                            ResourceBody::Blob(data) => {
+                                contents["blob"] = glib::base64_encode(&data).as_str().into()
+                            }
+                        }
+                        json!({ "contents": [contents] })
+                    }
                     Err(e) => {
                         json!({
                             "contents": [{
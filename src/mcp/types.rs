@@ -1,4 +1,5 @@
 use anyhow::Result;
+use gio::glib;
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -24,11 +25,29 @@ pub struct Resource {
     pub mime_type: &'static str,
 }
 
+/// A family of resources whose URI holds `{param}` placeholders
 #[derive(Debug, Serialize, Deserialize)]
-pub struct ResourceContent {
-    pub uri: &'static str,
+pub struct ResourceTemplate {
+    #[serde(rename = "uriTemplate")]
+    pub uri_template: &'static str,
+    pub name: &'static str,
+    pub description: &'static str,
+    #[serde(rename = "mimeType")]
     pub mime_type: &'static str,
-    pub text: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ResourceContent {
+    pub uri: String,
+    pub mime_type: String,
+    pub body: ResourceBody,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub enum ResourceBody {
+    Text(String),
+    /// Binary data, sent base64-encoded
+    Blob(Vec<u8>),
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -54,9 +73,65 @@ pub trait ResourceProvider {
         }
     }
 
+    /// Whether `URI` holds `{param}` placeholders, making the resource a
+    /// template rather than a single resource
+    fn is_template() -> bool {
+        Self::URI.contains('{')
+    }
+
+    fn resource_template() -> ResourceTemplate {
+        ResourceTemplate {
+            uri_template: Self::URI,
+            name: Self::NAME,
+            description: Self::DESCRIPTION,
+            mime_type: Self::MIME_TYPE,
+        }
+    }
+
+    /// The provider answering for `uri`. Templates override this to keep
+    /// the values of their placeholders, see [`uri_params`].
+    fn from_uri(uri: &str) -> Option<Self>
+    where
+        Self: Default,
+    {
+        (uri == Self::URI).then(Self::default)
+    }
+
     async fn get_content(&self) -> Result<ResourceContent>;
 }
 
+/// Values of the `{param}` placeholders of `template` in `uri`,
+/// percent-decoded, or `None` when `uri` doesn't match. A value spans a
+/// single path segment.
+pub fn uri_params(template: &str, uri: &str) -> Option<Vec<String>> {
+    let mut parts = template.split('{');
+    let mut rest = uri.strip_prefix(parts.next()?)?;
+    let mut values = Vec::new();
+    for part in parts {
+        let (_name, literal) = part.split_once('}')?;
+        let end = match literal {
+            "" => rest.len(),
+            literal => rest.find(literal)?,
+        };
+        let value = &rest[..end];
+        if value.is_empty() || value.contains('/') {
+            return None;
+        }
+        values.push(glib::uri_unescape_string(value, None::<&str>)?.to_string());
+        rest = &rest[end + literal.len()..];
+    }
+    rest.is_empty().then_some(values)
+}
+
+/// Dry-run is enabled globally in the config or requested for a single call
+fn is_dry_run(arguments: &serde_json::Value) -> bool {
+    crate::config::CONFIG.dry_run
+        || arguments
+            .get("dry_run")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false)
+}
+
 pub trait ToolParams {
     fn input_schema() -> serde_json::Value;
     fn extract_params(arguments: &serde_json::Value) -> anyhow::Result<Self>
@@ -69,6 +144,10 @@ pub trait ToolProvider {
 
     const NAME: &'static str;
     const DESCRIPTION: &'static str;
+    /// Parameters holding secrets, redacted before arguments are persisted
+    const SECRET_PARAMS: &'static [&'static str] = &[];
+    /// Result fields holding secrets, redacted before results are persisted
+    const SECRET_RESULT_FIELDS: &'static [&'static str] = &[];
 
     fn get_tool_definition() -> ToolDefinition {
         ToolDefinition {
@@ -79,16 +158,33 @@ pub trait ToolProvider {
     }
 
     fn input_schema() -> serde_json::Value {
-        Self::Params::input_schema()
+        let mut schema = Self::Params::input_schema();
+        schema["properties"]["dry_run"] = serde_json::json!({
+            "type": "boolean",
+            "description": "Validate the call and describe its effect without changing anything"
+        });
+        schema
     }
 
     async fn execute(&self, arguments: &serde_json::Value) -> Result<serde_json::Value> {
         let params = Self::Params::extract_params(arguments)?;
+        if is_dry_run(arguments) {
+            let effect = self.dry_run_with_params(params).await?;
+            return Ok(serde_json::json!({
+                "success": true,
+                "dry_run": true,
+                "result": effect
+            }));
+        }
         self.execute_with_params(params).await
     }
 
     async fn execute_with_params(&self, params: Self::Params) -> Result<serde_json::Value>;
 
+    /// Validate the parameters and resolve the targets of the call,
+    /// describing its effect without touching the desktop
+    async fn dry_run_with_params(&self, params: Self::Params) -> Result<serde_json::Value>;
+
     fn success_response(result: impl Into<serde_json::Value>) -> serde_json::Value {
         serde_json::json!({
             "success": true,
@@ -136,3 +232,32 @@ pub trait ToolProvider {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_uri_params() {
+        let template = "gnome://contacts/{uid}/photo";
+        assert_eq!(
+            uri_params(template, "gnome://contacts/pas-id-1/photo"),
+            Some(vec!["pas-id-1".to_string()])
+        );
+        assert_eq!(
+            uri_params(template, "gnome://contacts/anna%40example.com/photo"),
+            Some(vec!["anna@example.com".to_string()])
+        );
+        assert_eq!(uri_params(template, "gnome://contacts//photo"), None);
+        assert_eq!(uri_params(template, "gnome://contacts/a/b/photo"), None);
+        assert_eq!(uri_params(template, "gnome://contacts/a/photo/x"), None);
+        assert_eq!(
+            uri_params("gnome://agenda/{date}", "gnome://agenda/2024-07-10"),
+            Some(vec!["2024-07-10".to_string()])
+        );
+        assert_eq!(
+            uri_params("gnome://tasks/list", "gnome://tasks/list"),
+            Some(vec![])
+        );
+    }
+}
@@ -0,0 +1,195 @@
+use std::{
+    collections::HashMap,
+    sync::{LazyLock, Mutex},
+    time::{Duration, Instant},
+};
+
+use serde_json::json;
+
+use crate::{config::RateLimit, mcp::ToolProvider};
+
+static LIMITER: LazyLock<Mutex<RateLimiter>> = LazyLock::new(Default::default);
+
+/// Why a call was refused
+#[derive(Debug, Clone, PartialEq)]
+pub enum Throttled {
+    /// The token bucket is empty; a token frees up after `retry_after`
+    RateLimited { retry_after: Duration },
+    /// The per-session quota is used up and won't refill
+    QuotaExhausted { quota: u32 },
+}
+
+impl Throttled {
+    /// Structured tool response telling the model when it may retry
+    pub fn to_json(&self, tool: &str) -> serde_json::Value {
+        match self {
+            Self::RateLimited { retry_after } => {
+                let seconds = (retry_after.as_secs_f64() * 10.0).ceil() / 10.0;
+                let retry_at = chrono::Utc::now()
+                    + chrono::Duration::from_std(*retry_after).unwrap_or_default();
+                json!({
+                    "success": false,
+                    "error": format!("Rate limit exceeded for {tool}, retry in {seconds} seconds"),
+                    "throttled": {
+                        "reason": "rate_limited",
+                        "retry_after_seconds": seconds,
+                        "retry_at": retry_at.to_rfc3339()
+                    }
+                })
+            }
+            Self::QuotaExhausted { quota } => json!({
+                "success": false,
+                "error": format!(
+                    "Session quota of {quota} calls exhausted for {tool}, it cannot be called again in this session"
+                ),
+                "throttled": {
+                    "reason": "session_quota_exhausted",
+                    "quota": quota,
+                    "retry_after_seconds": null
+                }
+            }),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+#[derive(Debug, Default)]
+struct ToolState {
+    bucket: Option<Bucket>,
+    calls: u32,
+}
+
+/// Token buckets and session call counters, keyed by tool name
+#[derive(Debug, Default)]
+pub struct RateLimiter {
+    tools: HashMap<String, ToolState>,
+}
+
+impl RateLimiter {
+    /// Admit a call at `now`, consuming a token and a unit of quota
+    pub fn check(&mut self, tool: &str, limit: &RateLimit, now: Instant) -> Result<(), Throttled> {
+        let state = self.tools.entry(tool.to_owned()).or_default();
+
+        if let Some(quota) = limit.session_quota {
+            if state.calls >= quota {
+                return Err(Throttled::QuotaExhausted { quota });
+            }
+        }
+
+        if let Some(per_minute) = limit.per_minute.filter(|rate| *rate > 0.0) {
+            let capacity = limit.burst.unwrap_or(1).max(1) as f64;
+            let rate = per_minute / 60.0;
+            let bucket = state.bucket.get_or_insert(Bucket {
+                tokens: capacity,
+                refilled_at: now,
+            });
+
+            let elapsed = now.saturating_duration_since(bucket.refilled_at);
+            bucket.tokens = (bucket.tokens + elapsed.as_secs_f64() * rate).min(capacity);
+            bucket.refilled_at = now;
+
+            if bucket.tokens < 1.0 {
+                return Err(Throttled::RateLimited {
+                    retry_after: Duration::from_secs_f64((1.0 - bucket.tokens) / rate),
+                });
+            }
+            bucket.tokens -= 1.0;
+        }
+
+        state.calls += 1;
+        Ok(())
+    }
+}
+
+/// Check the configured limits for a tool before dispatching a call to it
+pub fn check<T: ToolProvider>() -> Result<(), Throttled> {
+    let limit = crate::config::CONFIG.rate_limits.limit_for(T::NAME);
+    if limit.is_unlimited() {
+        return Ok(());
+    }
+
+    LIMITER
+        .lock()
+        .unwrap_or_else(|err| err.into_inner())
+        .check(T::NAME, &limit, Instant::now())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_token_bucket_refills_over_time() {
+        let mut limiter = RateLimiter::default();
+        let limit = RateLimit {
+            per_minute: Some(6.0),
+            burst: Some(2),
+            session_quota: None,
+        };
+        let start = Instant::now();
+
+        assert!(limiter.check("set_volume", &limit, start).is_ok());
+        assert!(limiter.check("set_volume", &limit, start).is_ok());
+
+        // One token every 10 seconds
+        let Err(Throttled::RateLimited { retry_after }) =
+            limiter.check("set_volume", &limit, start)
+        else {
+            panic!("expected the third call to be rate limited");
+        };
+        assert!((retry_after.as_secs_f64() - 10.0).abs() < 0.01);
+
+        let later = start + Duration::from_secs(4);
+        let Err(Throttled::RateLimited { retry_after }) =
+            limiter.check("set_volume", &limit, later)
+        else {
+            panic!("expected the call to still be rate limited");
+        };
+        assert!((retry_after.as_secs_f64() - 6.0).abs() < 0.01);
+
+        assert!(limiter
+            .check("set_volume", &limit, start + Duration::from_secs(10))
+            .is_ok());
+
+        // Buckets are tracked per tool
+        assert!(limiter.check("send_notification", &limit, start).is_ok());
+    }
+
+    #[test]
+    fn test_session_quota() {
+        let mut limiter = RateLimiter::default();
+        let limit = RateLimit {
+            per_minute: None,
+            burst: None,
+            session_quota: Some(2),
+        };
+        let now = Instant::now();
+
+        assert!(limiter.check("send_notification", &limit, now).is_ok());
+        assert!(limiter.check("send_notification", &limit, now).is_ok());
+        assert_eq!(
+            limiter.check("send_notification", &limit, now),
+            Err(Throttled::QuotaExhausted { quota: 2 })
+        );
+    }
+
+    #[test]
+    fn test_throttled_response() {
+        let json = Throttled::RateLimited {
+            retry_after: Duration::from_millis(2_340),
+        }
+        .to_json("set_volume");
+        assert_eq!(json["success"], false);
+        assert_eq!(json["throttled"]["reason"], "rate_limited");
+        assert_eq!(json["throttled"]["retry_after_seconds"], 2.4);
+
+        let json = Throttled::QuotaExhausted { quota: 5 }.to_json("set_volume");
+        assert_eq!(json["throttled"]["reason"], "session_quota_exhausted");
+        assert!(json["throttled"]["retry_after_seconds"].is_null());
+    }
+}
@@ -0,0 +1,379 @@
+use anyhow::Result;
+use chrono::{DateTime, Datelike, Days, NaiveDate, Utc};
+use serde_json::json;
+
+use crate::{
+    gnome::evolution::{local_timezone, present_time, Event, SourceResults, Task},
+    mcp::{uri_params, ResourceBody, ResourceContent, ResourceProvider},
+    tools::free_busy::local_midnight,
+};
+
+/// Events and tasks of a day or a week, merged
+#[derive(Default)]
+pub struct Agenda {
+    date: String,
+}
+
+impl ResourceProvider for Agenda {
+    const URI: &'static str = "gnome://agenda/{date}";
+    const NAME: &'static str = "Agenda";
+    const DESCRIPTION: &'static str = "Events, tasks due, overdue tasks and conflicts of a day \
+        (2024-07-15, today, tomorrow) or a week (2024-W29, week, next-week) in local time";
+
+    fn from_uri(uri: &str) -> Option<Self> {
+        let date = uri_params(Self::URI, uri)?.pop()?;
+        Some(Agenda { date })
+    }
+
+    async fn get_content(&self) -> Result<ResourceContent> {
+        let local = local_timezone().await;
+        let now = Utc::now();
+        let today = now.with_timezone(&local).date_naive();
+        let (first_day, last_day) = parse_range(&self.date, today)?;
+        let start = local_midnight(first_day, local);
+        let end = local_midnight(last_day + Days::new(1), local);
+
+        let calendar = crate::config::CONFIG.get_calendar_config();
+        let SourceResults {
+            items: mut events,
+            errors: mut source_errors,
+        } = Event::all(start, end, &calendar.sources).await?;
+        let contacts = crate::config::CONFIG.get_contacts_config();
+        Event::name_attendees(&mut events, &contacts.sources).await;
+
+        let task_lists = crate::config::CONFIG.get_tasks_config();
+        let SourceResults {
+            items: tasks,
+            errors,
+        } = Task::all(false, false, 0, &task_lists.sources).await?;
+        source_errors.extend(errors);
+
+        let mut agenda_json = agenda(&events, &tasks, first_day, last_day, today, local);
+        agenda_json["timezone"] = local.name().into();
+        agenda_json["source_errors"] = serde_json::to_value(source_errors)?;
+
+        Ok(ResourceContent {
+            uri: format!("gnome://agenda/{}", self.date),
+            mime_type: Self::MIME_TYPE.to_owned(),
+            body: ResourceBody::Text(agenda_json.to_string()),
+        })
+    }
+}
+
+/// First and last day of the agenda: a date, `today`, `tomorrow`, an ISO
+/// week (`2024-W29`), `week` for the current week or `next-week`
+fn parse_range(value: &str, today: NaiveDate) -> Result<(NaiveDate, NaiveDate)> {
+    let week = |day: NaiveDate| {
+        let monday = day.week(chrono::Weekday::Mon).first_day();
+        (monday, monday + Days::new(6))
+    };
+    let range = match value {
+        "today" => (today, today),
+        "tomorrow" => (today + Days::new(1), today + Days::new(1)),
+        "week" => week(today),
+        "next-week" => week(today + Days::new(7)),
+        value => match value.split_once("-W") {
+            Some((year, number)) => year
+                .parse()
+                .ok()
+                .zip(number.parse().ok())
+                .and_then(|(year, number)| {
+                    NaiveDate::from_isoywd_opt(year, number, chrono::Weekday::Mon)
+                })
+                .map(week)
+                .ok_or_else(|| anyhow::anyhow!("Invalid week '{}', expected 2024-W29", value))?,
+            None => {
+                let day = NaiveDate::parse_from_str(value, "%Y-%m-%d").map_err(|_| {
+                    anyhow::anyhow!(
+                        "Invalid agenda date '{}', expected YYYY-MM-DD, YYYY-Www, today, \
+                         tomorrow, week or next-week",
+                        value
+                    )
+                })?;
+                (day, day)
+            }
+        },
+    };
+    Ok(range)
+}
+
+/// Days an event covers in local time. All-day events end on the day
+/// before their exclusive end date.
+fn event_days(event: &Event, local: chrono_tz::Tz) -> Option<(NaiveDate, NaiveDate)> {
+    let start = event.start_time?;
+    if event.all_day {
+        let first = start.date_naive();
+        let end = event.end_time.map_or(first, |end| end.date_naive());
+        return Some((first, end.pred_opt().unwrap_or(first).max(first)));
+    }
+    let end = event.end_time.unwrap_or(start).max(start);
+    let first = start.with_timezone(&local).date_naive();
+    // An event ending at midnight doesn't cover the next day
+    let last = match end > start {
+        true => (end - chrono::Duration::seconds(1))
+            .with_timezone(&local)
+            .date_naive(),
+        false => first,
+    };
+    Some((first, last))
+}
+
+/// Local date a task is due on
+fn due_day(task: &Task, local: chrono_tz::Tz) -> Option<NaiveDate> {
+    let due = task.due_date?;
+    Some(match task.all_day {
+        true => due.date_naive(),
+        false => due.with_timezone(&local).date_naive(),
+    })
+}
+
+fn is_opaque(event: &Event) -> bool {
+    !event
+        .transparency
+        .as_deref()
+        .is_some_and(|transparency| transparency.eq_ignore_ascii_case("TRANSPARENT"))
+        && !event
+            .status
+            .as_deref()
+            .is_some_and(|status| status.eq_ignore_ascii_case("CANCELLED"))
+}
+
+/// Pairs of timed, opaque events overlapping each other, with the period
+/// they share
+fn conflicts<'a>(
+    events: &[&'a Event],
+) -> Vec<(&'a Event, &'a Event, DateTime<Utc>, DateTime<Utc>)> {
+    let mut busy = events
+        .iter()
+        .filter(|event| !event.all_day && is_opaque(event))
+        .filter_map(|event| Some((event.start_time?, event.end_time?, *event)))
+        .filter(|(start, end, _)| end > start)
+        .collect::<Vec<_>>();
+    busy.sort_by_key(|(start, end, _)| (*start, *end));
+
+    let mut conflicts = Vec::new();
+    for (index, (_, end, event)) in busy.iter().enumerate() {
+        for (other_start, other_end, other) in &busy[index + 1..] {
+            if other_start >= end {
+                break;
+            }
+            conflicts.push((*event, *other, *other_start, (*end).min(*other_end)));
+        }
+    }
+    conflicts
+}
+
+/// The agenda from `first_day` to `last_day`: for each day its all-day
+/// items, its timed occurrences in order and the open tasks due, then the
+/// tasks overdue on `today` that aren't due in the range, and the
+/// conflicting events
+fn agenda(
+    events: &[Event],
+    tasks: &[Task],
+    first_day: NaiveDate,
+    last_day: NaiveDate,
+    today: NaiveDate,
+    local: chrono_tz::Tz,
+) -> serde_json::Value {
+    let mut events = events
+        .iter()
+        .filter_map(|event| Some((event_days(event, local)?, event)))
+        .filter(|((first, last), _)| *first <= last_day && *last >= first_day)
+        .collect::<Vec<_>>();
+    events.sort_by(|(_, a), (_, b)| {
+        (a.start_time, a.end_time, &a.summary).cmp(&(b.start_time, b.end_time, &b.summary))
+    });
+    let mut tasks = tasks
+        .iter()
+        .filter(|task| !task.is_completed() && !task.is_cancelled())
+        .filter_map(|task| Some((due_day(task, local)?, task)))
+        .collect::<Vec<_>>();
+    tasks.sort_by_key(|(day, task)| (*day, task.due_date));
+
+    let days = first_day
+        .iter_days()
+        .take_while(|day| *day <= last_day)
+        .map(|day| {
+            let on_day = events
+                .iter()
+                .filter(|((first, last), _)| (*first..=*last).contains(&day))
+                .map(|(_, event)| *event);
+            json!({
+                "date": day.to_string(),
+                "weekday": day.weekday().to_string(),
+                "all_day": on_day
+                    .clone()
+                    .filter(|event| event.all_day)
+                    .map(|event| event.to_json(local))
+                    .collect::<Vec<_>>(),
+                "events": on_day
+                    .filter(|event| !event.all_day)
+                    .map(|event| event.to_json(local))
+                    .collect::<Vec<_>>(),
+                "tasks_due": tasks
+                    .iter()
+                    .filter(|(due, _)| *due == day)
+                    .map(|(_, task)| task.to_json(local))
+                    .collect::<Vec<_>>()
+            })
+        })
+        .collect::<Vec<_>>();
+
+    let overdue = tasks
+        .iter()
+        .filter(|(due, _)| *due < today.min(first_day))
+        .map(|(_, task)| task.to_json(local))
+        .collect::<Vec<_>>();
+
+    let summary = |event: &Event| {
+        json!({
+            "uid": event.uid,
+            "summary": event.summary,
+            "start_time": present_time(event.start_time, local, false),
+            "end_time": present_time(event.end_time, local, false),
+            "recurrence_id": present_time(event.recurrence_id, local, false)
+        })
+    };
+    let conflicts = conflicts(&events.iter().map(|(_, event)| *event).collect::<Vec<_>>())
+        .into_iter()
+        .map(|(event, other, start, end)| {
+            json!({
+                "start": present_time(Some(start), local, false),
+                "end": present_time(Some(end), local, false),
+                "events": [summary(event), summary(other)]
+            })
+        })
+        .collect::<Vec<_>>();
+
+    json!({
+        "start": first_day.to_string(),
+        "end": last_day.to_string(),
+        "days": days,
+        "overdue_tasks": overdue,
+        "conflicts": conflicts
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn day(value: &str) -> NaiveDate {
+        value.parse().unwrap()
+    }
+
+    fn time(value: &str) -> Option<DateTime<Utc>> {
+        value.parse().ok()
+    }
+
+    fn event(uid: &str, start: &str, end: &str) -> Event {
+        Event {
+            uid: uid.to_owned(),
+            summary: Some(uid.to_owned()),
+            start_time: time(start),
+            end_time: time(end),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_parse_range() {
+        let today = day("2024-07-17");
+        assert_eq!(
+            parse_range("2024-07-15", today).unwrap(),
+            (day("2024-07-15"), day("2024-07-15"))
+        );
+        assert_eq!(parse_range("today", today).unwrap(), (today, today));
+        assert_eq!(
+            parse_range("tomorrow", today).unwrap(),
+            (day("2024-07-18"), day("2024-07-18"))
+        );
+        assert_eq!(
+            parse_range("week", today).unwrap(),
+            (day("2024-07-15"), day("2024-07-21"))
+        );
+        assert_eq!(
+            parse_range("next-week", today).unwrap(),
+            (day("2024-07-22"), day("2024-07-28"))
+        );
+        assert_eq!(
+            parse_range("2025-W01", today).unwrap(),
+            (day("2024-12-30"), day("2025-01-05"))
+        );
+        assert!(parse_range("2024-W54", today).is_err());
+        assert!(parse_range("July 15", today).is_err());
+    }
+
+    #[test]
+    fn test_agenda() {
+        let local: chrono_tz::Tz = "Europe/Paris".parse().unwrap();
+        let mut holiday = event("holiday", "2024-07-14T00:00:00Z", "2024-07-16T00:00:00Z");
+        holiday.all_day = true;
+        let mut focus = event("focus", "2024-07-15T09:30:00Z", "2024-07-15T10:30:00Z");
+        focus.transparency = Some("TRANSPARENT".to_owned());
+        let events = [
+            event("standup", "2024-07-15T07:00:00Z", "2024-07-15T07:15:00Z"),
+            event("review", "2024-07-15T09:00:00Z", "2024-07-15T10:00:00Z"),
+            event("lunch", "2024-07-15T09:45:00Z", "2024-07-15T11:00:00Z"),
+            focus,
+            holiday,
+            // Ends at local midnight, so not on the 16th
+            event("late", "2024-07-15T20:00:00Z", "2024-07-15T22:00:00Z"),
+            event("next", "2024-07-16T07:00:00Z", "2024-07-16T08:00:00Z"),
+        ];
+        let task = |uid: &str, due: &str, status: &str| Task {
+            uid: uid.to_owned(),
+            due_date: time(due),
+            status: status.to_owned(),
+            ..Default::default()
+        };
+        let tasks = [
+            task("report", "2024-07-15T15:00:00Z", "NEEDS-ACTION"),
+            task("invoice", "2024-07-10T15:00:00Z", "IN-PROCESS"),
+            task("done", "2024-07-15T15:00:00Z", "COMPLETED"),
+            task("later", "2024-07-20T15:00:00Z", "NEEDS-ACTION"),
+            task("call", "2024-07-13T15:00:00Z", "NEEDS-ACTION"),
+        ];
+
+        let today = day("2024-07-15");
+        let agenda = agenda(&events, &tasks, today, today, today, local);
+        let uids = |value: &serde_json::Value| {
+            value
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|item| item["uid"].as_str().unwrap().to_owned())
+                .collect::<Vec<_>>()
+        };
+        let days = agenda["days"].as_array().unwrap();
+        assert_eq!(days.len(), 1);
+        assert_eq!(days[0]["weekday"], "Mon");
+        assert_eq!(uids(&days[0]["all_day"]), ["holiday"]);
+        assert_eq!(
+            uids(&days[0]["events"]),
+            ["standup", "review", "focus", "lunch", "late"]
+        );
+        assert_eq!(uids(&days[0]["tasks_due"]), ["report"]);
+        assert_eq!(uids(&agenda["overdue_tasks"]), ["invoice", "call"]);
+
+        // The transparent focus time doesn't conflict
+        let conflicts = agenda["conflicts"].as_array().unwrap();
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(uids(&conflicts[0]["events"]), ["review", "lunch"]);
+        assert_eq!(conflicts[0]["start"], "2024-07-15T11:45:00+02:00");
+        assert_eq!(conflicts[0]["end"], "2024-07-15T12:00:00+02:00");
+
+        // Looking ahead, a task due before the range but after today isn't
+        // overdue yet
+        let today = day("2024-07-12");
+        let (first, last) = parse_range("2024-W29", today).unwrap();
+        let agenda = super::agenda(&events, &tasks, first, last, today, local);
+        assert_eq!(uids(&agenda["overdue_tasks"]), ["invoice"]);
+        let days = agenda["days"].as_array().unwrap();
+        assert_eq!(days.len(), 7);
+        assert_eq!(uids(&days[0]["all_day"]), ["holiday"]);
+        assert!(days[1]["all_day"].as_array().unwrap().is_empty());
+        assert_eq!(uids(&days[1]["events"]), ["next"]);
+        assert_eq!(uids(&days[5]["tasks_due"]), ["later"]);
+    }
+}
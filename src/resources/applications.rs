@@ -2,7 +2,7 @@ use anyhow::Result;
 use gio::prelude::*;
 use serde_json::json;
 
-use crate::mcp::{ResourceContent, ResourceProvider};
+use crate::mcp::{ResourceBody, ResourceContent, ResourceProvider};
 
 #[derive(Default)]
 pub struct Applications;
@@ -56,9 +56,9 @@ impl ResourceProvider for Applications {
         });
 
         Ok(ResourceContent {
-            uri: Self::URI,
-            mime_type: Self::MIME_TYPE,
-            text: apps_json.to_string(),
+            uri: Self::URI.to_owned(),
+            mime_type: Self::MIME_TYPE.to_owned(),
+            body: ResourceBody::Text(apps_json.to_string()),
         })
     }
 }
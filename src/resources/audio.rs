@@ -3,7 +3,7 @@ use std::collections::HashMap;
 use anyhow::Result;
 use serde_json::json;
 
-use crate::mcp::{ResourceContent, ResourceProvider};
+use crate::mcp::{ResourceBody, ResourceContent, ResourceProvider};
 
 #[derive(Default)]
 pub struct Audio;
@@ -18,9 +18,9 @@ impl ResourceProvider for Audio {
         let audio_status = get_audio_status().await?;
 
         Ok(ResourceContent {
-            uri: Self::URI,
-            mime_type: Self::MIME_TYPE,
-            text: audio_status.to_string(),
+            uri: Self::URI.to_owned(),
+            mime_type: Self::MIME_TYPE.to_owned(),
+            body: ResourceBody::Text(audio_status.to_string()),
         })
     }
 }
@@ -47,30 +47,7 @@ async fn get_audio_status() -> Result<serde_json::Value> {
 }
 
 async fn get_volume_status() -> Result<serde_json::Value> {
-    let output = tokio::process::Command::new("wpctl")
-        .args(["get-volume", "@DEFAULT_AUDIO_SINK@"])
-        .output()
-        .await?;
-
-    if !output.status.success() {
-        return Err(anyhow::anyhow!("wpctl get-volume failed"));
-    }
-
-    let volume_output = String::from_utf8_lossy(&output.stdout);
-
-    // Parse output like "Volume: 0.65 [MUTED]"
-    let mut volume_percent = 0.0;
-    let mut is_muted = false;
-
-    if let Some(vol_str) = volume_output.split_whitespace().nth(1) {
-        if let Ok(vol_float) = vol_str.parse::<f64>() {
-            volume_percent = vol_float * 100.0;
-        }
-    }
-
-    if volume_output.contains("[MUTED]") {
-        is_muted = true;
-    }
+    let (volume_percent, is_muted) = crate::tools::audio::volume_state().await?;
 
     Ok(json!({
         "level": volume_percent.round(),
@@ -0,0 +1,36 @@
+use anyhow::Result;
+use serde_json::json;
+
+use crate::{
+    audit::AuditLog,
+    mcp::{ResourceBody, ResourceContent, ResourceProvider},
+};
+
+#[derive(Default)]
+pub struct Audit;
+
+impl ResourceProvider for Audit {
+    const URI: &'static str = "gnome://audit/recent";
+    const NAME: &'static str = "Audit Log";
+    const DESCRIPTION: &'static str =
+        "Most recent tool invocations recorded in the audit log, newest first";
+
+    async fn get_content(&self) -> Result<ResourceContent> {
+        let config = crate::config::CONFIG.get_audit_resource_config();
+        let log = AuditLog::from_config(&crate::config::CONFIG.audit);
+        let entries = log.recent(config.limit)?;
+
+        let audit_json = json!({
+            "entries": entries,
+            "count": entries.len(),
+            "path": log.path(),
+            "enabled": crate::config::CONFIG.audit.enabled
+        });
+
+        Ok(ResourceContent {
+            uri: Self::URI.to_owned(),
+            mime_type: Self::MIME_TYPE.to_owned(),
+            body: ResourceBody::Text(audit_json.to_string()),
+        })
+    }
+}
@@ -2,8 +2,8 @@ use anyhow::Result;
 use serde_json::json;
 
 use crate::{
-    gnome::evolution::Event,
-    mcp::{ResourceContent, ResourceProvider},
+    gnome::evolution::{local_timezone, Event, SourceResults},
+    mcp::{ResourceBody, ResourceContent, ResourceProvider},
 };
 
 #[derive(Default)]
@@ -20,17 +20,25 @@ impl ResourceProvider for Calendar {
         let start_time = now - chrono::Duration::days(config.days_behind as i64);
         let end_time = now + chrono::Duration::days(config.days_ahead as i64);
 
-        let events = Event::all(start_time, end_time).await?;
+        let SourceResults {
+            items: mut events,
+            errors,
+        } = Event::all(start_time, end_time, &config.sources).await?;
+        let contacts = crate::config::CONFIG.get_contacts_config();
+        Event::name_attendees(&mut events, &contacts.sources).await;
 
+        let local = local_timezone().await;
         let events_json = json!({
-            "events": events.iter().map(|e| e.to_json()).collect::<Vec<_>>(),
-            "count": events.len()
+            "events": events.iter().map(|e| e.to_json(local)).collect::<Vec<_>>(),
+            "timezone": local.name(),
+            "count": events.len(),
+            "source_errors": errors
         });
 
         Ok(ResourceContent {
-            uri: Self::URI,
-            mime_type: Self::MIME_TYPE,
-            text: events_json.to_string(),
+            uri: Self::URI.to_owned(),
+            mime_type: Self::MIME_TYPE.to_owned(),
+            body: ResourceBody::Text(events_json.to_string()),
         })
     }
 }
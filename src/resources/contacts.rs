@@ -1,9 +1,10 @@
 use anyhow::Result;
+use chrono::{NaiveDate, Utc};
 use serde_json::json;
 
 use crate::{
-    gnome::evolution::Contact,
-    mcp::{ResourceContent, ResourceProvider},
+    gnome::evolution::{local_timezone, photo_uri, Contact, ContactDate, SourceResults},
+    mcp::{uri_params, ResourceBody, ResourceContent, ResourceProvider},
 };
 
 #[derive(Default)]
@@ -16,17 +17,209 @@ impl ResourceProvider for Contacts {
 
     async fn get_content(&self) -> Result<ResourceContent> {
         let config = crate::config::CONFIG.get_contacts_config();
-        let contacts = Contact::all(config.email_only).await?;
+        let SourceResults {
+            items: contacts,
+            errors,
+        } = Contact::all(config.email_only, &config.sources).await?;
 
         let contacts_json = json!({
             "contacts": contacts.iter().map(|c| c.to_json()).collect::<Vec<_>>(),
-            "count": contacts.len()
+            "count": contacts.len(),
+            "source_errors": errors
         });
 
         Ok(ResourceContent {
-            uri: Self::URI,
-            mime_type: Self::MIME_TYPE,
-            text: contacts_json.to_string(),
+            uri: Self::URI.to_owned(),
+            mime_type: Self::MIME_TYPE.to_owned(),
+            body: ResourceBody::Text(contacts_json.to_string()),
         })
     }
 }
+
+/// Birthdays and anniversaries of the contacts coming up
+#[derive(Default)]
+pub struct UpcomingDates;
+
+impl ResourceProvider for UpcomingDates {
+    const URI: &'static str = "gnome://contacts/upcoming-dates";
+    const NAME: &'static str = "Upcoming Birthdays and Anniversaries";
+    const DESCRIPTION: &'static str =
+        "Birthdays and anniversaries of contacts from Evolution Data Server in the coming days";
+
+    async fn get_content(&self) -> Result<ResourceContent> {
+        let config = crate::config::CONFIG.get_contacts_config();
+        let SourceResults {
+            items: contacts,
+            errors,
+        } = Contact::all(false, &config.sources).await?;
+
+        let local = local_timezone().await;
+        let today = Utc::now().with_timezone(&local).date_naive();
+        let dates = upcoming_dates(&contacts, today, config.upcoming_days);
+
+        let dates_json = json!({
+            "dates": dates,
+            "count": dates.len(),
+            "today": today.to_string(),
+            "days_ahead": config.upcoming_days,
+            "source_errors": errors
+        });
+
+        Ok(ResourceContent {
+            uri: Self::URI.to_owned(),
+            mime_type: Self::MIME_TYPE.to_owned(),
+            body: ResourceBody::Text(dates_json.to_string()),
+        })
+    }
+}
+
+/// Birthdays and anniversaries falling from `today` to `days` days later,
+/// soonest first, with the age or number of years they mark
+fn upcoming_dates(contacts: &[Contact], today: NaiveDate, days: u32) -> Vec<serde_json::Value> {
+    let last = today + chrono::Days::new(u64::from(days));
+    let mut dates = contacts
+        .iter()
+        .flat_map(|contact| {
+            [
+                ("birthday", "age", contact.birthday),
+                ("anniversary", "years", contact.anniversary),
+            ]
+            .into_iter()
+            .filter_map(move |(kind, count, date): (_, _, Option<ContactDate>)| {
+                let date = date?;
+                let next = date.next_occurrence(today);
+                (next <= last).then(|| {
+                    let mut entry = json!({
+                        "date": next.to_string(),
+                        "days_until": (next - today).num_days(),
+                        "kind": kind,
+                        "original_date": date.to_string(),
+                        "contact": contact.full_name.as_deref().unwrap_or(&contact.uid),
+                        "uid": contact.uid
+                    });
+                    if let Some(years) = date.years_on(next) {
+                        entry[count] = years.into();
+                    }
+                    (next, entry)
+                })
+            })
+        })
+        .collect::<Vec<_>>();
+    dates.sort_by(|(a, a_entry), (b, b_entry)| {
+        (a, a_entry["contact"].as_str()).cmp(&(b, b_entry["contact"].as_str()))
+    });
+    dates.into_iter().map(|(_, entry)| entry).collect()
+}
+
+/// The PHOTO of a single contact, as an image
+#[derive(Default)]
+pub struct ContactPhoto {
+    uid: String,
+}
+
+impl ResourceProvider for ContactPhoto {
+    const URI: &'static str = "gnome://contacts/{uid}/photo";
+    const NAME: &'static str = "Contact Photo";
+    const DESCRIPTION: &'static str = "Photo of a contact from Evolution Data Server";
+    const MIME_TYPE: &'static str = "image/*";
+
+    fn from_uri(uri: &str) -> Option<Self> {
+        let uid = uri_params(Self::URI, uri)?.pop()?;
+        Some(ContactPhoto { uid })
+    }
+
+    async fn get_content(&self) -> Result<ResourceContent> {
+        let config = crate::config::CONFIG.get_contacts_config();
+        let connection = zbus::Connection::session().await?;
+        let (_source, _proxy, stored) =
+            Contact::find(&connection, &self.uid, &config.sources).await?;
+        let (mime_type, data) = Contact::photo_image(&stored)?;
+
+        Ok(ResourceContent {
+            uri: photo_uri(&self.uid),
+            mime_type,
+            body: ResourceBody::Blob(data),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_contact_photo_uri() {
+        assert!(ContactPhoto::is_template());
+        assert!(!Contacts::is_template());
+
+        let photo = ContactPhoto::from_uri("gnome://contacts/pas-id-5A3B%2F1/photo").unwrap();
+        assert_eq!(photo.uid, "pas-id-5A3B/1");
+        assert_eq!(
+            photo_uri(&photo.uid),
+            "gnome://contacts/pas-id-5A3B%2F1/photo"
+        );
+
+        assert!(ContactPhoto::from_uri("gnome://contacts/list").is_none());
+        assert!(ContactPhoto::from_uri("gnome://contacts//photo").is_none());
+        assert!(Contacts::from_uri("gnome://contacts/list").is_some());
+    }
+
+    #[test]
+    fn test_upcoming_dates() {
+        let contact = |name: &str, birthday: &str, anniversary: Option<&str>| Contact {
+            uid: name.to_lowercase(),
+            full_name: Some(name.to_owned()),
+            birthday: birthday.parse().ok(),
+            anniversary: anniversary.and_then(|date| date.parse().ok()),
+            ..Default::default()
+        };
+        let contacts = [
+            contact("Anna", "1990-03-01", Some("2015-02-25")),
+            contact("Bob", "--02-26", None),
+            contact("Carl", "2000-02-29", None),
+            contact("Dora", "1985-12-31", None),
+        ];
+        let today = NaiveDate::from_ymd_opt(2027, 2, 25).unwrap();
+
+        let dates = upcoming_dates(&contacts, today, 5);
+        let summary = dates
+            .iter()
+            .map(|d| {
+                (
+                    d["contact"].as_str().unwrap(),
+                    d["kind"].as_str().unwrap(),
+                    d["date"].as_str().unwrap(),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            summary,
+            [
+                ("Anna", "anniversary", "2027-02-25"),
+                ("Bob", "birthday", "2027-02-26"),
+                // Feb 29 falls on Feb 28 in common years
+                ("Carl", "birthday", "2027-02-28"),
+                ("Anna", "birthday", "2027-03-01"),
+            ]
+        );
+        assert_eq!(dates[0]["years"], 12);
+        assert_eq!(dates[0]["days_until"], 0);
+        assert_eq!(dates[0]["original_date"], "2015-02-25");
+        assert!(dates[1].get("age").is_none());
+        assert_eq!(dates[1]["original_date"], "--02-26");
+        assert_eq!(dates[2]["age"], 27);
+        assert_eq!(dates[3]["age"], 37);
+
+        // Dates already past this year come up next year
+        let today = NaiveDate::from_ymd_opt(2027, 12, 30).unwrap();
+        let dates = upcoming_dates(&contacts, today, 3);
+        assert_eq!(dates.len(), 1);
+        assert_eq!(dates[0]["date"], "2027-12-31");
+        assert_eq!(dates[0]["age"], 42);
+        let today = NaiveDate::from_ymd_opt(2028, 1, 1).unwrap();
+        assert!(upcoming_dates(&contacts, today, 30).is_empty());
+        let dates = upcoming_dates(&contacts, today, 60);
+        assert_eq!(dates[2]["date"], "2028-02-29");
+        assert_eq!(dates[2]["age"], 28);
+    }
+}
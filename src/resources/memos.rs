@@ -0,0 +1,38 @@
+use anyhow::Result;
+use serde_json::json;
+
+use crate::{
+    gnome::evolution::{local_timezone, Memo, SourceResults},
+    mcp::{ResourceBody, ResourceContent, ResourceProvider},
+};
+
+#[derive(Default)]
+pub struct Memos;
+
+impl ResourceProvider for Memos {
+    const URI: &'static str = "gnome://memos/list";
+    const NAME: &'static str = "Memo Lists";
+    const DESCRIPTION: &'static str = "Memos (notes) from Evolution Data Server memo lists";
+
+    async fn get_content(&self) -> Result<ResourceContent> {
+        let config = crate::config::CONFIG.get_memos_config();
+        let SourceResults {
+            items: memos,
+            errors,
+        } = Memo::all(&config.sources).await?;
+
+        let local = local_timezone().await;
+        let memos_json = json!({
+            "memos": memos.iter().map(|m| m.to_json(local)).collect::<Vec<_>>(),
+            "timezone": local.name(),
+            "count": memos.len(),
+            "source_errors": errors
+        });
+
+        Ok(ResourceContent {
+            uri: Self::URI.to_owned(),
+            mime_type: Self::MIME_TYPE.to_owned(),
+            body: ResourceBody::Text(memos_json.to_string()),
+        })
+    }
+}
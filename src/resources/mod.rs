@@ -1,6 +1,12 @@
+pub mod agenda;
 pub mod applications;
 pub mod audio;
+pub mod audit;
 pub mod calendar;
 pub mod contacts;
+pub mod memos;
+pub mod reminders;
+pub mod sources;
 pub mod system_info;
 pub mod tasks;
+pub mod undo;
@@ -0,0 +1,177 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde_json::json;
+
+use crate::{
+    gnome::evolution::{local_timezone, present_time, Alarm, Event, SourceResults, Task},
+    mcp::{ResourceBody, ResourceContent, ResourceProvider},
+};
+
+/// How far past the window events are read, so that alarms set long before
+/// the start still show up
+const MAX_LEAD_TIME: chrono::Duration = chrono::Duration::days(7);
+
+#[derive(Default)]
+pub struct Reminders;
+
+impl ResourceProvider for Reminders {
+    const URI: &'static str = "gnome://calendar/reminders";
+    const NAME: &'static str = "Reminders";
+    const DESCRIPTION: &'static str = "Upcoming event and task alarms from Evolution Data Server";
+
+    async fn get_content(&self) -> Result<ResourceContent> {
+        let config = crate::config::CONFIG.get_reminders_config();
+        let now = Utc::now();
+        let window_end = now + chrono::Duration::hours(config.hours_ahead as i64);
+
+        let SourceResults {
+            items: events,
+            errors: event_errors,
+        } = Event::all(
+            now,
+            window_end + MAX_LEAD_TIME,
+            &crate::config::CONFIG.get_calendar_config().sources,
+        )
+        .await?;
+        let SourceResults {
+            items: tasks,
+            errors: task_errors,
+        } = Task::all(
+            false,
+            false,
+            0,
+            &crate::config::CONFIG.get_tasks_config().sources,
+        )
+        .await?;
+
+        let local = local_timezone().await;
+        let reminders = upcoming(&events, &tasks, now, window_end)
+            .into_iter()
+            .map(|reminder| {
+                let mut json = json!({
+                    "time": present_time(Some(reminder.time), local, false),
+                    "minutes_until": (reminder.time - now).num_minutes(),
+                    "action": reminder.alarm.action,
+                    "description": reminder.alarm.description,
+                });
+                match reminder.item {
+                    Item::Event(event) => {
+                        json["event"] = event.to_json(local);
+                    }
+                    Item::Task(task) => {
+                        json["task"] = task.to_json(local);
+                    }
+                }
+                json
+            })
+            .collect::<Vec<_>>();
+
+        let reminders_json = json!({
+            "reminders": reminders,
+            "timezone": local.name(),
+            "count": reminders.len(),
+            "source_errors": event_errors.into_iter().chain(task_errors).collect::<Vec<_>>()
+        });
+
+        Ok(ResourceContent {
+            uri: Self::URI.to_owned(),
+            mime_type: Self::MIME_TYPE.to_owned(),
+            body: ResourceBody::Text(reminders_json.to_string()),
+        })
+    }
+}
+
+enum Item<'a> {
+    Event(&'a Event),
+    Task(&'a Task),
+}
+
+struct Reminder<'a> {
+    time: DateTime<Utc>,
+    alarm: &'a Alarm,
+    item: Item<'a>,
+}
+
+/// Alarms of the events and tasks going off between `start` and `end`,
+/// soonest first
+fn upcoming<'a>(
+    events: &'a [Event],
+    tasks: &'a [Task],
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> Vec<Reminder<'a>> {
+    let event_alarms = events.iter().flat_map(|event| {
+        event
+            .alarm_times()
+            .into_iter()
+            .map(move |(alarm, time)| Reminder {
+                time,
+                alarm,
+                item: Item::Event(event),
+            })
+    });
+    let task_alarms = tasks.iter().flat_map(|task| {
+        task.alarm_times()
+            .into_iter()
+            .map(move |(alarm, time)| Reminder {
+                time,
+                alarm,
+                item: Item::Task(task),
+            })
+    });
+
+    let mut reminders = event_alarms
+        .chain(task_alarms)
+        .filter(|reminder| reminder.time >= start && reminder.time < end)
+        .collect::<Vec<_>>();
+    reminders.sort_by_key(|reminder| reminder.time);
+    reminders
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    #[test]
+    fn test_upcoming_reminders() {
+        let now = Utc.with_ymd_and_hms(2024, 7, 10, 8, 0, 0).unwrap();
+        let hours = chrono::Duration::hours;
+        let events = [
+            Event {
+                uid: "standup".to_owned(),
+                start_time: Some(now + hours(1)),
+                end_time: Some(now + hours(2)),
+                alarms: vec![
+                    Alarm::display(15, false, None),
+                    Alarm::display(90, false, None),
+                ],
+                ..Default::default()
+            },
+            Event {
+                uid: "trip".to_owned(),
+                start_time: Some(now + hours(72)),
+                alarms: vec![Alarm::display(48 * 60, false, None)],
+                ..Default::default()
+            },
+        ];
+        let tasks = [Task {
+            uid: "report".to_owned(),
+            due_date: Some(now + hours(4)),
+            alarms: vec![Alarm::display(60, true, None)],
+            ..Default::default()
+        }];
+
+        let reminders = upcoming(&events, &tasks, now, now + hours(24));
+        let times = reminders
+            .iter()
+            .map(|reminder| (reminder.time - now).num_minutes())
+            .collect::<Vec<_>>();
+        assert_eq!(times, [45, 180]);
+        assert!(matches!(reminders[0].item, Item::Event(event) if event.uid == "standup"));
+        assert!(matches!(reminders[1].item, Item::Task(task) if task.uid == "report"));
+
+        assert_eq!(upcoming(&events, &tasks, now, now + hours(25)).len(), 3);
+    }
+}
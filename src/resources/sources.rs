@@ -0,0 +1,75 @@
+use std::collections::HashMap;
+
+use anyhow::Result;
+use serde_json::json;
+
+use crate::{
+    gnome::evolution::{
+        fetch_sources, get_evolution_sources, is_writable, source_manager::is_created,
+        SOURCE_TIMEOUT,
+    },
+    mcp::{ResourceBody, ResourceContent, ResourceProvider},
+};
+
+#[derive(Default)]
+pub struct Sources;
+
+impl ResourceProvider for Sources {
+    const URI: &'static str = "gnome://evolution/sources";
+    const NAME: &'static str = "Evolution Sources";
+    const DESCRIPTION: &'static str =
+        "Calendars, task lists, memo lists and address books configured in Evolution Data Server";
+
+    async fn get_content(&self) -> Result<ResourceContent> {
+        let connection = zbus::Connection::session().await?;
+        let mut sources = get_evolution_sources(&connection)
+            .await?
+            .into_values()
+            .map(|(info, _proxy)| info)
+            .collect::<Vec<_>>();
+        sources.sort_by(|a, b| {
+            (a.source_type.name(), &a.display_name).cmp(&(b.source_type.name(), &b.display_name))
+        });
+
+        // Opening a backend can hang, so a source that doesn't answer in time
+        // is listed with an unknown writability
+        let writable = fetch_sources(sources.clone(), SOURCE_TIMEOUT, |info| {
+            let connection = connection.clone();
+            async move {
+                let writable = is_writable(&connection, &info).await;
+                Ok(vec![(info.uid, writable)])
+            }
+        })
+        .await
+        .items
+        .into_iter()
+        .collect::<HashMap<_, _>>();
+
+        let mut sources_json = Vec::with_capacity(sources.len());
+        for info in &sources {
+            sources_json.push(json!({
+                "uid": info.uid,
+                "display_name": info.display_name,
+                "type": info.source_type.name(),
+                "backend": info.source_type.backend_name(),
+                "color": info.color,
+                "writable": writable.get(&info.uid).copied().flatten(),
+                "default": info.is_default(),
+                "parent": info.parent,
+                "account": info.account,
+                "created_by_server": is_created(&info.uid)
+            }));
+        }
+
+        let sources_json = json!({
+            "sources": sources_json,
+            "count": sources.len()
+        });
+
+        Ok(ResourceContent {
+            uri: Self::URI.to_owned(),
+            mime_type: Self::MIME_TYPE.to_owned(),
+            body: ResourceBody::Text(sources_json.to_string()),
+        })
+    }
+}
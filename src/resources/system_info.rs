@@ -1,7 +1,7 @@
 use anyhow::Result;
 use serde_json::json;
 
-use crate::mcp::{ResourceContent, ResourceProvider};
+use crate::mcp::{ResourceBody, ResourceContent, ResourceProvider};
 
 #[derive(Default)]
 pub struct SystemInfo;
@@ -45,9 +45,9 @@ impl ResourceProvider for SystemInfo {
         });
 
         Ok(ResourceContent {
-            uri: Self::URI,
-            mime_type: Self::MIME_TYPE,
-            text: system_info.to_string(),
+            uri: Self::URI.to_owned(),
+            mime_type: Self::MIME_TYPE.to_owned(),
+            body: ResourceBody::Text(system_info.to_string()),
         })
     }
 }
@@ -1,9 +1,11 @@
+use std::collections::{HashMap, HashSet};
+
 use anyhow::Result;
 use serde_json::json;
 
 use crate::{
-    gnome::evolution::Task,
-    mcp::{ResourceContent, ResourceProvider},
+    gnome::evolution::{local_timezone, SourceResults, Task},
+    mcp::{ResourceBody, ResourceContent, ResourceProvider},
 };
 
 #[derive(Default)]
@@ -16,22 +18,158 @@ impl ResourceProvider for Tasks {
 
     async fn get_content(&self) -> Result<ResourceContent> {
         let config = crate::config::CONFIG.get_tasks_config();
-        let tasks = Task::all(
+        let SourceResults {
+            items: tasks,
+            errors,
+        } = Task::all(
             config.include_completed,
             config.include_cancelled,
             config.due_within_days,
+            &config.sources,
         )
         .await?;
 
+        let local = local_timezone().await;
         let tasks_json = json!({
-            "tasks": tasks.iter().map(|t| t.to_json()).collect::<Vec<_>>(),
-            "count": tasks.len()
+            "tasks": task_tree(&tasks, local),
+            "timezone": local.name(),
+            "count": tasks.len(),
+            "source_errors": errors
         });
 
         Ok(ResourceContent {
-            uri: Self::URI,
-            mime_type: Self::MIME_TYPE,
-            text: tasks_json.to_string(),
+            uri: Self::URI.to_owned(),
+            mime_type: Self::MIME_TYPE.to_owned(),
+            body: ResourceBody::Text(tasks_json.to_string()),
         })
     }
 }
+
+/// The tasks nested under their parents in `subtasks`. Tasks whose parent
+/// isn't listed stay at the top level, and siblings are ordered by due
+/// date, then priority and summary.
+fn task_tree(tasks: &[Task], local: chrono_tz::Tz) -> Vec<serde_json::Value> {
+    let index = tasks
+        .iter()
+        .enumerate()
+        .map(|(i, task)| (task.uid.as_str(), i))
+        .collect::<HashMap<_, _>>();
+
+    // A parent named by the subtask wins over one listing it as a child
+    let mut parents = HashMap::new();
+    for (i, task) in tasks.iter().enumerate() {
+        for child in &task.children {
+            if let Some(&child) = index.get(child.as_str()) {
+                parents.entry(child).or_insert(i);
+            }
+        }
+    }
+    for (i, task) in tasks.iter().enumerate() {
+        if let Some(&parent) = task.parent.as_deref().and_then(|uid| index.get(uid)) {
+            parents.insert(i, parent);
+        }
+    }
+
+    let mut children: HashMap<usize, Vec<usize>> = HashMap::new();
+    for (&child, &parent) in &parents {
+        if child != parent {
+            children.entry(parent).or_default().push(child);
+        }
+    }
+    let order = |i: &usize| {
+        let task = &tasks[*i];
+        (
+            task.due_date.is_none(),
+            task.due_date,
+            task.priority.unwrap_or(10),
+            task.summary.clone(),
+        )
+    };
+    for siblings in children.values_mut() {
+        siblings.sort_by_key(order);
+    }
+    let mut roots = (0..tasks.len())
+        .filter(|i| parents.get(i).map_or(true, |parent| parent == i))
+        .collect::<Vec<_>>();
+    roots.sort_by_key(order);
+
+    fn node(
+        i: usize,
+        tasks: &[Task],
+        children: &HashMap<usize, Vec<usize>>,
+        visited: &mut HashSet<usize>,
+        local: chrono_tz::Tz,
+    ) -> serde_json::Value {
+        visited.insert(i);
+        let mut json = tasks[i].to_json(local);
+        json["subtasks"] = children
+            .get(&i)
+            .into_iter()
+            .flatten()
+            .filter(|child| !visited.contains(child))
+            .copied()
+            .collect::<Vec<_>>()
+            .into_iter()
+            .map(|child| node(child, tasks, children, visited, local))
+            .collect();
+        json
+    }
+
+    let mut visited = HashSet::new();
+    let mut tree = roots
+        .into_iter()
+        .map(|root| node(root, tasks, &children, &mut visited, local))
+        .collect::<Vec<_>>();
+    // Tasks in a parent cycle have no root, start from the first of each
+    while let Some(i) = (0..tasks.len()).find(|i| !visited.contains(i)) {
+        tree.push(node(i, tasks, &children, &mut visited, local));
+    }
+    tree
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_task_tree() {
+        let task = |uid: &str, parent: Option<&str>, due: Option<&str>| Task {
+            uid: uid.to_owned(),
+            summary: Some(uid.to_owned()),
+            parent: parent.map(ToOwned::to_owned),
+            due_date: due.and_then(|due| due.parse().ok()),
+            ..Default::default()
+        };
+        let mut trip = task("trip", None, None);
+        trip.children = vec!["tickets".to_owned()];
+        let tasks = [
+            task("pack", Some("trip"), Some("2024-07-20T08:00:00Z")),
+            task("socks", Some("pack"), None),
+            trip,
+            task("tickets", None, Some("2024-07-10T08:00:00Z")),
+            task("orphan", Some("deleted"), Some("2024-07-01T08:00:00Z")),
+            task("a", Some("b"), None),
+            task("b", Some("a"), None),
+        ];
+
+        let tree = task_tree(&tasks, chrono_tz::UTC);
+        let uids = |nodes: &serde_json::Value| {
+            nodes
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|node| node["uid"].as_str().unwrap().to_owned())
+                .collect::<Vec<_>>()
+        };
+        let tree = serde_json::Value::from(tree);
+        assert_eq!(uids(&tree), ["orphan", "trip", "a"]);
+        assert_eq!(tree[0]["parent"], "deleted");
+        assert_eq!(uids(&tree[1]["subtasks"]), ["tickets", "pack"]);
+        assert_eq!(uids(&tree[1]["subtasks"][1]["subtasks"]), ["socks"]);
+        assert_eq!(uids(&tree[2]["subtasks"]), ["b"]);
+        assert!(tree[2]["subtasks"][0]["subtasks"]
+            .as_array()
+            .unwrap()
+            .is_empty());
+    }
+}
@@ -0,0 +1,29 @@
+use anyhow::Result;
+use serde_json::json;
+
+use crate::mcp::{ResourceBody, ResourceContent, ResourceProvider};
+
+#[derive(Default)]
+pub struct UndoHistory;
+
+impl ResourceProvider for UndoHistory {
+    const URI: &'static str = "gnome://undo/history";
+    const NAME: &'static str = "Undo History";
+    const DESCRIPTION: &'static str =
+        "Reversible desktop changes that undo_last_action can roll back, newest first";
+
+    async fn get_content(&self) -> Result<ResourceContent> {
+        let entries = crate::undo::history();
+
+        let history_json = json!({
+            "entries": entries,
+            "count": entries.len()
+        });
+
+        Ok(ResourceContent {
+            uri: Self::URI.to_owned(),
+            mime_type: Self::MIME_TYPE.to_owned(),
+            body: ResourceBody::Text(history_json.to_string()),
+        })
+    }
+}
@@ -23,29 +23,38 @@ impl ToolProvider for Applications {
         )
         .await
     }
+
+    async fn dry_run_with_params(&self, params: Self::Params) -> Result<serde_json::Value> {
+        let app_info = find_application(&params.app_name)?;
+        Ok(serde_json::json!({
+            "description": format!("Would launch application: {}", app_info.name()),
+            "name": app_info.name().to_string(),
+            "id": app_info.id().map(|i| i.to_string()),
+            "executable": app_info.executable().to_string_lossy()
+        }))
+    }
 }
 
-async fn launch_application(app_name: &str) -> Result<()> {
+fn find_application(app_name: &str) -> Result<gio::AppInfo> {
     let app_infos = gio::AppInfo::all();
     let total_apps = app_infos.len();
+    let app_name_lower = app_name.to_lowercase();
+
+    app_infos
+        .into_iter()
+        .filter(|app_info| app_info.should_show())
+        .find(|app_info| app_info.name().to_lowercase().contains(&app_name_lower))
+        .ok_or_else(|| {
+            anyhow::anyhow!(
+                "App '{}' not found among {} total apps",
+                app_name,
+                total_apps
+            )
+        })
+}
 
-    for app_info in app_infos {
-        if !app_info.should_show() {
-            continue;
-        }
-
-        let name = app_info.name().to_lowercase();
-        let app_name_lower = app_name.to_lowercase();
-
-        if name.contains(&app_name_lower) {
-            app_info.launch(&[], gio::AppLaunchContext::NONE)?;
-            return Ok(());
-        }
-    }
-
-    Err(anyhow::anyhow!(
-        "App '{}' not found among {} total apps",
-        app_name,
-        total_apps
-    ))
+async fn launch_application(app_name: &str) -> Result<()> {
+    let app_info = find_application(app_name)?;
+    app_info.launch(&[], gio::AppLaunchContext::NONE)?;
+    Ok(())
 }
@@ -1,6 +1,6 @@
 use anyhow::Result;
 
-use crate::{mcp::ToolProvider, tool_params};
+use crate::{mcp::ToolProvider, tool_params, undo::PreviousState};
 
 #[derive(Default)]
 pub struct Volume;
@@ -19,32 +19,102 @@ impl ToolProvider for Volume {
     type Params = VolumeParams;
 
     async fn execute_with_params(&self, params: Self::Params) -> Result<serde_json::Value> {
-        let config = crate::config::CONFIG.get_audio_tool_config();
-
-        if let Some(volume) = params.volume {
-            Self::execute_with_result(|| {
-                set_system_volume(volume, params.relative.unwrap_or(false))
-            })
-            .await
-        } else if let Some(direction) = params.direction {
-            let volume_change = match direction.as_str() {
-                "up" => config.volume_step as f64,
-                "down" => -(config.volume_step as f64),
-                _ => {
-                    return Err(anyhow::anyhow!(
-                        "Invalid direction: {}. Use 'up' or 'down'",
-                        direction
-                    ));
-                }
-            };
-            Self::execute_with_result(|| set_system_volume(volume_change, true)).await
-        } else if let Some(mute) = params.mute {
-            Self::execute_with_result(|| set_system_mute(mute)).await
-        } else {
-            Err(anyhow::anyhow!(
-                "Must specify either volume, direction, or mute parameter"
-            ))
-        }
+        let action = volume_action(&params)?;
+        Self::execute_with_result(|| apply_volume_action(action)).await
+    }
+
+    async fn dry_run_with_params(&self, params: Self::Params) -> Result<serde_json::Value> {
+        let description = match volume_action(&params)? {
+            VolumeAction::Set { volume, relative } => {
+                format!("Would set volume to {}", wpctl_volume_arg(volume, relative))
+            }
+            VolumeAction::Mute(true) => "Would mute the default audio sink".to_owned(),
+            VolumeAction::Mute(false) => "Would unmute the default audio sink".to_owned(),
+        };
+        Ok(serde_json::json!({
+            "description": description,
+            "sink": "@DEFAULT_AUDIO_SINK@"
+        }))
+    }
+}
+
+enum VolumeAction {
+    Set { volume: f64, relative: bool },
+    Mute(bool),
+}
+
+async fn apply_volume_action(action: VolumeAction) -> Result<String> {
+    let previous = volume_state().await.ok();
+    let message = match action {
+        VolumeAction::Set { volume, relative } => set_system_volume(volume, relative).await?,
+        VolumeAction::Mute(mute) => set_system_mute(mute).await?,
+    };
+    if let Some((level, muted)) = previous {
+        crate::undo::record(Volume::NAME, PreviousState::Volume { level, muted });
+    }
+    Ok(message)
+}
+
+pub(crate) async fn restore_volume(level: f64, muted: bool) -> Result<()> {
+    set_system_volume(level, false).await?;
+    set_system_mute(muted).await?;
+    Ok(())
+}
+
+/// Volume of the default sink in percent, and whether it is muted
+pub(crate) async fn volume_state() -> Result<(f64, bool)> {
+    let output = tokio::process::Command::new("wpctl")
+        .args(["get-volume", "@DEFAULT_AUDIO_SINK@"])
+        .output()
+        .await?;
+
+    if !output.status.success() {
+        return Err(anyhow::anyhow!("wpctl get-volume failed"));
+    }
+
+    let volume_output = String::from_utf8_lossy(&output.stdout);
+
+    // Parse output like "Volume: 0.65 [MUTED]"
+    let volume_percent = volume_output
+        .split_whitespace()
+        .nth(1)
+        .and_then(|vol_str| vol_str.parse::<f64>().ok())
+        .map(|vol_float| (vol_float * 10_000.0).round() / 100.0)
+        .unwrap_or(0.0);
+    let is_muted = volume_output.contains("[MUTED]");
+
+    Ok((volume_percent, is_muted))
+}
+
+fn volume_action(params: &VolumeParams) -> Result<VolumeAction> {
+    let config = crate::config::CONFIG.get_audio_tool_config();
+
+    if let Some(volume) = params.volume {
+        Ok(VolumeAction::Set {
+            volume,
+            relative: params.relative.unwrap_or(false),
+        })
+    } else if let Some(direction) = &params.direction {
+        let volume_change = match direction.as_str() {
+            "up" => config.volume_step as f64,
+            "down" => -(config.volume_step as f64),
+            _ => {
+                return Err(anyhow::anyhow!(
+                    "Invalid direction: {}. Use 'up' or 'down'",
+                    direction
+                ));
+            }
+        };
+        Ok(VolumeAction::Set {
+            volume: volume_change,
+            relative: true,
+        })
+    } else if let Some(mute) = params.mute {
+        Ok(VolumeAction::Mute(mute))
+    } else {
+        Err(anyhow::anyhow!(
+            "Must specify either volume, direction, or mute parameter"
+        ))
     }
 }
 
@@ -71,10 +141,41 @@ impl ToolProvider for Media {
 
         Self::execute_with_result(|| control_media_playback(&params.action, player_ref)).await
     }
+
+    async fn dry_run_with_params(&self, params: Self::Params) -> Result<serde_json::Value> {
+        let (method, _) = media_method(&params.action)?;
+        let player_ref = if params.player.is_empty() {
+            None
+        } else {
+            Some(params.player.as_str())
+        };
+
+        let connection = zbus::Connection::session().await?;
+        let target_player = resolve_player(&connection, player_ref).await?;
+
+        Ok(serde_json::json!({
+            "description": format!("Would call {method} on {target_player}"),
+            "player": target_player,
+            "method": method
+        }))
+    }
 }
 
-async fn set_system_volume(volume: f64, relative: bool) -> Result<String> {
-    let volume_str = if relative {
+/// MPRIS method for an action, along with the message reported on success
+fn media_method(action: &str) -> Result<(&'static str, &'static str)> {
+    match action {
+        "play" => Ok(("Play", "Started playback on")),
+        "pause" => Ok(("Pause", "Paused playback on")),
+        "play_pause" => Ok(("PlayPause", "Toggled playback on")),
+        "stop" => Ok(("Stop", "Stopped playback on")),
+        "next" => Ok(("Next", "Skipped to next track on")),
+        "previous" => Ok(("Previous", "Skipped to previous track on")),
+        _ => Err(anyhow::anyhow!("Unknown action: {}", action)),
+    }
+}
+
+fn wpctl_volume_arg(volume: f64, relative: bool) -> String {
+    if relative {
         if volume >= 0.0 {
             format!("{volume}%+")
         } else {
@@ -82,7 +183,11 @@ async fn set_system_volume(volume: f64, relative: bool) -> Result<String> {
         }
     } else {
         format!("{volume}%")
-    };
+    }
+}
+
+async fn set_system_volume(volume: f64, relative: bool) -> Result<String> {
+    let volume_str = wpctl_volume_arg(volume, relative);
 
     // Try wpctl (WirePlumber control)
     let output = tokio::process::Command::new("wpctl")
@@ -120,26 +225,30 @@ async fn set_system_mute(mute: bool) -> Result<String> {
     ))
 }
 
-async fn control_media_playback(action: &str, player: Option<&str>) -> Result<String> {
-    let connection = zbus::Connection::session().await?;
-
+async fn resolve_player(connection: &zbus::Connection, player: Option<&str>) -> Result<String> {
     // Find available MPRIS players
-    let players = find_mpris_players(&connection).await?;
+    let players = find_mpris_players(connection).await?;
 
     if players.is_empty() {
         return Err(anyhow::anyhow!("No media players found"));
     }
 
     // Select target player
-    let target_player = if let Some(player_name) = player {
+    if let Some(player_name) = player {
         players
-            .iter()
+            .into_iter()
             .find(|p| p.to_lowercase().contains(&player_name.to_lowercase()))
-            .ok_or_else(|| anyhow::anyhow!("Player '{}' not found", player_name))?
+            .ok_or_else(|| anyhow::anyhow!("Player '{}' not found", player_name))
     } else {
         // Use the first available player
-        &players[0]
-    };
+        Ok(players[0].clone())
+    }
+}
+
+async fn control_media_playback(action: &str, player: Option<&str>) -> Result<String> {
+    let (method, message) = media_method(action)?;
+    let connection = zbus::Connection::session().await?;
+    let target_player = resolve_player(&connection, player).await?;
 
     // Connect to the MPRIS player
     let player_proxy = zbus::Proxy::new(
@@ -151,33 +260,8 @@ async fn control_media_playback(action: &str, player: Option<&str>) -> Result<St
     .await?;
 
     // Execute the requested action
-    match action {
-        "play" => {
-            player_proxy.call_method("Play", &()).await?;
-            Ok(format!("Started playback on {target_player}"))
-        }
-        "pause" => {
-            player_proxy.call_method("Pause", &()).await?;
-            Ok(format!("Paused playback on {target_player}"))
-        }
-        "play_pause" => {
-            player_proxy.call_method("PlayPause", &()).await?;
-            Ok(format!("Toggled playback on {target_player}"))
-        }
-        "stop" => {
-            player_proxy.call_method("Stop", &()).await?;
-            Ok(format!("Stopped playback on {target_player}"))
-        }
-        "next" => {
-            player_proxy.call_method("Next", &()).await?;
-            Ok(format!("Skipped to next track on {target_player}"))
-        }
-        "previous" => {
-            player_proxy.call_method("Previous", &()).await?;
-            Ok(format!("Skipped to previous track on {target_player}"))
-        }
-        _ => Err(anyhow::anyhow!("Unknown action: {}", action)),
-    }
+    player_proxy.call_method(method, &()).await?;
+    Ok(format!("{message} {target_player}"))
 }
 
 async fn find_mpris_players(connection: &zbus::Connection) -> Result<Vec<String>> {
@@ -0,0 +1,820 @@
+use std::str::FromStr;
+
+use anyhow::Result;
+use chrono::{DateTime, NaiveDate, NaiveDateTime, TimeZone, Utc};
+use serde_json::json;
+
+use crate::{
+    gnome::evolution::{local_timezone, Alarm, Attendee, Event},
+    mcp::ToolProvider,
+    tool_params,
+};
+
+#[derive(Default)]
+pub struct CreateCalendarEvent;
+
+#[derive(Default)]
+pub struct UpdateCalendarEvent;
+
+#[derive(Default)]
+pub struct DeleteCalendarEvent;
+
+#[derive(Default)]
+pub struct RespondToInvitation;
+
+tool_params! {
+    CreateCalendarEventParams,
+    required(summary: string, "Event title"),
+    required(start: string, "Start time, either RFC 3339 (2024-07-10T10:00:00+02:00) or local time (2024-07-10T10:00), or a date (2024-07-10) for an all-day event"),
+    optional(end: string, "End time, in the same formats as start; for all-day events, the day after the last one (default: one hour after start, or the next day)"),
+    optional(timezone: string, "IANA timezone for local times and the stored TZID, e.g. Europe/Paris (default: system timezone)"),
+    optional(location: string, "Event location"),
+    optional(description: string, "Event description"),
+    optional(attendees: string, "Comma-separated attendee email addresses"),
+    optional(rrule: string, "Recurrence rule, e.g. FREQ=WEEKLY;BYDAY=MO;COUNT=4"),
+    optional(reminders: string, "Comma-separated minutes before the start to show a reminder, e.g. 10,60"),
+    optional(calendar: string, "Calendar UID or display name (default: the default calendar)")
+}
+
+impl ToolProvider for CreateCalendarEvent {
+    const NAME: &'static str = "create_calendar_event";
+    const DESCRIPTION: &'static str =
+        "Create an event in a writable Evolution Data Server calendar and return its UID";
+    type Params = CreateCalendarEventParams;
+
+    async fn execute_with_params(&self, params: Self::Params) -> Result<serde_json::Value> {
+        Self::execute_with_result(|| async {
+            let event = build_event(&params)?;
+            let uid = event.create(params.calendar.as_deref()).await?;
+            Ok(json!({
+                "uid": uid,
+                "summary": event.summary
+            }))
+        })
+        .await
+    }
+
+    async fn dry_run_with_params(&self, params: Self::Params) -> Result<serde_json::Value> {
+        let event = build_event(&params)?;
+        event.validate()?;
+
+        let connection = zbus::Connection::session().await?;
+        let (source, _proxy) =
+            Event::writable_calendar(&connection, params.calendar.as_deref()).await?;
+        Ok(json!({
+            "description": format!(
+                "Would create event '{}' in {}",
+                params.summary, source.display_name
+            ),
+            "calendar": source.display_name,
+            "event": event.to_json(local_timezone().await)
+        }))
+    }
+}
+
+tool_params! {
+    UpdateCalendarEventParams,
+    required(uid: string, "UID of the event to change"),
+    optional(occurrence: string, "Original start time of the occurrence to change, or its date for all-day events (default: change all occurrences)"),
+    optional(summary: string, "New title"),
+    optional(start: string, "New start time, RFC 3339 or local time, or a date to make it an all-day event; the duration is kept unless end is given"),
+    optional(end: string, "New end time, RFC 3339 or local time, or the day after the last one for all-day events"),
+    optional(timezone: string, "IANA timezone for local times and the stored TZID (default: the event's timezone)"),
+    optional(location: string, "New location, empty to clear it"),
+    optional(description: string, "New description, empty to clear it"),
+    optional(attendees: string, "Comma-separated attendee email addresses, replacing the current ones"),
+    optional(rrule: string, "New recurrence rule for the whole series, empty to stop recurring"),
+    optional(calendar: string, "Calendar UID or display name holding the event (default: search all calendars)")
+}
+
+impl ToolProvider for UpdateCalendarEvent {
+    const NAME: &'static str = "update_calendar_event";
+    const DESCRIPTION: &'static str =
+        "Reschedule or edit a calendar event, or a single occurrence of a recurring event";
+    type Params = UpdateCalendarEventParams;
+
+    async fn execute_with_params(&self, params: Self::Params) -> Result<serde_json::Value> {
+        Self::execute_with_result(|| async {
+            let connection = zbus::Connection::session().await?;
+            let (source, proxy, stored) =
+                Event::find(&connection, &params.uid, params.calendar.as_deref()).await?;
+            let event = apply_changes(&stored, &params)?;
+            event.modify(&source, &proxy, &stored).await?;
+            Ok(json!({
+                "uid": event.uid,
+                "calendar": source.display_name,
+                "event": event.to_json(local_timezone().await)
+            }))
+        })
+        .await
+    }
+
+    async fn dry_run_with_params(&self, params: Self::Params) -> Result<serde_json::Value> {
+        let connection = zbus::Connection::session().await?;
+        let (source, proxy, stored) =
+            Event::find(&connection, &params.uid, params.calendar.as_deref()).await?;
+        crate::gnome::evolution::ensure_writable(&proxy, &source).await?;
+        let event = apply_changes(&stored, &params)?;
+        event.validate()?;
+
+        let summary = event.summary.as_deref().unwrap_or(&event.uid);
+        let description = match event.recurrence_id {
+            Some(occurrence) => format!(
+                "Would update the {} occurrence of '{}' in {}",
+                occurrence.to_rfc3339(),
+                summary,
+                source.display_name
+            ),
+            None => format!("Would update '{}' in {}", summary, source.display_name),
+        };
+        Ok(json!({
+            "description": description,
+            "calendar": source.display_name,
+            "event": event.to_json(local_timezone().await)
+        }))
+    }
+}
+
+tool_params! {
+    DeleteCalendarEventParams,
+    required(uid: string, "UID of the event to delete"),
+    optional(occurrence: string, "Original start time of the occurrence to delete, or its date for all-day events (default: delete all occurrences)"),
+    optional(calendar: string, "Calendar UID or display name holding the event (default: search all calendars)")
+}
+
+impl ToolProvider for DeleteCalendarEvent {
+    const NAME: &'static str = "delete_calendar_event";
+    const DESCRIPTION: &'static str =
+        "Delete a calendar event, or a single occurrence of a recurring event";
+    type Params = DeleteCalendarEventParams;
+
+    async fn execute_with_params(&self, params: Self::Params) -> Result<serde_json::Value> {
+        Self::execute_with_result(|| async {
+            let connection = zbus::Connection::session().await?;
+            let (source, proxy, stored) =
+                Event::find(&connection, &params.uid, params.calendar.as_deref()).await?;
+            let event = Event::from_str(&stored)?;
+            let occurrence = occurrence_of(&event, &stored, params.occurrence.as_deref())?;
+            event.remove(&source, &proxy, occurrence).await?;
+            Ok(json!({
+                "uid": event.uid,
+                "calendar": source.display_name,
+                "occurrence": occurrence
+            }))
+        })
+        .await
+    }
+
+    async fn dry_run_with_params(&self, params: Self::Params) -> Result<serde_json::Value> {
+        let connection = zbus::Connection::session().await?;
+        let (source, proxy, stored) =
+            Event::find(&connection, &params.uid, params.calendar.as_deref()).await?;
+        crate::gnome::evolution::ensure_writable(&proxy, &source).await?;
+        let event = Event::from_str(&stored)?;
+        let occurrence = occurrence_of(&event, &stored, params.occurrence.as_deref())?;
+
+        let summary = event.summary.as_deref().unwrap_or(&event.uid);
+        let description = match occurrence {
+            Some(occurrence) => format!(
+                "Would delete the {} occurrence of '{}' from {}",
+                occurrence.to_rfc3339(),
+                summary,
+                source.display_name
+            ),
+            None => format!("Would delete '{}' from {}", summary, source.display_name),
+        };
+        Ok(json!({
+            "description": description,
+            "calendar": source.display_name,
+            "event": event.to_json(local_timezone().await)
+        }))
+    }
+}
+
+tool_params! {
+    RespondToInvitationParams,
+    required(uid: string, "UID of the event you are invited to"),
+    required(response: string, "Reply: 'accept', 'decline' or 'tentative'"),
+    optional(occurrence: string, "Original start time of the occurrence to reply to, or its date for all-day events (default: all occurrences)"),
+    optional(email: string, "Your address among the attendees (default: the address of the calendar's account)"),
+    optional(calendar: string, "Calendar UID or display name holding the event (default: search all calendars)")
+}
+
+impl ToolProvider for RespondToInvitation {
+    const NAME: &'static str = "respond_to_invitation";
+    const DESCRIPTION: &'static str =
+        "Accept, decline or tentatively accept a calendar invitation by setting your participation status";
+    type Params = RespondToInvitationParams;
+
+    async fn execute_with_params(&self, params: Self::Params) -> Result<serde_json::Value> {
+        Self::execute_with_result(|| async {
+            let connection = zbus::Connection::session().await?;
+            let (source, proxy, stored) =
+                Event::find(&connection, &params.uid, params.calendar.as_deref()).await?;
+            let (event, status, changed) = reply_to(&proxy, &stored, &params).await?;
+            if changed {
+                event.modify(&source, &proxy, &stored).await?;
+            }
+            Ok(json!({
+                "uid": event.uid,
+                "calendar": source.display_name,
+                "status": status,
+                "changed": changed,
+                "event": event.to_json(local_timezone().await)
+            }))
+        })
+        .await
+    }
+
+    async fn dry_run_with_params(&self, params: Self::Params) -> Result<serde_json::Value> {
+        let connection = zbus::Connection::session().await?;
+        let (source, proxy, stored) =
+            Event::find(&connection, &params.uid, params.calendar.as_deref()).await?;
+        crate::gnome::evolution::ensure_writable(&proxy, &source).await?;
+        let (event, status, changed) = reply_to(&proxy, &stored, &params).await?;
+
+        let summary = event.summary.as_deref().unwrap_or(&event.uid);
+        let description = match changed {
+            true => format!(
+                "Would reply {} to '{}' in {}",
+                status, summary, source.display_name
+            ),
+            false => format!(
+                "Already replied {} to '{}' in {}",
+                status, summary, source.display_name
+            ),
+        };
+        Ok(json!({
+            "description": description,
+            "calendar": source.display_name,
+            "status": status,
+            "changed": changed,
+            "event": event.to_json(local_timezone().await)
+        }))
+    }
+}
+
+/// The stored event with the user's reply, the participation status it
+/// was given and whether that changes the stored event
+async fn reply_to(
+    proxy: &zbus::Proxy<'_>,
+    stored: &str,
+    params: &RespondToInvitationParams,
+) -> Result<(Event, &'static str, bool)> {
+    let address = match &params.email {
+        Some(email) => email.clone(),
+        None => crate::gnome::evolution::calendar_email_address(proxy)
+            .await
+            .ok_or_else(|| {
+                anyhow::anyhow!("The calendar has no account address, pass your email")
+            })?,
+    };
+    let event = at_occurrence(stored, params.occurrence.as_deref())?;
+    set_participation(event, &params.response, &address)
+}
+
+/// Set the participation status of the attendee at `address` from an
+/// accept, decline or tentative reply, which no longer awaits an RSVP.
+/// Also tells whether the attendee had replied otherwise before.
+fn set_participation(
+    mut event: Event,
+    response: &str,
+    address: &str,
+) -> Result<(Event, &'static str, bool)> {
+    let status = match response.to_ascii_lowercase().as_str() {
+        "accept" => "ACCEPTED",
+        "decline" => "DECLINED",
+        "tentative" => "TENTATIVE",
+        _ => anyhow::bail!(
+            "Unknown response '{}', expected accept, decline or tentative",
+            response
+        ),
+    };
+    let uid = event.uid.clone();
+    let attendee = event
+        .attendees
+        .iter_mut()
+        .find(|attendee| attendee.is(address))
+        .ok_or_else(|| anyhow::anyhow!("{} is not invited to '{}'", address, uid))?;
+    let changed = attendee.status.as_deref() != Some(status) || attendee.rsvp;
+    attendee.status = Some(status.to_owned());
+    attendee.rsvp = false;
+    Ok((event, status, changed))
+}
+
+/// Narrow a stored event to one of its occurrences: the detached instance
+/// when the occurrence was already changed on its own, or a copy of the
+/// series at that time, which gets detached when changed
+fn at_occurrence(stored: &str, occurrence: Option<&str>) -> Result<Event> {
+    let mut event = Event::from_str(stored)?;
+    let Some(occurrence) = occurrence_of(&event, stored, occurrence)? else {
+        return Ok(event);
+    };
+    if let Some(instance) = Event::detached_instance(stored, occurrence) {
+        return Ok(instance);
+    }
+
+    let duration = event
+        .start_time
+        .zip(event.end_time)
+        .map(|(start, end)| end - start);
+    event.recurrence_id = Some(occurrence);
+    event.rrule = None;
+    event.start_time = Some(occurrence);
+    event.end_time = duration.map(|duration| occurrence + duration);
+    Ok(event)
+}
+
+/// Resolve the `occurrence` parameter against a stored event, interpreting
+/// local times in the event's timezone
+fn occurrence_of(
+    event: &Event,
+    stored: &str,
+    occurrence: Option<&str>,
+) -> Result<Option<DateTime<Utc>>> {
+    let Some(occurrence) = occurrence else {
+        return Ok(None);
+    };
+    if !Event::is_recurring(stored) {
+        anyhow::bail!(
+            "Event '{}' does not recur, omit the occurrence to change it",
+            event.uid
+        );
+    }
+    if event.all_day {
+        return parse_date(occurrence).map(Some);
+    }
+    let tz = event_timezone(event.timezone.as_deref())?;
+    parse_date_time(occurrence, tz).map(Some)
+}
+
+/// Apply the update parameters to a stored event
+fn apply_changes(stored: &str, params: &UpdateCalendarEventParams) -> Result<Event> {
+    if params.occurrence.is_some() && params.rrule.is_some() {
+        anyhow::bail!("The recurrence rule can only be changed for all occurrences");
+    }
+    let mut event = at_occurrence(stored, params.occurrence.as_deref())?;
+    let duration = event
+        .start_time
+        .zip(event.end_time)
+        .map(|(start, end)| end - start);
+
+    let tz = match &params.timezone {
+        Some(timezone) => {
+            let tz = event_timezone(Some(timezone))?;
+            event.timezone = tz.map(|tz| tz.name().to_owned());
+            tz
+        }
+        None => event_timezone(event.timezone.as_deref())?,
+    };
+    if let Some(start) = &params.start {
+        let all_day = is_date(start);
+        let start_time = match all_day {
+            true => parse_date(start)?,
+            false => parse_date_time(start, tz)?,
+        };
+        // Switching between timed and all-day events resets the duration
+        let duration = match all_day == event.all_day {
+            true => duration,
+            false => Some(default_duration(all_day)),
+        };
+        if all_day {
+            event.timezone = None;
+        }
+        event.all_day = all_day;
+        event.start_time = Some(start_time);
+        event.end_time = duration.map(|duration| start_time + duration);
+    }
+    if let Some(end) = &params.end {
+        event.end_time = Some(match event.all_day {
+            true => parse_date(end)?,
+            false => parse_date_time(end, tz)?,
+        });
+    }
+
+    if let Some(summary) = &params.summary {
+        event.summary = Some(summary.clone());
+    }
+    if let Some(location) = &params.location {
+        event.location = Some(location.clone()).filter(|l| !l.is_empty());
+    }
+    if let Some(description) = &params.description {
+        event.description = Some(description.clone()).filter(|d| !d.is_empty());
+    }
+    if let Some(attendees) = &params.attendees {
+        // Attendees already invited keep their role and reply
+        event.attendees = parse_list(attendees)
+            .iter()
+            .map(|email| {
+                event
+                    .attendees
+                    .iter()
+                    .find(|attendee| attendee.is(email))
+                    .cloned()
+                    .unwrap_or_else(|| Attendee::invite(email))
+            })
+            .collect();
+    }
+    if let Some(rrule) = &params.rrule {
+        event.rrule = Some(rrule.clone()).filter(|r| !r.is_empty());
+    }
+
+    Ok(event)
+}
+
+fn build_event(params: &CreateCalendarEventParams) -> Result<Event> {
+    let all_day = is_date(&params.start);
+    let tz = match all_day {
+        true => None,
+        false => event_timezone(params.timezone.as_deref())?,
+    };
+    let parse = |value: &str| match all_day {
+        true => parse_date(value),
+        false => parse_date_time(value, tz),
+    };
+    let start_time = parse(&params.start)?;
+    let end_time = match &params.end {
+        Some(end) => parse(end)?,
+        None => start_time + default_duration(all_day),
+    };
+
+    Ok(Event {
+        summary: Some(params.summary.clone()),
+        description: params.description.clone(),
+        start_time: Some(start_time),
+        end_time: Some(end_time),
+        location: params.location.clone(),
+        attendees: params
+            .attendees
+            .as_deref()
+            .map(parse_list)
+            .unwrap_or_default()
+            .iter()
+            .map(|email| Attendee::invite(email))
+            .collect(),
+        rrule: params.rrule.clone().filter(|rrule| !rrule.is_empty()),
+        timezone: tz.map(|tz| tz.name().to_owned()),
+        all_day,
+        alarms: parse_reminders(params.reminders.as_deref(), false, &params.summary)?,
+        ..Default::default()
+    })
+}
+
+/// One day for all-day events, an hour otherwise
+fn default_duration(all_day: bool) -> chrono::Duration {
+    match all_day {
+        true => chrono::Duration::days(1),
+        false => chrono::Duration::hours(1),
+    }
+}
+
+pub(crate) fn is_date(value: &str) -> bool {
+    NaiveDate::parse_from_str(value, "%Y-%m-%d").is_ok()
+}
+
+/// Parse a `YYYY-MM-DD` date, kept at midnight UTC like the dates of
+/// all-day events
+pub(crate) fn parse_date(value: &str) -> Result<DateTime<Utc>> {
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .ok()
+        .and_then(|date| date.and_hms_opt(0, 0, 0))
+        .map(|date| date.and_utc())
+        .ok_or_else(|| anyhow::anyhow!("Invalid date '{}', expected YYYY-MM-DD", value))
+}
+
+/// The requested timezone, or the system one when it is a known IANA zone
+pub(crate) fn event_timezone(timezone: Option<&str>) -> Result<Option<chrono_tz::Tz>> {
+    match timezone {
+        Some(timezone) => timezone
+            .parse::<chrono_tz::Tz>()
+            .map(Some)
+            .map_err(|_| anyhow::anyhow!("Unknown timezone '{}'", timezone)),
+        None => Ok(iana_time_zone::get_timezone()
+            .ok()
+            .and_then(|timezone| timezone.parse::<chrono_tz::Tz>().ok())),
+    }
+}
+
+/// Parse an RFC 3339 timestamp, or a local time in `tz` (the system
+/// timezone when unset)
+pub(crate) fn parse_date_time(value: &str, tz: Option<chrono_tz::Tz>) -> Result<DateTime<Utc>> {
+    if let Ok(date_time) = DateTime::parse_from_rfc3339(value) {
+        return Ok(date_time.with_timezone(&Utc));
+    }
+
+    let naive = ["%Y-%m-%dT%H:%M:%S", "%Y-%m-%dT%H:%M", "%Y-%m-%d %H:%M"]
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(value, format).ok())
+        .ok_or_else(|| {
+            anyhow::anyhow!(
+                "Invalid date/time '{}', expected RFC 3339 or YYYY-MM-DDTHH:MM",
+                value
+            )
+        })?;
+
+    let local = match tz {
+        Some(tz) => tz
+            .from_local_datetime(&naive)
+            .earliest()
+            .map(|dt| dt.with_timezone(&Utc)),
+        None => chrono::Local
+            .from_local_datetime(&naive)
+            .earliest()
+            .map(|dt| dt.with_timezone(&Utc)),
+    };
+    local.ok_or_else(|| anyhow::anyhow!("'{}' does not exist in the timezone", value))
+}
+
+/// DISPLAY alarms from a comma-separated list of minutes before the start,
+/// or before the end when `related_end` is set
+pub(crate) fn parse_reminders(
+    value: Option<&str>,
+    related_end: bool,
+    description: &str,
+) -> Result<Vec<Alarm>> {
+    parse_list(value.unwrap_or_default())
+        .iter()
+        .map(|minutes| {
+            let minutes = minutes
+                .parse::<i64>()
+                .ok()
+                .filter(|minutes| *minutes >= 0)
+                .ok_or_else(|| {
+                    anyhow::anyhow!("Invalid reminder '{}', expected minutes before", minutes)
+                })?;
+            Ok(Alarm::display(
+                minutes,
+                related_end,
+                Some(description.to_owned()),
+            ))
+        })
+        .collect()
+}
+
+fn parse_list(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(ToOwned::to_owned)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::mcp::ToolParams;
+
+    #[test]
+    fn test_parse_date_time() {
+        let paris = Some(chrono_tz::Europe::Paris);
+        assert_eq!(
+            parse_date_time("2024-07-10T10:00:00+02:00", None).unwrap(),
+            "2024-07-10T08:00:00Z".parse::<DateTime<Utc>>().unwrap()
+        );
+        assert_eq!(
+            parse_date_time("2024-07-10T10:00", paris).unwrap(),
+            "2024-07-10T08:00:00Z".parse::<DateTime<Utc>>().unwrap()
+        );
+        assert_eq!(
+            parse_date_time("2024-01-10 10:00", paris).unwrap(),
+            "2024-01-10T09:00:00Z".parse::<DateTime<Utc>>().unwrap()
+        );
+        assert!(parse_date_time("next tuesday", paris).is_err());
+    }
+
+    #[test]
+    fn test_build_event() {
+        let params = CreateCalendarEventParams::extract_params(&json!({
+            "summary": "Standup",
+            "start": "2024-07-10T09:30",
+            "timezone": "Europe/Paris",
+            "attendees": "anna@example.com, bob@example.com,",
+            "rrule": "FREQ=DAILY;COUNT=5",
+            "reminders": "10, 60"
+        }))
+        .unwrap();
+
+        let event = build_event(&params).unwrap();
+        assert_eq!(event.summary.as_deref(), Some("Standup"));
+        assert_eq!(event.timezone.as_deref(), Some("Europe/Paris"));
+        assert_eq!(
+            event.end_time.unwrap() - event.start_time.unwrap(),
+            chrono::Duration::hours(1)
+        );
+        assert_eq!(
+            event.attendees,
+            [
+                Attendee::invite("anna@example.com"),
+                Attendee::invite("bob@example.com")
+            ]
+        );
+        assert_eq!(
+            event
+                .alarm_times()
+                .iter()
+                .map(|(_, time)| time.to_rfc3339())
+                .collect::<Vec<_>>(),
+            ["2024-07-10T07:20:00+00:00", "2024-07-10T06:30:00+00:00"]
+        );
+        assert!(event.validate().is_ok());
+        assert!(event
+            .to_ical()
+            .contains("BEGIN:VALARM\r\nACTION:DISPLAY\r\nTRIGGER:-PT10M"));
+
+        let params = CreateCalendarEventParams::extract_params(&json!({
+            "summary": "Standup",
+            "start": "2024-07-10T09:30",
+            "reminders": "soon"
+        }))
+        .unwrap();
+        assert!(build_event(&params).is_err());
+
+        let params = CreateCalendarEventParams::extract_params(&json!({
+            "summary": "Standup",
+            "start": "2024-07-10T09:30",
+            "timezone": "Nowhere/Special"
+        }))
+        .unwrap();
+        assert!(build_event(&params).is_err());
+    }
+
+    #[test]
+    fn test_build_all_day_event() {
+        let params = CreateCalendarEventParams::extract_params(&json!({
+            "summary": "Conference",
+            "start": "2024-07-10",
+            "end": "2024-07-12"
+        }))
+        .unwrap();
+
+        let event = build_event(&params).unwrap();
+        assert!(event.all_day);
+        assert_eq!(event.timezone, None);
+        assert!(event
+            .to_ical()
+            .contains("DTSTART;VALUE=DATE:20240710\r\nDTEND;VALUE=DATE:20240712\r\n"));
+
+        // Giving a time makes it a timed event again
+        let params = UpdateCalendarEventParams::extract_params(&json!({
+            "uid": "conference",
+            "start": "2024-07-10T09:00:00Z"
+        }))
+        .unwrap();
+        let event = apply_changes(&event.to_ical(), &params).unwrap();
+        assert!(!event.all_day);
+        assert_eq!(event.end_time, "2024-07-10T10:00:00Z".parse().ok());
+    }
+
+    fn recurring_event() -> Event {
+        Event {
+            uid: "weekly-1".to_string(),
+            summary: Some("Sync".to_string()),
+            start_time: "2024-07-10T08:00:00Z".parse().ok(),
+            end_time: "2024-07-10T09:00:00Z".parse().ok(),
+            rrule: Some("FREQ=WEEKLY;COUNT=4".to_string()),
+            timezone: Some("Europe/Paris".to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_apply_changes_to_series() {
+        let params = UpdateCalendarEventParams::extract_params(&json!({
+            "uid": "weekly-1",
+            "start": "2024-07-11T14:00",
+            "location": "Room 2"
+        }))
+        .unwrap();
+
+        let event = apply_changes(&recurring_event().to_ical(), &params).unwrap();
+        assert_eq!(event.recurrence_id, None);
+        assert_eq!(event.rrule.as_deref(), Some("FREQ=WEEKLY;COUNT=4"));
+        assert_eq!(
+            event.start_time,
+            "2024-07-11T12:00:00Z".parse::<DateTime<Utc>>().ok()
+        );
+        assert_eq!(
+            event.end_time,
+            "2024-07-11T13:00:00Z".parse::<DateTime<Utc>>().ok()
+        );
+        assert_eq!(event.location.as_deref(), Some("Room 2"));
+    }
+
+    #[test]
+    fn test_apply_changes_to_occurrence() {
+        let params = UpdateCalendarEventParams::extract_params(&json!({
+            "uid": "weekly-1",
+            "occurrence": "2024-07-24T10:00",
+            "summary": "Sync (moved)"
+        }))
+        .unwrap();
+
+        let event = apply_changes(&recurring_event().to_ical(), &params).unwrap();
+        assert_eq!(
+            event.recurrence_id,
+            "2024-07-24T08:00:00Z".parse::<DateTime<Utc>>().ok()
+        );
+        assert_eq!(event.start_time, event.recurrence_id);
+        assert_eq!(event.rrule, None);
+        assert_eq!(event.summary.as_deref(), Some("Sync (moved)"));
+
+        let params = UpdateCalendarEventParams::extract_params(&json!({
+            "uid": "weekly-1",
+            "occurrence": "2024-07-24T10:00",
+            "rrule": "FREQ=DAILY"
+        }))
+        .unwrap();
+        assert!(apply_changes(&recurring_event().to_ical(), &params).is_err());
+
+        let mut single = recurring_event();
+        single.rrule = None;
+        let stored = single.to_ical();
+        assert!(occurrence_of(&single, &stored, Some("2024-07-24T10:00")).is_err());
+    }
+
+    #[test]
+    fn test_apply_changes_to_detached_occurrence() {
+        let series = recurring_event().to_ical();
+        let moved = UpdateCalendarEventParams::extract_params(&json!({
+            "uid": "weekly-1",
+            "occurrence": "2024-07-24T10:00",
+            "start": "2024-07-24T15:00",
+            "summary": "Sync (moved)"
+        }))
+        .unwrap();
+        let instance = apply_changes(&series, &moved)
+            .unwrap()
+            .patch_ical(&series)
+            .unwrap();
+
+        // EDS stores the detached instance along with its series
+        let stored = format!(
+            "BEGIN:VCALENDAR\r\nVERSION:2.0\r\n{}{}END:VCALENDAR\r\n",
+            series, instance
+        );
+        let located = UpdateCalendarEventParams::extract_params(&json!({
+            "uid": "weekly-1",
+            "occurrence": "2024-07-24T10:00",
+            "location": "Room 2"
+        }))
+        .unwrap();
+        let event = apply_changes(&stored, &located).unwrap();
+        assert_eq!(event.summary.as_deref(), Some("Sync (moved)"));
+        assert_eq!(
+            event.start_time,
+            "2024-07-24T13:00:00Z".parse::<DateTime<Utc>>().ok()
+        );
+        assert_eq!(event.location.as_deref(), Some("Room 2"));
+
+        let patched = event.patch_ical(&stored).unwrap();
+        assert!(patched.contains("SUMMARY:Sync (moved)\r\n"));
+        assert!(patched.contains("LOCATION:Room 2\r\n"));
+        assert!(patched.contains("DTSTART;TZID=Europe/Paris:20240724T150000\r\n"));
+    }
+
+    #[test]
+    fn test_occurrence_of_rdate_series() {
+        let stored = "BEGIN:VEVENT\r\nUID:talks-1\r\nDTSTART:20240710T080000Z\r\n\
+                      DTEND:20240710T090000Z\r\nSUMMARY:Talk\r\n\
+                      RDATE:20240717T080000Z,20240731T080000Z\r\nEND:VEVENT\r\n";
+        let event = Event::from_str(stored).unwrap();
+        assert_eq!(
+            occurrence_of(&event, stored, Some("2024-07-17T08:00:00Z")).unwrap(),
+            "2024-07-17T08:00:00Z".parse::<DateTime<Utc>>().ok()
+        );
+        let event = at_occurrence(stored, Some("2024-07-17T08:00:00Z")).unwrap();
+        assert_eq!(event.start_time, event.recurrence_id);
+    }
+
+    #[test]
+    fn test_set_participation() {
+        let mut event = recurring_event();
+        event.attendees = vec![
+            Attendee::invite("anna@example.com"),
+            Attendee {
+                status: Some("NEEDS-ACTION".to_owned()),
+                ..Attendee::invite("me@example.com")
+            },
+        ];
+
+        let (event, status, changed) =
+            set_participation(event, "Accept", "MAILTO:me@example.com").unwrap();
+        assert_eq!(status, "ACCEPTED");
+        assert!(changed);
+        assert_eq!(event.attendees[1].status.as_deref(), Some("ACCEPTED"));
+        assert!(!event.attendees[1].rsvp);
+        assert_eq!(event.attendees[0].status, None);
+        assert!(event.attendees[0].rsvp);
+
+        // Replying the same again leaves the event as it is
+        let (event, _, changed) = set_participation(event, "accept", "me@example.com").unwrap();
+        assert!(!changed);
+        assert_eq!(event.attendees[1].status.as_deref(), Some("ACCEPTED"));
+
+        assert!(set_participation(event.clone(), "maybe", "me@example.com").is_err());
+        assert!(set_participation(event, "decline", "bob@example.com").is_err());
+    }
+}
@@ -0,0 +1,360 @@
+use std::str::FromStr;
+
+use anyhow::Result;
+use serde_json::json;
+
+use crate::{
+    gnome::evolution::{Contact, ContactDate, SourceFilter, TypedValue},
+    mcp::ToolProvider,
+    tool_params,
+};
+
+#[derive(Default)]
+pub struct CreateContact;
+
+#[derive(Default)]
+pub struct UpdateContact;
+
+#[derive(Default)]
+pub struct DeleteContact;
+
+tool_params! {
+    CreateContactParams,
+    required(full_name: string, "Full name of the contact"),
+    optional(emails: string, "Comma-separated email addresses, each optionally prefixed with its types (work:anna@work.com, home/pref:anna@example.com)"),
+    optional(phones: string, "Comma-separated phone numbers, each optionally prefixed with its types (cell/pref:+1 555 0100)"),
+    optional(organization: string, "Organization"),
+    optional(title: string, "Job title"),
+    optional(nickname: string, "Nickname"),
+    optional(birthday: string, "Birthday as YYYY-MM-DD, or --MM-DD when the year is unknown"),
+    optional(note: string, "Free-form note"),
+    optional(address_book: string, "Address book UID or display name (default: the default address book)")
+}
+
+impl ToolProvider for CreateContact {
+    const NAME: &'static str = "create_contact";
+    const DESCRIPTION: &'static str =
+        "Create a contact in a writable Evolution Data Server address book and return its UID";
+    type Params = CreateContactParams;
+
+    async fn execute_with_params(&self, params: Self::Params) -> Result<serde_json::Value> {
+        Self::execute_with_result(|| async {
+            let contact = new_contact(&params)?;
+            let uid = contact.create(params.address_book.as_deref()).await?;
+            Ok(json!({
+                "uid": uid,
+                "full_name": contact.full_name
+            }))
+        })
+        .await
+    }
+
+    async fn dry_run_with_params(&self, params: Self::Params) -> Result<serde_json::Value> {
+        let contact = new_contact(&params)?;
+        let connection = zbus::Connection::session().await?;
+        let (source, _proxy) =
+            Contact::writable_address_book(&connection, params.address_book.as_deref()).await?;
+        Ok(json!({
+            "description": format!(
+                "Would create contact '{}' in {}",
+                params.full_name, source.display_name
+            ),
+            "address_book": source.display_name,
+            "contact": contact.to_json()
+        }))
+    }
+}
+
+tool_params! {
+    UpdateContactParams,
+    required(uid: string, "UID of the contact to change"),
+    optional(full_name: string, "New full name"),
+    optional(emails: string, "Comma-separated email addresses, each optionally prefixed with its types (work:anna@work.com), replacing the current ones"),
+    optional(phones: string, "Comma-separated phone numbers, each optionally prefixed with its types (cell/pref:+1 555 0100), replacing the current ones"),
+    optional(organization: string, "Organization, empty to clear it"),
+    optional(title: string, "Job title, empty to clear it"),
+    optional(nickname: string, "Nickname, empty to clear it"),
+    optional(birthday: string, "Birthday as YYYY-MM-DD, or --MM-DD when the year is unknown, empty to clear it"),
+    optional(note: string, "Free-form note, empty to clear it"),
+    optional(address_book: string, "Address book UID or display name holding the contact (default: search all address books)")
+}
+
+impl ToolProvider for UpdateContact {
+    const NAME: &'static str = "update_contact";
+    const DESCRIPTION: &'static str = "Edit a contact in an Evolution Data Server address book";
+    type Params = UpdateContactParams;
+
+    async fn execute_with_params(&self, params: Self::Params) -> Result<serde_json::Value> {
+        Self::execute_with_result(|| async {
+            let connection = zbus::Connection::session().await?;
+            let (source, proxy, stored) = Contact::find(
+                &connection,
+                &params.uid,
+                &SourceFilter::only(params.address_book.as_deref()),
+            )
+            .await?;
+            let contact = apply_changes(Contact::from_str(&stored)?, &params)?;
+            contact.modify(&source, &proxy, &stored).await?;
+            Ok(json!({
+                "uid": contact.uid,
+                "address_book": source.display_name,
+                "contact": contact.to_json()
+            }))
+        })
+        .await
+    }
+
+    async fn dry_run_with_params(&self, params: Self::Params) -> Result<serde_json::Value> {
+        let connection = zbus::Connection::session().await?;
+        let (source, proxy, stored) = Contact::find(
+            &connection,
+            &params.uid,
+            &SourceFilter::only(params.address_book.as_deref()),
+        )
+        .await?;
+        crate::gnome::evolution::ensure_writable(&proxy, &source).await?;
+        let contact = apply_changes(Contact::from_str(&stored)?, &params)?;
+        Ok(json!({
+            "description": format!(
+                "Would update contact '{}' in {}",
+                contact.full_name.as_deref().unwrap_or(&contact.uid),
+                source.display_name
+            ),
+            "address_book": source.display_name,
+            "contact": contact.to_json()
+        }))
+    }
+}
+
+tool_params! {
+    DeleteContactParams,
+    required(uid: string, "UID of the contact to delete"),
+    optional(address_book: string, "Address book UID or display name holding the contact (default: search all address books)")
+}
+
+impl ToolProvider for DeleteContact {
+    const NAME: &'static str = "delete_contact";
+    const DESCRIPTION: &'static str = "Delete a contact from an Evolution Data Server address book";
+    type Params = DeleteContactParams;
+
+    async fn execute_with_params(&self, params: Self::Params) -> Result<serde_json::Value> {
+        Self::execute_with_result(|| async {
+            let connection = zbus::Connection::session().await?;
+            let (source, proxy, stored) = Contact::find(
+                &connection,
+                &params.uid,
+                &SourceFilter::only(params.address_book.as_deref()),
+            )
+            .await?;
+            let contact = Contact::from_str(&stored)?;
+            contact.remove(&source, &proxy).await?;
+            Ok(json!({
+                "uid": contact.uid,
+                "address_book": source.display_name
+            }))
+        })
+        .await
+    }
+
+    async fn dry_run_with_params(&self, params: Self::Params) -> Result<serde_json::Value> {
+        let connection = zbus::Connection::session().await?;
+        let (source, proxy, stored) = Contact::find(
+            &connection,
+            &params.uid,
+            &SourceFilter::only(params.address_book.as_deref()),
+        )
+        .await?;
+        crate::gnome::evolution::ensure_writable(&proxy, &source).await?;
+        let contact = Contact::from_str(&stored)?;
+        Ok(json!({
+            "description": format!(
+                "Would delete contact '{}' from {}",
+                contact.full_name.as_deref().unwrap_or(&contact.uid),
+                source.display_name
+            ),
+            "address_book": source.display_name,
+            "contact": contact.to_json()
+        }))
+    }
+}
+
+fn new_contact(params: &CreateContactParams) -> Result<Contact> {
+    if params.full_name.trim().is_empty() {
+        anyhow::bail!("full_name cannot be empty");
+    }
+
+    Ok(Contact {
+        full_name: Some(params.full_name.trim().to_string()),
+        emails: params
+            .emails
+            .as_deref()
+            .map(|emails| parse_typed_list(emails, &[]))
+            .unwrap_or_default(),
+        phones: params
+            .phones
+            .as_deref()
+            .map(|phones| parse_typed_list(phones, &[]))
+            .unwrap_or_default(),
+        organization: non_empty(params.organization.as_deref()),
+        title: non_empty(params.title.as_deref()),
+        nickname: non_empty(params.nickname.as_deref()),
+        birthday: params
+            .birthday
+            .as_deref()
+            .map(ContactDate::from_str)
+            .transpose()?,
+        note: non_empty(params.note.as_deref()),
+        ..Default::default()
+    })
+}
+
+/// Apply the update parameters to a stored contact
+fn apply_changes(mut contact: Contact, params: &UpdateContactParams) -> Result<Contact> {
+    if let Some(full_name) = &params.full_name {
+        if full_name.trim().is_empty() {
+            anyhow::bail!("full_name cannot be empty");
+        }
+        contact.full_name = Some(full_name.trim().to_string());
+    }
+    if let Some(emails) = &params.emails {
+        contact.emails = parse_typed_list(emails, &contact.emails);
+    }
+    if let Some(phones) = &params.phones {
+        contact.phones = parse_typed_list(phones, &contact.phones);
+    }
+    if params.organization.is_some() {
+        contact.organization = non_empty(params.organization.as_deref());
+    }
+    if params.title.is_some() {
+        contact.title = non_empty(params.title.as_deref());
+    }
+    if params.nickname.is_some() {
+        contact.nickname = non_empty(params.nickname.as_deref());
+    }
+    if let Some(birthday) = &params.birthday {
+        contact.birthday = match birthday.as_str() {
+            "" => None,
+            birthday => Some(ContactDate::from_str(birthday)?),
+        };
+    }
+    if params.note.is_some() {
+        contact.note = non_empty(params.note.as_deref());
+    }
+    Ok(contact)
+}
+
+fn non_empty(value: Option<&str>) -> Option<String> {
+    value
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .map(ToOwned::to_owned)
+}
+
+/// Parse a comma-separated list of values, each optionally prefixed with
+/// slash-separated types ("work/pref:anna@work.com"). A value given without
+/// types keeps the ones it already had in `current`.
+fn parse_typed_list(value: &str, current: &[TypedValue]) -> Vec<TypedValue> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(|item| match item.split_once(':') {
+            Some((kinds, value)) if is_type_prefix(kinds) => {
+                let mut typed = TypedValue::new(value.trim());
+                for kind in kinds.split('/').filter(|kind| !kind.is_empty()) {
+                    match kind.eq_ignore_ascii_case("pref") {
+                        true => typed.preferred = true,
+                        false => typed.types.push(kind.to_ascii_uppercase()),
+                    }
+                }
+                typed
+            }
+            _ => current
+                .iter()
+                .find(|typed| typed.value == item)
+                .cloned()
+                .unwrap_or_else(|| TypedValue::new(item)),
+        })
+        .filter(|typed| !typed.value.is_empty())
+        .collect()
+}
+
+/// Whether the text before a colon lists types rather than being part of
+/// the value, as in a "tel:" or "mailto:" URI
+fn is_type_prefix(kinds: &str) -> bool {
+    !kinds.is_empty()
+        && kinds.chars().all(|c| c.is_ascii_alphabetic() || c == '/')
+        && !["tel", "mailto", "sip", "xmpp"]
+            .iter()
+            .any(|scheme| kinds.eq_ignore_ascii_case(scheme))
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::mcp::ToolParams;
+
+    #[test]
+    fn test_new_contact() {
+        let params = CreateContactParams::extract_params(&json!({
+            "full_name": "Anna Smith",
+            "emails": "home/pref:anna@example.com, work:anna@work.com",
+            "birthday": "1990-04-02"
+        }))
+        .unwrap();
+
+        let contact = new_contact(&params).unwrap();
+        assert_eq!(contact.full_name.as_deref(), Some("Anna Smith"));
+        assert_eq!(contact.emails.len(), 2);
+        assert_eq!(contact.emails[0].value, "anna@example.com");
+        assert_eq!(contact.emails[0].types, ["HOME"]);
+        assert!(contact.emails[0].preferred);
+        assert_eq!(contact.emails[1].value, "anna@work.com");
+        assert_eq!(contact.emails[1].types, ["WORK"]);
+        assert!(!contact.emails[1].preferred);
+        assert_eq!(contact.birthday, ContactDate::new(Some(1990), 4, 2));
+        let vcard = contact.to_vcard();
+        assert!(vcard.contains("BDAY:1990-04-02\r\n"));
+        assert!(vcard.contains("EMAIL;TYPE=HOME,PREF:anna@example.com\r\n"));
+        assert!(vcard.contains("EMAIL;TYPE=WORK:anna@work.com\r\n"));
+
+        let params = CreateContactParams::extract_params(&json!({
+            "full_name": "Anna Smith",
+            "birthday": "April 2nd"
+        }))
+        .unwrap();
+        assert!(new_contact(&params).is_err());
+    }
+
+    #[test]
+    fn test_apply_changes() {
+        let stored = Contact {
+            uid: "c-1".to_string(),
+            full_name: Some("Anna Smith".to_string()),
+            emails: vec![TypedValue {
+                value: "anna@example.com".to_string(),
+                types: vec!["HOME".to_string()],
+                preferred: true,
+            }],
+            title: Some("Engineer".to_string()),
+            ..Default::default()
+        };
+        let params = UpdateContactParams::extract_params(&json!({
+            "uid": "c-1",
+            "emails": "anna@example.com, work:anna@work.com",
+            "phones": "tel:+1 555 0100",
+            "title": ""
+        }))
+        .unwrap();
+
+        let contact = apply_changes(stored, &params).unwrap();
+        // Unchanged addresses keep their types
+        assert_eq!(contact.emails[0].types, ["HOME"]);
+        assert!(contact.emails[0].preferred);
+        assert_eq!(contact.emails[1].types, ["WORK"]);
+        assert_eq!(contact.phones, [TypedValue::new("tel:+1 555 0100")]);
+        assert_eq!(contact.title, None);
+    }
+}
@@ -0,0 +1,354 @@
+use anyhow::Result;
+use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveTime, TimeZone, Utc, Weekday};
+use serde_json::json;
+
+use crate::{
+    gnome::evolution::{local_timezone, Event, SourceResults},
+    mcp::ToolProvider,
+    tool_params,
+};
+
+#[derive(Default)]
+pub struct FindFreeSlots;
+
+tool_params! {
+    FindFreeSlotsParams,
+    required(duration_minutes: i64, "Length of the slot to find, in minutes"),
+    optional(from: string = "", "First day to search, YYYY-MM-DD (default: today)"),
+    optional(days: i64 = 7, "Number of days to search, up to 62 (default: 7)"),
+    optional(work_start: string = "09:00", "Start of the working day in local time, HH:MM (default: 09:00)"),
+    optional(work_end: string = "17:00", "End of the working day in local time, HH:MM (default: 17:00)"),
+    optional(include_weekends: bool = false, "Also search Saturdays and Sundays (default: false)"),
+    optional(max_results: i64 = 10, "Maximum number of free slots to return (default: 10)")
+}
+
+impl ToolProvider for FindFreeSlots {
+    const NAME: &'static str = "find_free_slots";
+    const DESCRIPTION: &'static str = "Find free time slots within working hours across all calendars, along with the busy periods in that range";
+    type Params = FindFreeSlotsParams;
+
+    async fn execute_with_params(&self, params: Self::Params) -> Result<serde_json::Value> {
+        Self::execute_with_result(|| async {
+            let local = local_timezone().await;
+            let search = Search::from_params(&params, local, Utc::now())?;
+            let sources = crate::config::CONFIG.get_calendar_config().sources;
+            let SourceResults {
+                items: events,
+                errors,
+            } = Event::all(search.start, search.end, &sources).await?;
+
+            let busy = busy_intervals(&events, local);
+            let slots = search.free_slots(&busy);
+            let format = |time: DateTime<Utc>| time.with_timezone(&local).to_rfc3339();
+            Ok(json!({
+                "timezone": local.name(),
+                "range": {
+                    "start": format(search.start),
+                    "end": format(search.end)
+                },
+                "busy": busy
+                    .iter()
+                    .filter(|busy| busy.end > search.start && busy.start < search.end)
+                    .map(|busy| json!({
+                        "start": format(busy.start),
+                        "end": format(busy.end)
+                    }))
+                    .collect::<Vec<_>>(),
+                "slots": slots
+                    .iter()
+                    .map(|free| json!({
+                        "start": format(free.start),
+                        "end": format(free.start + search.duration),
+                        "free_until": format(free.end)
+                    }))
+                    .collect::<Vec<_>>(),
+                "count": slots.len(),
+                // Slots may overlap events of the calendars that failed
+                "source_errors": errors
+            }))
+        })
+        .await
+    }
+
+    async fn dry_run_with_params(&self, params: Self::Params) -> Result<serde_json::Value> {
+        let local = local_timezone().await;
+        let search = Search::from_params(&params, local, Utc::now())?;
+        Ok(json!({
+            "description": format!(
+                "Would look for {} minute slots between {} and {}",
+                search.duration.num_minutes(),
+                search.start.with_timezone(&local).to_rfc3339(),
+                search.end.with_timezone(&local).to_rfc3339()
+            ),
+            "timezone": local.name()
+        }))
+    }
+}
+
+/// A period of time, from `start` to the exclusive `end`
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Interval {
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+}
+
+/// Merged periods in which the events keep the user busy. Transparent and
+/// cancelled events are ignored and all-day events block whole local days.
+fn busy_intervals(events: &[Event], local: chrono_tz::Tz) -> Vec<Interval> {
+    let mut intervals = events
+        .iter()
+        .filter(|event| {
+            !event
+                .transparency
+                .as_deref()
+                .is_some_and(|transparency| transparency.eq_ignore_ascii_case("TRANSPARENT"))
+                && !event
+                    .status
+                    .as_deref()
+                    .is_some_and(|status| status.eq_ignore_ascii_case("CANCELLED"))
+        })
+        .filter_map(|event| {
+            let start = event.start_time?;
+            let (start, end) = if event.all_day {
+                let end = event.end_time.unwrap_or(start + Duration::days(1));
+                (
+                    local_midnight(start.date_naive(), local),
+                    local_midnight(end.date_naive(), local),
+                )
+            } else {
+                (start, event.end_time.unwrap_or(start))
+            };
+            (end > start).then_some(Interval { start, end })
+        })
+        .collect::<Vec<_>>();
+    intervals.sort_by_key(|interval| interval.start);
+
+    let mut merged: Vec<Interval> = Vec::with_capacity(intervals.len());
+    for interval in intervals {
+        match merged.last_mut() {
+            Some(last) if interval.start <= last.end => last.end = last.end.max(interval.end),
+            _ => merged.push(interval),
+        }
+    }
+    merged
+}
+
+pub(crate) fn local_midnight(date: NaiveDate, local: chrono_tz::Tz) -> DateTime<Utc> {
+    local_time(date, NaiveTime::MIN, local)
+}
+
+/// `time` on `date` in the local timezone, moved past a DST gap if needed
+fn local_time(date: NaiveDate, time: NaiveTime, local: chrono_tz::Tz) -> DateTime<Utc> {
+    let naive = date.and_time(time);
+    local
+        .from_local_datetime(&naive)
+        .earliest()
+        .or_else(|| {
+            local
+                .from_local_datetime(&(naive + Duration::hours(1)))
+                .earliest()
+        })
+        .map_or_else(|| naive.and_utc(), |time| time.with_timezone(&Utc))
+}
+
+/// What to look for, resolved from the tool parameters
+#[derive(Debug)]
+struct Search {
+    duration: Duration,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    first_day: NaiveDate,
+    days: i64,
+    work_start: NaiveTime,
+    work_end: NaiveTime,
+    include_weekends: bool,
+    max_results: usize,
+    not_before: DateTime<Utc>,
+    local: chrono_tz::Tz,
+}
+
+impl Search {
+    fn from_params(
+        params: &FindFreeSlotsParams,
+        local: chrono_tz::Tz,
+        now: DateTime<Utc>,
+    ) -> Result<Self> {
+        if params.duration_minutes <= 0 {
+            anyhow::bail!("duration_minutes must be positive");
+        }
+        if !(1..=62).contains(&params.days) {
+            anyhow::bail!("days must be between 1 and 62");
+        }
+        if params.max_results < 1 {
+            anyhow::bail!("max_results must be at least 1");
+        }
+
+        let first_day = match params.from.as_str() {
+            "" => now.with_timezone(&local).date_naive(),
+            from => NaiveDate::parse_from_str(from, "%Y-%m-%d")
+                .map_err(|_| anyhow::anyhow!("Invalid date '{}', expected YYYY-MM-DD", from))?,
+        };
+        let work_start = parse_time(&params.work_start)?;
+        let work_end = parse_time(&params.work_end)?;
+        if work_end <= work_start {
+            anyhow::bail!("work_end must be after work_start");
+        }
+
+        Ok(Self {
+            duration: Duration::minutes(params.duration_minutes),
+            start: local_midnight(first_day, local),
+            end: local_midnight(first_day + Duration::days(params.days), local),
+            first_day,
+            days: params.days,
+            work_start,
+            work_end,
+            include_weekends: params.include_weekends,
+            max_results: params.max_results as usize,
+            not_before: now,
+            local,
+        })
+    }
+
+    /// Free periods long enough for the duration, within working hours and
+    /// not in the past, earliest first
+    fn free_slots(&self, busy: &[Interval]) -> Vec<Interval> {
+        let mut slots = Vec::new();
+        let days = (0..self.days)
+            .map(|offset| self.first_day + Duration::days(offset))
+            .filter(|day| {
+                self.include_weekends || !matches!(day.weekday(), Weekday::Sat | Weekday::Sun)
+            });
+
+        for day in days {
+            let day_start = local_time(day, self.work_start, self.local).max(self.not_before);
+            let day_end = local_time(day, self.work_end, self.local);
+
+            let mut start = day_start;
+            for interval in busy
+                .iter()
+                .filter(|busy| busy.end > day_start && busy.start < day_end)
+            {
+                if interval.start - start >= self.duration {
+                    slots.push(Interval {
+                        start,
+                        end: interval.start,
+                    });
+                }
+                start = start.max(interval.end);
+            }
+            if day_end - start >= self.duration {
+                slots.push(Interval {
+                    start,
+                    end: day_end,
+                });
+            }
+
+            if slots.len() >= self.max_results {
+                slots.truncate(self.max_results);
+                break;
+            }
+        }
+        slots
+    }
+}
+
+fn parse_time(value: &str) -> Result<NaiveTime> {
+    NaiveTime::parse_from_str(value, "%H:%M")
+        .map_err(|_| anyhow::anyhow!("Invalid time '{}', expected HH:MM", value))
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::mcp::ToolParams;
+
+    fn event(start: &str, end: &str) -> Event {
+        Event {
+            start_time: start.parse().ok(),
+            end_time: end.parse().ok(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_busy_intervals() {
+        let local = chrono_tz::Europe::Paris;
+        let mut transparent = event("2024-07-10T06:00:00Z", "2024-07-10T16:00:00Z");
+        transparent.transparency = Some("TRANSPARENT".to_string());
+        let mut cancelled = event("2024-07-10T06:00:00Z", "2024-07-10T16:00:00Z");
+        cancelled.status = Some("CANCELLED".to_string());
+        let mut holiday = event("2024-07-12T00:00:00Z", "2024-07-13T00:00:00Z");
+        holiday.all_day = true;
+
+        let busy = busy_intervals(
+            &[
+                event("2024-07-10T09:00:00Z", "2024-07-10T10:00:00Z"),
+                event("2024-07-10T07:00:00Z", "2024-07-10T08:00:00Z"),
+                event("2024-07-10T07:30:00Z", "2024-07-10T09:00:00Z"),
+                transparent,
+                cancelled,
+                holiday,
+            ],
+            local,
+        );
+
+        assert_eq!(
+            busy,
+            [
+                Interval {
+                    start: "2024-07-10T07:00:00Z".parse().unwrap(),
+                    end: "2024-07-10T10:00:00Z".parse().unwrap(),
+                },
+                // The holiday spans the day in local time
+                Interval {
+                    start: "2024-07-11T22:00:00Z".parse().unwrap(),
+                    end: "2024-07-12T22:00:00Z".parse().unwrap(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_free_slots() {
+        let params = FindFreeSlotsParams::extract_params(&json!({
+            "duration_minutes": 60,
+            "from": "2024-07-12",
+            "days": 4
+        }))
+        .unwrap();
+        let now = "2024-07-12T15:45:00Z".parse().unwrap();
+        let search = Search::from_params(&params, chrono_tz::UTC, now).unwrap();
+
+        let busy = [
+            Interval {
+                start: "2024-07-12T10:00:00Z".parse().unwrap(),
+                end: "2024-07-12T15:30:00Z".parse().unwrap(),
+            },
+            Interval {
+                start: "2024-07-15T09:00:00Z".parse().unwrap(),
+                end: "2024-07-15T17:00:00Z".parse().unwrap(),
+            },
+        ];
+        let slots = search.free_slots(&busy);
+
+        // The morning of Friday is past, the weekend is skipped and Monday
+        // is fully booked
+        assert_eq!(
+            slots,
+            [Interval {
+                start: "2024-07-12T15:45:00Z".parse().unwrap(),
+                end: "2024-07-12T17:00:00Z".parse().unwrap(),
+            }]
+        );
+
+        let params = FindFreeSlotsParams::extract_params(&json!({
+            "duration_minutes": 30,
+            "work_start": "18:00",
+            "work_end": "09:00"
+        }))
+        .unwrap();
+        assert!(Search::from_params(&params, chrono_tz::UTC, now).is_err());
+    }
+}
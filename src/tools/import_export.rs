@@ -0,0 +1,455 @@
+use std::{io::Write, path::PathBuf};
+
+use anyhow::{Context, Result};
+use chrono::Utc;
+use gio::glib;
+use serde_json::{json, Value};
+
+use crate::{
+    gnome::evolution::{
+        files::{self, CalendarObject, ComponentKind},
+        local_timezone, CalendarQuery, ComponentField, Contact, ContactField, ContactQuery, Event,
+        SourceResults, SourceType, Task,
+    },
+    mcp::ToolProvider,
+    tool_params,
+    tools::search::event_range,
+};
+
+#[derive(Default)]
+pub struct ImportCalendarFile;
+
+#[derive(Default)]
+pub struct ImportContactsFile;
+
+#[derive(Default)]
+pub struct ExportToFile;
+
+tool_params! {
+    ImportCalendarFileParams,
+    required(path: string, "Absolute path of the .ics file"),
+    optional(calendar: string, "Calendar UID or display name receiving the events (default: the default calendar)"),
+    optional(task_list: string, "Task list UID or display name receiving the tasks (default: the default task list)"),
+    optional(replace: bool, "Overwrite events and tasks whose UID is already stored instead of skipping them (default: false)")
+}
+
+impl ToolProvider for ImportCalendarFile {
+    const NAME: &'static str = "import_calendar_file";
+    const DESCRIPTION: &'static str = "Import the events and tasks of an .ics file into Evolution Data Server, skipping the ones already stored";
+    type Params = ImportCalendarFileParams;
+
+    async fn execute_with_params(&self, params: Self::Params) -> Result<serde_json::Value> {
+        Self::execute_with_result(|| import_calendar(&params, false)).await
+    }
+
+    async fn dry_run_with_params(&self, params: Self::Params) -> Result<serde_json::Value> {
+        let mut result = import_calendar(&params, true).await?;
+        result["description"] = format!("Would import {}", params.path).into();
+        Ok(result)
+    }
+}
+
+tool_params! {
+    ImportContactsFileParams,
+    required(path: string, "Absolute path of the .vcf file"),
+    optional(address_book: string, "Address book UID or display name (default: the default address book)"),
+    optional(replace: bool, "Overwrite contacts whose UID is already stored instead of skipping them (default: false)")
+}
+
+impl ToolProvider for ImportContactsFile {
+    const NAME: &'static str = "import_contacts_file";
+    const DESCRIPTION: &'static str = "Import the vCards of a .vcf file into an Evolution Data Server address book, skipping the ones already stored";
+    type Params = ImportContactsFileParams;
+
+    async fn execute_with_params(&self, params: Self::Params) -> Result<serde_json::Value> {
+        Self::execute_with_result(|| import_contacts(&params, false)).await
+    }
+
+    async fn dry_run_with_params(&self, params: Self::Params) -> Result<serde_json::Value> {
+        let mut result = import_contacts(&params, true).await?;
+        result["description"] = format!("Would import {}", params.path).into();
+        Ok(result)
+    }
+}
+
+tool_params! {
+    ExportToFileParams,
+    required(kind: string, "What to export: 'events', 'tasks' or 'contacts'"),
+    required(path: string, "Absolute path of the .ics or .vcf file to write"),
+    optional(text: string, "Only items with a text field containing this"),
+    optional(category: string, "Only items in this category"),
+    optional(from: string, "Only events occurring from this date (YYYY-MM-DD) or time (default: all events, unless to is given)"),
+    optional(to: string, "Only events occurring until this date, inclusive, or time"),
+    optional(source: string, "Calendar, task list or address book UID or display name (default: the ones of the matching resource)"),
+    optional(overwrite: bool, "Replace the file if it exists (default: false)")
+}
+
+impl ToolProvider for ExportToFile {
+    const NAME: &'static str = "export_to_file";
+    const DESCRIPTION: &'static str =
+        "Export Evolution Data Server events or tasks to an .ics file, or contacts to a .vcf file";
+    type Params = ExportToFileParams;
+
+    async fn execute_with_params(&self, params: Self::Params) -> Result<serde_json::Value> {
+        Self::execute_with_result(|| async {
+            let path = export_path(&params.kind, &params.path)?;
+            let (content, count, errors) = export(&params).await?;
+
+            let mut file = std::fs::OpenOptions::new()
+                .write(true)
+                .create(true)
+                .truncate(true)
+                .create_new(!params.overwrite.unwrap_or(false))
+                .open(&path)
+                .with_context(|| format!("Failed to create {}", path.display()))?;
+            file.write_all(content.as_bytes())
+                .with_context(|| format!("Failed to write {}", path.display()))?;
+
+            Ok(json!({
+                "path": path,
+                "count": count,
+                "source_errors": errors
+            }))
+        })
+        .await
+    }
+
+    async fn dry_run_with_params(&self, params: Self::Params) -> Result<serde_json::Value> {
+        let path = export_path(&params.kind, &params.path)?;
+        if path.exists() && !params.overwrite.unwrap_or(false) {
+            anyhow::bail!("{} already exists", path.display());
+        }
+        let (_content, count, errors) = export(&params).await?;
+        Ok(json!({
+            "description": format!("Would write {} {} to {}", count, params.kind, path.display()),
+            "path": path,
+            "count": count,
+            "source_errors": errors
+        }))
+    }
+}
+
+/// An absolute path, where a leading `~/` stands for the home directory
+fn file_path(path: &str) -> Result<PathBuf> {
+    let path = match path.strip_prefix("~/") {
+        Some(relative) => glib::home_dir().join(relative),
+        None => PathBuf::from(path),
+    };
+    if !path.is_absolute() {
+        anyhow::bail!("Expected an absolute path, got '{}'", path.display());
+    }
+    Ok(path)
+}
+
+/// The file to export `kind` to: an absolute path with the extension of
+/// the format, and a regular file if it already exists, so an export can't
+/// replace an unrelated file
+fn export_path(kind: &str, path: &str) -> Result<PathBuf> {
+    let path = file_path(path)?;
+    let extension = match kind {
+        "events" | "tasks" => "ics",
+        "contacts" => "vcf",
+        _ => anyhow::bail!(
+            "Unknown kind '{}'. Available: events, tasks, contacts",
+            kind
+        ),
+    };
+    if !path
+        .extension()
+        .is_some_and(|e| e.eq_ignore_ascii_case(extension))
+    {
+        anyhow::bail!("{} must be exported to a .{} file", kind, extension);
+    }
+    match std::fs::symlink_metadata(&path) {
+        Ok(metadata) if !metadata.file_type().is_file() => {
+            anyhow::bail!("{} is not a regular file", path.display())
+        }
+        _ => Ok(path),
+    }
+}
+
+fn read_file(path: &str) -> Result<String> {
+    let path = file_path(path)?;
+    std::fs::read_to_string(&path).with_context(|| format!("Failed to read {}", path.display()))
+}
+
+/// Outcome of importing one item, as reported to the client
+fn import_entry(
+    uid: &str,
+    summary: Option<&str>,
+    kind: &str,
+    source: &str,
+    result: Result<&str>,
+) -> Value {
+    let mut entry = json!({
+        "uid": uid,
+        "summary": summary,
+        "type": kind,
+        "source": source,
+    });
+    match result {
+        Ok(result) => entry["result"] = result.into(),
+        Err(err) => {
+            entry["result"] = "failed".into();
+            entry["error"] = format!("{:#}", err).into();
+        }
+    }
+    entry
+}
+
+/// What was done with each item, plus the count of each outcome
+fn import_summary(items: Vec<Value>) -> Value {
+    let count = |result: &str| items.iter().filter(|item| item["result"] == result).count();
+    json!({
+        "imported": count("imported") + count("would_import"),
+        "replaced": count("replaced") + count("would_replace"),
+        "skipped_duplicates": count("skipped_duplicate"),
+        "failed": count("failed"),
+        "items": items
+    })
+}
+
+/// Store each series of the file with `ReceiveObjects`, which also adds the
+/// timezones it uses, unless its UID exists and `replace` isn't set
+async fn import_calendar(params: &ImportCalendarFileParams, dry_run: bool) -> Result<Value> {
+    let objects = files::split_calendar(&read_file(&params.path)?)?;
+    let replace = params.replace.unwrap_or(false);
+    let connection = zbus::Connection::session().await?;
+
+    let has = |kind| objects.iter().any(|object| object.kind == kind);
+    let calendar = match has(ComponentKind::Event) {
+        true => Some(Event::writable_calendar(&connection, params.calendar.as_deref()).await?),
+        false => None,
+    };
+    let task_list = match has(ComponentKind::Task) {
+        true => Some(Task::writable_task_list(&connection, params.task_list.as_deref()).await?),
+        false => None,
+    };
+
+    let mut items = Vec::new();
+    for CalendarObject {
+        uid,
+        kind,
+        summary,
+        ical,
+    } in &objects
+    {
+        let (target, name) = match kind {
+            ComponentKind::Event => (&calendar, "event"),
+            ComponentKind::Task => (&task_list, "task"),
+        };
+        let Some((source, proxy)) = target else {
+            continue;
+        };
+
+        let exists = proxy
+            .call_method("GetObject", &(uid.as_str(), ""))
+            .await
+            .is_ok();
+        let result = match (exists, replace, dry_run) {
+            (true, false, _) => Ok("skipped_duplicate"),
+            (true, true, true) => Ok("would_replace"),
+            (false, _, true) => Ok("would_import"),
+            (exists, _, false) => proxy
+                .call_method("ReceiveObjects", &(ical.as_str(), 0u32))
+                .await
+                .map(|_| if exists { "replaced" } else { "imported" })
+                .map_err(anyhow::Error::from),
+        };
+        items.push(import_entry(
+            uid,
+            summary.as_deref(),
+            name,
+            &source.display_name,
+            result,
+        ));
+    }
+
+    Ok(import_summary(items))
+}
+
+/// Create each vCard of the file, unless its UID exists and `replace`
+/// isn't set
+async fn import_contacts(params: &ImportContactsFileParams, dry_run: bool) -> Result<Value> {
+    let cards = files::split_vcards(&read_file(&params.path)?)?;
+    let replace = params.replace.unwrap_or(false);
+    let connection = zbus::Connection::session().await?;
+    let (source, proxy) =
+        Contact::writable_address_book(&connection, params.address_book.as_deref()).await?;
+
+    let mut items = Vec::new();
+    for (contact, vcard) in &cards {
+        let exists = !contact.uid.is_empty()
+            && proxy
+                .call_method("GetContact", &(contact.uid.as_str(),))
+                .await
+                .is_ok();
+        let result = match (exists, replace, dry_run) {
+            (true, false, _) => Ok("skipped_duplicate"),
+            (true, true, true) => Ok("would_replace"),
+            (false, _, true) => Ok("would_import"),
+            (true, true, false) => proxy
+                .call_method("ModifyContacts", &(vec![vcard.as_str()], 0u32))
+                .await
+                .map(|_| "replaced")
+                .map_err(anyhow::Error::from),
+            (false, _, false) => proxy
+                .call_method("CreateContacts", &(vec![vcard.as_str()], 0u32))
+                .await
+                .map(|_| "imported")
+                .map_err(anyhow::Error::from),
+        };
+        items.push(import_entry(
+            &contact.uid,
+            contact.full_name.as_deref(),
+            "contact",
+            &source.display_name,
+            result,
+        ));
+    }
+
+    Ok(import_summary(items))
+}
+
+/// The file content for the export, the number of items it holds and the
+/// sources that couldn't be read
+async fn export(params: &ExportToFileParams) -> Result<(String, usize, Value)> {
+    let text = params.text.clone().filter(|text| !text.is_empty());
+    let category = params.category.clone().filter(|c| !c.is_empty());
+    let source = params.source.as_deref();
+    let has_range = params.from.is_some() || params.to.is_some();
+    let config = &crate::config::CONFIG;
+
+    let calendar_query = |range: Option<(chrono::DateTime<Utc>, chrono::DateTime<Utc>)>| {
+        let mut query = CalendarQuery::All;
+        if let Some((start, end)) = range {
+            query = query.and(CalendarQuery::OccursIn(start, end));
+        }
+        if let Some(text) = &text {
+            query = query.and(CalendarQuery::Contains(ComponentField::Any, text.clone()));
+        }
+        if let Some(category) = &category {
+            query = query.and(CalendarQuery::HasCategory(category.clone()));
+        }
+        query.to_string()
+    };
+
+    let (results, marker) = match params.kind.as_str() {
+        "events" => {
+            let range = match has_range {
+                true => Some(event_range(
+                    params.from.as_deref(),
+                    params.to.as_deref(),
+                    local_timezone().await,
+                    Utc::now(),
+                )?),
+                false => None,
+            };
+            let sources = config.get_calendar_config().sources.narrowed(source);
+            let results = crate::gnome::evolution::stored_objects(
+                &sources,
+                |kind| matches!(kind, SourceType::Calendar { .. }),
+                calendar_query(range),
+            )
+            .await?;
+            (results, "BEGIN:VEVENT")
+        }
+        "tasks" | "contacts" if has_range => {
+            anyhow::bail!("from and to only apply to events")
+        }
+        "tasks" => {
+            let sources = config.get_tasks_config().sources.narrowed(source);
+            let results = crate::gnome::evolution::stored_objects(
+                &sources,
+                |kind| matches!(kind, SourceType::TaskList { .. }),
+                calendar_query(None),
+            )
+            .await?;
+            (results, "BEGIN:VTODO")
+        }
+        "contacts" => {
+            let mut query = ContactQuery::All;
+            if let Some(text) = &text {
+                query = query.and(ContactQuery::Contains(ContactField::AnyField, text.clone()));
+            }
+            if let Some(category) = &category {
+                query = query.and(ContactQuery::Contains(
+                    ContactField::Category,
+                    category.clone(),
+                ));
+            }
+            let sources = config.get_contacts_config().sources.narrowed(source);
+            let results = crate::gnome::evolution::stored_objects(
+                &sources,
+                |kind| matches!(kind, SourceType::AddressBook { .. }),
+                query.to_string(),
+            )
+            .await?;
+            (results, "BEGIN:VCARD")
+        }
+        kind => anyhow::bail!(
+            "Unknown kind '{}', expected events, tasks or contacts",
+            kind
+        ),
+    };
+
+    let SourceResults { items, errors } = results;
+    let content = match marker {
+        "BEGIN:VCARD" => files::join_vcards(&items),
+        _ => files::join_calendar(&items),
+    };
+    let count = content.matches(marker).count();
+    Ok((content, count, json!(errors)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_file_path() {
+        assert_eq!(
+            file_path("/tmp/invite.ics").unwrap(),
+            PathBuf::from("/tmp/invite.ics")
+        );
+        assert_eq!(
+            file_path("~/contacts.vcf").unwrap(),
+            glib::home_dir().join("contacts.vcf")
+        );
+        assert!(file_path("invite.ics").is_err());
+    }
+
+    #[test]
+    fn test_export_path() {
+        let dir = std::env::temp_dir();
+        let ics = dir.join("export.ics");
+        assert_eq!(export_path("events", ics.to_str().unwrap()).unwrap(), ics);
+        assert!(export_path("tasks", dir.join("tasks.ICS").to_str().unwrap()).is_ok());
+        assert!(export_path("contacts", dir.join("contacts.vcf").to_str().unwrap()).is_ok());
+        assert!(export_path("contacts", ics.to_str().unwrap()).is_err());
+        assert!(export_path("events", "~/.bashrc").is_err());
+        assert!(export_path("memos", ics.to_str().unwrap()).is_err());
+
+        let directory = dir.join(format!("export-{}.ics", std::process::id()));
+        std::fs::create_dir_all(&directory).unwrap();
+        let result = export_path("events", directory.to_str().unwrap());
+        std::fs::remove_dir(&directory).unwrap();
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_import_summary() {
+        let source = "Work";
+        let items = vec![
+            import_entry("a", Some("A"), "event", source, Ok("imported")),
+            import_entry("b", None, "task", source, Ok("skipped_duplicate")),
+            import_entry("c", None, "event", source, Err(anyhow::anyhow!("Denied"))),
+        ];
+        let summary = import_summary(items);
+        assert_eq!(summary["imported"], 1);
+        assert_eq!(summary["skipped_duplicates"], 1);
+        assert_eq!(summary["failed"], 1);
+        assert_eq!(summary["items"][0]["source"], "Work");
+        assert_eq!(summary["items"][2]["error"], "Denied");
+    }
+}
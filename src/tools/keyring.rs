@@ -21,6 +21,8 @@ impl ToolProvider for Keyring {
     const NAME: &'static str = "keyring_management";
     const DESCRIPTION: &'static str =
         "Manage secrets in the GNOME Keyring. Actions: store, retrieve, delete";
+    const SECRET_PARAMS: &'static [&'static str] = &["secret"];
+    const SECRET_RESULT_FIELDS: &'static [&'static str] = &["secret"];
     type Params = KeyringParams;
 
     async fn execute_with_params(&self, params: Self::Params) -> Result<Value> {
@@ -49,6 +51,77 @@ impl ToolProvider for Keyring {
             )),
         }
     }
+
+    async fn dry_run_with_params(&self, params: Self::Params) -> Result<Value> {
+        let attributes = parse_attributes(params.attributes.as_deref().unwrap_or("{}"))?;
+        match params.action.as_str() {
+            "store" => {
+                let label = params
+                    .label
+                    .ok_or_else(|| anyhow::anyhow!("label required for store action"))?;
+                params
+                    .secret
+                    .ok_or_else(|| anyhow::anyhow!("secret required for store action"))?;
+
+                let existing = if attributes.is_empty() {
+                    None
+                } else {
+                    find_item_label(&attributes).await?
+                };
+                let description = match &existing {
+                    Some(existing) => {
+                        format!("Would replace secret '{existing}' with '{label}'")
+                    }
+                    None => format!("Would store new secret '{label}'"),
+                };
+                Ok(json!({
+                    "description": description,
+                    "label": label,
+                    "attributes": attributes,
+                    "replaces": existing
+                }))
+            }
+            "retrieve" | "delete" => {
+                if attributes.is_empty() {
+                    return Err(anyhow::anyhow!("Attributes cannot be empty"));
+                }
+                let label = find_item_label(&attributes)
+                    .await?
+                    .ok_or_else(|| anyhow::anyhow!("Secret not found"))?;
+                Ok(json!({
+                    "description": format!("Would {} secret '{label}'", params.action),
+                    "label": label,
+                    "attributes": attributes
+                }))
+            }
+            _ => Err(anyhow::anyhow!(
+                "Unknown action: {}. Available: store, retrieve, delete",
+                params.action
+            )),
+        }
+    }
+}
+
+fn parse_attributes(attributes: &str) -> Result<HashMap<String, String>> {
+    if attributes.trim().is_empty() || attributes == "{}" {
+        Ok(HashMap::new())
+    } else {
+        serde_json::from_str(attributes)
+            .map_err(|e| anyhow::anyhow!("Invalid attributes JSON: {}", e))
+    }
+}
+
+/// Label of the first item matching the attributes, without unlocking its
+/// secret
+async fn find_item_label(attributes: &HashMap<String, String>) -> Result<Option<String>> {
+    let service = Service::new().await?;
+    let collection = service.default_collection().await?;
+
+    let items = collection.search_items(attributes).await?;
+    match items.first() {
+        Some(item) => Ok(Some(item.label().await?)),
+        None => Ok(None),
+    }
 }
 
 async fn store_secret(label: String, secret: String, attributes: String) -> Result<Value> {
@@ -56,13 +129,7 @@ async fn store_secret(label: String, secret: String, attributes: String) -> Resu
     let collection = service.default_collection().await?;
 
     // Parse attributes from JSON string
-    let attributes: HashMap<String, String> = if attributes.trim().is_empty() || attributes == "{}"
-    {
-        HashMap::new()
-    } else {
-        serde_json::from_str(&attributes)
-            .map_err(|e| anyhow::anyhow!("Invalid attributes JSON: {}", e))?
-    };
+    let attributes = parse_attributes(&attributes)?;
 
     collection
         .create_item(
@@ -0,0 +1,206 @@
+use std::str::FromStr;
+
+use anyhow::Result;
+use serde_json::{json, Value};
+
+use crate::{
+    gnome::evolution::{local_timezone, Memo},
+    mcp::ToolProvider,
+    tool_params,
+    tools::calendar::{event_timezone, is_date, parse_date, parse_date_time},
+};
+
+#[derive(Default)]
+pub struct MemoManagement;
+
+tool_params! {
+    MemoParams,
+    required(action: string, "Action to perform: 'create', 'update', 'delete'"),
+    optional(uid: string, "Memo UID (required for update and delete)"),
+    optional(summary: string, "Memo title (required for create)"),
+    optional(description: string, "Memo text, empty to clear it"),
+    optional(date: string, "Date the memo is about (2024-07-15), or a time, empty to clear it"),
+    optional(categories: string, "Comma-separated categories, replacing the current ones, empty to clear them"),
+    optional(memo_list: string, "Memo list UID or display name (default: the default memo list when creating, all memo lists otherwise)")
+}
+
+impl ToolProvider for MemoManagement {
+    const NAME: &'static str = "memo_management";
+    const DESCRIPTION: &'static str =
+        "Manage Evolution Data Server memos (notes). Actions: create, update, delete";
+    type Params = MemoParams;
+
+    async fn execute_with_params(&self, params: Self::Params) -> Result<Value> {
+        Self::execute_with_result(|| manage_memo(&params)).await
+    }
+
+    async fn dry_run_with_params(&self, params: Self::Params) -> Result<Value> {
+        let connection = zbus::Connection::session().await?;
+        match params.action.as_str() {
+            "create" => {
+                let memo = new_memo(&params)?;
+                let (source, _proxy) =
+                    Memo::writable_memo_list(&connection, params.memo_list.as_deref()).await?;
+                Ok(json!({
+                    "description": format!(
+                        "Would create memo '{}' in {}",
+                        memo.summary.as_deref().unwrap_or_default(),
+                        source.display_name
+                    ),
+                    "memo_list": source.display_name,
+                    "memo": memo.to_json(local_timezone().await)
+                }))
+            }
+            "update" | "delete" => {
+                let uid = required_uid(&params)?;
+                let (source, proxy, stored) =
+                    Memo::find(&connection, uid, params.memo_list.as_deref()).await?;
+                crate::gnome::evolution::ensure_writable(&proxy, &source).await?;
+
+                let memo = Memo::from_str(&stored)?;
+                let summary = memo.summary.clone().unwrap_or_else(|| memo.uid.clone());
+                let memo = match params.action.as_str() {
+                    "delete" => memo,
+                    _ => apply_fields(memo, &params)?,
+                };
+                Ok(json!({
+                    "description": format!(
+                        "Would {} memo '{}' in {}",
+                        params.action, summary, source.display_name
+                    ),
+                    "memo_list": source.display_name,
+                    "memo": memo.to_json(local_timezone().await)
+                }))
+            }
+            _ => Err(unknown_action(&params.action)),
+        }
+    }
+}
+
+async fn manage_memo(params: &MemoParams) -> Result<Value> {
+    match params.action.as_str() {
+        "create" => {
+            let memo = new_memo(params)?;
+            let uid = memo.create(params.memo_list.as_deref()).await?;
+            Ok(json!({
+                "uid": uid,
+                "summary": memo.summary
+            }))
+        }
+        "update" => {
+            let uid = required_uid(params)?;
+            let connection = zbus::Connection::session().await?;
+            let (source, proxy, stored) =
+                Memo::find(&connection, uid, params.memo_list.as_deref()).await?;
+            let memo = apply_fields(Memo::from_str(&stored)?, params)?;
+            memo.modify(&source, &proxy, &stored).await?;
+            Ok(json!({
+                "uid": memo.uid,
+                "memo_list": source.display_name,
+                "memo": memo.to_json(local_timezone().await)
+            }))
+        }
+        "delete" => {
+            let uid = required_uid(params)?;
+            let connection = zbus::Connection::session().await?;
+            let (source, proxy, stored) =
+                Memo::find(&connection, uid, params.memo_list.as_deref()).await?;
+            let memo = Memo::from_str(&stored)?;
+            memo.remove(&source, &proxy).await?;
+            Ok(json!({
+                "uid": memo.uid,
+                "memo_list": source.display_name
+            }))
+        }
+        _ => Err(unknown_action(&params.action)),
+    }
+}
+
+fn unknown_action(action: &str) -> anyhow::Error {
+    anyhow::anyhow!(
+        "Unknown action: {}. Available: create, update, delete",
+        action
+    )
+}
+
+fn required_uid(params: &MemoParams) -> Result<&str> {
+    params
+        .uid
+        .as_deref()
+        .ok_or_else(|| anyhow::anyhow!("uid required for {} action", params.action))
+}
+
+fn new_memo(params: &MemoParams) -> Result<Memo> {
+    if params.summary.as_deref().map_or(true, str::is_empty) {
+        anyhow::bail!("summary required for create action");
+    }
+    apply_fields(Memo::default(), params)
+}
+
+fn apply_fields(mut memo: Memo, params: &MemoParams) -> Result<Memo> {
+    if let Some(summary) = &params.summary {
+        if summary.is_empty() {
+            anyhow::bail!("summary cannot be empty");
+        }
+        memo.summary = Some(summary.clone());
+    }
+    if let Some(description) = &params.description {
+        memo.description = Some(description.clone()).filter(|d| !d.is_empty());
+    }
+    if let Some(date) = &params.date {
+        memo.date = match date.as_str() {
+            "" => None,
+            date if is_date(date) => Some(parse_date(date)?),
+            date => Some(parse_date_time(date, event_timezone(None)?)?),
+        };
+        memo.all_day = is_date(date);
+    }
+    if let Some(categories) = &params.categories {
+        memo.categories = categories
+            .split(',')
+            .map(str::trim)
+            .filter(|c| !c.is_empty())
+            .map(ToOwned::to_owned)
+            .collect();
+    }
+    Ok(memo)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mcp::ToolParams;
+
+    fn params(arguments: Value) -> MemoParams {
+        MemoParams::extract_params(&arguments).unwrap()
+    }
+
+    #[test]
+    fn test_memo_fields() {
+        let memo = new_memo(&params(json!({
+            "action": "create",
+            "summary": "Book ideas",
+            "description": "Dune\nHyperion",
+            "date": "2024-07-15",
+            "categories": "Personal, Reading,"
+        })))
+        .unwrap();
+        assert_eq!(memo.summary.as_deref(), Some("Book ideas"));
+        assert_eq!(memo.description.as_deref(), Some("Dune\nHyperion"));
+        assert!(memo.all_day);
+        assert!(memo.to_ical().contains("DTSTART;VALUE=DATE:20240715\r\n"));
+        assert_eq!(memo.categories, ["Personal", "Reading"]);
+
+        let memo = apply_fields(
+            memo,
+            &params(json!({"action": "update", "description": "", "date": "", "categories": ""})),
+        )
+        .unwrap();
+        assert_eq!(memo.description, None);
+        assert_eq!(memo.date, None);
+        assert!(memo.categories.is_empty());
+
+        assert!(new_memo(&params(json!({"action": "create"}))).is_err());
+        assert!(new_memo(&params(json!({"action": "create", "summary": ""}))).is_err());
+    }
+}
@@ -1,9 +1,18 @@
 pub mod applications;
 pub mod audio;
+pub mod calendar;
+pub mod contacts;
+pub mod free_busy;
+pub mod import_export;
 pub mod keyring;
+pub mod memos;
 pub mod notifications;
 pub mod open_file;
 pub mod quick_settings;
 pub mod screenshot;
+pub mod search;
+pub mod sources;
+pub mod tasks;
+pub mod undo;
 pub mod wallpaper;
 pub mod window_management;
@@ -23,6 +23,14 @@ impl ToolProvider for Notifications {
         )
         .await
     }
+
+    async fn dry_run_with_params(&self, params: Self::Params) -> Result<serde_json::Value> {
+        Ok(serde_json::json!({
+            "description": format!("Would send notification: {}", params.summary),
+            "summary": params.summary,
+            "body": params.body
+        }))
+    }
 }
 
 async fn send_notification(summary: &str, body: &str) -> Result<()> {
@@ -32,3 +40,26 @@ async fn send_notification(summary: &str, body: &str) -> Result<()> {
     proxy.add_notification("", notification).await?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_dry_run_skips_execution() {
+        let result = Notifications
+            .execute(&json!({"summary": "Hello", "body": "World", "dry_run": true}))
+            .await
+            .unwrap();
+
+        assert_eq!(result["success"], true);
+        assert_eq!(result["dry_run"], true);
+        assert_eq!(result["result"]["summary"], "Hello");
+
+        let schema = Notifications::input_schema();
+        assert_eq!(schema["properties"]["dry_run"]["type"], "boolean");
+        assert_eq!(schema["required"], json!(["summary", "body"]));
+    }
+}
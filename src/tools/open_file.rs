@@ -19,6 +19,25 @@ impl ToolProvider for OpenFile {
     async fn execute_with_params(&self, params: Self::Params) -> Result<serde_json::Value> {
         Self::execute_with_result(|| open_file(&params.path)).await
     }
+
+    async fn dry_run_with_params(&self, params: Self::Params) -> Result<serde_json::Value> {
+        let (handler, description) = match default_app_for(&params.path) {
+            Ok((_, app_info)) => {
+                let name = app_info.name().to_string();
+                let description = format!("Would open {} with {} via GIO", params.path, name);
+                (name, description)
+            }
+            Err(_) => (
+                "xdg-open".to_owned(),
+                format!("Would open {} with xdg-open", params.path),
+            ),
+        };
+        Ok(serde_json::json!({
+            "description": description,
+            "path": params.path,
+            "handler": handler
+        }))
+    }
 }
 
 async fn open_file(path: &str) -> Result<String> {
@@ -35,7 +54,7 @@ async fn open_file(path: &str) -> Result<String> {
     Err(anyhow::anyhow!("All open methods failed for: {}", path))
 }
 
-async fn try_gio_launch(path: &str) -> Result<String> {
+fn default_app_for(path: &str) -> Result<(gio::File, gio::AppInfo)> {
     // Convert path to GFile
     let file = if path.starts_with("http://")
         || path.starts_with("https://")
@@ -53,20 +72,19 @@ async fn try_gio_launch(path: &str) -> Result<String> {
         gio::Cancellable::NONE,
     )?;
 
-    if let Some(content_type) = file_info.content_type() {
-        if let Some(app_info) = gio::AppInfo::default_for_type(&content_type, false) {
-            match app_info.launch(&[file], Option::<&gio::AppLaunchContext>::None) {
-                Ok(_) => {
-                    return Ok(format!("Opened with {} via GIO", app_info.name()));
-                }
-                Err(e) => {
-                    return Err(anyhow::anyhow!("GIO launch failed: {}", e));
-                }
-            }
-        }
-    }
+    file_info
+        .content_type()
+        .and_then(|content_type| gio::AppInfo::default_for_type(&content_type, false))
+        .map(|app_info| (file, app_info))
+        .ok_or_else(|| anyhow::anyhow!("No default app found via GIO"))
+}
 
-    Err(anyhow::anyhow!("No default app found via GIO"))
+async fn try_gio_launch(path: &str) -> Result<String> {
+    let (file, app_info) = default_app_for(path)?;
+    match app_info.launch(&[file], Option::<&gio::AppLaunchContext>::None) {
+        Ok(_) => Ok(format!("Opened with {} via GIO", app_info.name())),
+        Err(e) => Err(anyhow::anyhow!("GIO launch failed: {}", e)),
+    }
 }
 
 async fn try_xdg_open(path: &str) -> Result<String> {
@@ -1,7 +1,7 @@
 use anyhow::Result;
 use gio::prelude::*;
 
-use crate::{mcp::ToolProvider, tool_params};
+use crate::{mcp::ToolProvider, tool_params, undo::PreviousState};
 
 #[derive(Default)]
 pub struct QuickSettings;
@@ -19,11 +19,99 @@ impl ToolProvider for QuickSettings {
     type Params = QuickSettingsParams;
 
     async fn execute_with_params(&self, params: Self::Params) -> Result<serde_json::Value> {
-        Self::execute_with_result(|| execute_boolean_toggle(&params.setting, params.enabled)).await
+        Self::execute_with_result(|| toggle_with_undo(&params.setting, params.enabled)).await
+    }
+
+    async fn dry_run_with_params(&self, params: Self::Params) -> Result<serde_json::Value> {
+        let (label, backend) = describe_setting(&params.setting)?;
+        Ok(serde_json::json!({
+            "description": format!(
+                "Would {} {}",
+                if params.enabled { "enable" } else { "disable" },
+                label
+            ),
+            "setting": params.setting,
+            "enabled": params.enabled,
+            "backend": backend
+        }))
+    }
+}
+
+/// Human-readable name of a setting and where it is stored
+fn describe_setting(setting: &str) -> Result<(&'static str, &'static str)> {
+    match setting {
+        "wifi" => Ok(("WiFi", "NetworkManager WirelessEnabled")),
+        "bluetooth" => Ok(("Bluetooth", "BlueZ hci0 adapter Powered")),
+        "night_light" => Ok((
+            "Night light",
+            "org.gnome.settings-daemon.plugins.color night-light-enabled",
+        )),
+        "do_not_disturb" => Ok((
+            "Do Not Disturb",
+            "org.gnome.desktop.notifications show-banners",
+        )),
+        "dark_style" => Ok(("Dark style", "org.gnome.desktop.interface color-scheme")),
+        _ => Err(anyhow::anyhow!("Unknown boolean setting: {}", setting)),
+    }
+}
+
+async fn toggle_with_undo(setting: &str, enabled: bool) -> Result<String> {
+    let previous = read_setting(setting).await.ok();
+    let message = execute_boolean_toggle(setting, enabled).await?;
+    if let Some(previous) = previous.filter(|previous| *previous != enabled) {
+        crate::undo::record(
+            QuickSettings::NAME,
+            PreviousState::QuickSetting {
+                setting: setting.to_owned(),
+                enabled: previous,
+            },
+        );
+    }
+    Ok(message)
+}
+
+/// Current value of a boolean setting
+async fn read_setting(setting: &str) -> Result<bool> {
+    match setting {
+        "wifi" => {
+            let connection = zbus::Connection::system().await?;
+            let nm_proxy = zbus::Proxy::new(
+                &connection,
+                "org.freedesktop.NetworkManager",
+                "/org/freedesktop/NetworkManager",
+                "org.freedesktop.NetworkManager",
+            )
+            .await?;
+            Ok(nm_proxy.get_property("WirelessEnabled").await?)
+        }
+        "bluetooth" => {
+            let connection = zbus::Connection::system().await?;
+            let adapter_proxy = zbus::Proxy::new(
+                &connection,
+                "org.bluez",
+                "/org/bluez/hci0",
+                "org.bluez.Adapter1",
+            )
+            .await?;
+            Ok(adapter_proxy.get_property("Powered").await?)
+        }
+        "night_light" => {
+            let settings = gio::Settings::new("org.gnome.settings-daemon.plugins.color");
+            Ok(settings.boolean("night-light-enabled"))
+        }
+        "do_not_disturb" => {
+            let settings = gio::Settings::new("org.gnome.desktop.notifications");
+            Ok(!settings.boolean("show-banners"))
+        }
+        "dark_style" => {
+            let settings = gio::Settings::new("org.gnome.desktop.interface");
+            Ok(settings.string("color-scheme") == "prefer-dark")
+        }
+        _ => Err(anyhow::anyhow!("Unknown boolean setting: {}", setting)),
     }
 }
 
-async fn execute_boolean_toggle(setting: &str, enabled: bool) -> Result<String> {
+pub(crate) async fn execute_boolean_toggle(setting: &str, enabled: bool) -> Result<String> {
     match setting {
         "wifi" => toggle_wifi(enabled).await,
         "bluetooth" => toggle_bluetooth(enabled).await,
@@ -23,6 +23,21 @@ impl ToolProvider for Screenshot {
 
         Self::execute_with_result(|| take_screenshot_portal(interactive)).await
     }
+
+    async fn dry_run_with_params(&self, params: Self::Params) -> Result<serde_json::Value> {
+        let config = crate::config::CONFIG.get_screenshot_config();
+
+        let interactive = params.interactive.unwrap_or(config.interactive);
+
+        Ok(serde_json::json!({
+            "description": if interactive {
+                "Would open the interactive screenshot dialog"
+            } else {
+                "Would take a full screenshot through the desktop portal"
+            },
+            "interactive": interactive
+        }))
+    }
 }
 
 async fn take_screenshot_portal(interactive: bool) -> Result<String> {
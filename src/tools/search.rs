@@ -0,0 +1,373 @@
+use anyhow::Result;
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use serde_json::json;
+
+use crate::{
+    gnome::evolution::{
+        local_timezone, CalendarQuery, ComponentField, Contact, ContactField, ContactQuery, Event,
+        SourceResults, Task,
+    },
+    mcp::ToolProvider,
+    tool_params,
+    tools::{calendar::parse_date_time, free_busy::local_midnight},
+};
+
+#[derive(Default)]
+pub struct SearchContacts;
+
+#[derive(Default)]
+pub struct SearchEvents;
+
+#[derive(Default)]
+pub struct SearchTasks;
+
+tool_params! {
+    SearchContactsParams,
+    optional(text: string, "Text to look for in any field of the contact"),
+    optional(name: string, "Text the full name or nickname contains"),
+    optional(name_prefix: string, "Text the full name starts with"),
+    optional(email: string, "Text an email address contains"),
+    optional(phone: string, "Text a phone number contains"),
+    optional(organization: string, "Text the organization contains"),
+    optional(category: string, "Text a category of the contact contains"),
+    optional(address_book: string, "Address book UID or display name (default: the address books of the contacts resource)")
+}
+
+tool_params! {
+    SearchEventsParams,
+    optional(text: string, "Text to look for in any text property of the event"),
+    optional(summary: string, "Text the summary contains"),
+    optional(location: string, "Text the location contains"),
+    optional(attendee: string, "Text the name or address of an attendee or the organizer contains"),
+    optional(category: string, "Category the event has"),
+    optional(from: string, "Start of the range, a date (2024-07-15) or a date/time (default: the calendar resource range)"),
+    optional(to: string, "End of the range, a date (included) or a date/time (default: the calendar resource range)"),
+    optional(calendar: string, "Calendar UID or display name (default: the calendars of the calendar resource)")
+}
+
+tool_params! {
+    SearchTasksParams,
+    optional(text: string, "Text to look for in any text property of the task"),
+    optional(summary: string, "Text the summary contains"),
+    optional(category: string, "Category the task has"),
+    optional(include_completed: bool, "Also return completed tasks (default: false)"),
+    optional(task_list: string, "Task list UID or display name (default: the task lists of the tasks resource)")
+}
+
+impl ToolProvider for SearchContacts {
+    const NAME: &'static str = "search_contacts";
+    const DESCRIPTION: &'static str =
+        "Search Evolution Data Server contacts by name, email, phone, organization or any text";
+    type Params = SearchContactsParams;
+
+    async fn execute_with_params(&self, params: Self::Params) -> Result<serde_json::Value> {
+        Self::execute_with_result(|| async {
+            let query = contact_query(&params)?;
+            let sources = crate::config::CONFIG
+                .get_contacts_config()
+                .sources
+                .narrowed(params.address_book.as_deref());
+            let SourceResults {
+                items: contacts,
+                errors,
+            } = Contact::search(&query, &sources).await?;
+            Ok(json!({
+                "contacts": contacts.iter().map(|c| c.to_json()).collect::<Vec<_>>(),
+                "count": contacts.len(),
+                "source_errors": errors
+            }))
+        })
+        .await
+    }
+
+    async fn dry_run_with_params(&self, params: Self::Params) -> Result<serde_json::Value> {
+        let query = contact_query(&params)?;
+        Ok(json!({
+            "description": "Would search contacts",
+            "query": query.to_string()
+        }))
+    }
+}
+
+impl ToolProvider for SearchEvents {
+    const NAME: &'static str = "search_events";
+    const DESCRIPTION: &'static str = "Search Evolution Data Server calendar events by text, summary, location, attendee or category within a time range";
+    type Params = SearchEventsParams;
+
+    async fn execute_with_params(&self, params: Self::Params) -> Result<serde_json::Value> {
+        Self::execute_with_result(|| async {
+            let local = local_timezone().await;
+            let query = event_query(&params)?;
+            let (start, end) = event_range(
+                params.from.as_deref(),
+                params.to.as_deref(),
+                local,
+                Utc::now(),
+            )?;
+            let sources = crate::config::CONFIG
+                .get_calendar_config()
+                .sources
+                .narrowed(params.calendar.as_deref());
+            let SourceResults {
+                items: mut events,
+                errors,
+            } = Event::search(&query, start, end, &sources).await?;
+            let contacts = crate::config::CONFIG.get_contacts_config();
+            Event::name_attendees(&mut events, &contacts.sources).await;
+            Ok(json!({
+                "events": events.iter().map(|e| e.to_json(local)).collect::<Vec<_>>(),
+                "timezone": local.name(),
+                "count": events.len(),
+                "source_errors": errors
+            }))
+        })
+        .await
+    }
+
+    async fn dry_run_with_params(&self, params: Self::Params) -> Result<serde_json::Value> {
+        let local = local_timezone().await;
+        let query = event_query(&params)?;
+        let (start, end) = event_range(
+            params.from.as_deref(),
+            params.to.as_deref(),
+            local,
+            Utc::now(),
+        )?;
+        Ok(json!({
+            "description": format!(
+                "Would search events between {} and {}",
+                start.with_timezone(&local).to_rfc3339(),
+                end.with_timezone(&local).to_rfc3339()
+            ),
+            "query": CalendarQuery::OccursIn(start, end).and(query).to_string()
+        }))
+    }
+}
+
+impl ToolProvider for SearchTasks {
+    const NAME: &'static str = "search_tasks";
+    const DESCRIPTION: &'static str =
+        "Search Evolution Data Server tasks by text, summary or category";
+    type Params = SearchTasksParams;
+
+    async fn execute_with_params(&self, params: Self::Params) -> Result<serde_json::Value> {
+        Self::execute_with_result(|| async {
+            let local = local_timezone().await;
+            let query = task_query(&params)?;
+            let include_completed = params.include_completed.unwrap_or(false);
+            let sources = crate::config::CONFIG
+                .get_tasks_config()
+                .sources
+                .narrowed(params.task_list.as_deref());
+            let SourceResults {
+                items: tasks,
+                errors,
+            } = Task::search(&query, &sources).await?;
+            let tasks = tasks
+                .into_iter()
+                .filter(|task| include_completed || !task.is_completed())
+                .collect::<Vec<_>>();
+            Ok(json!({
+                "tasks": tasks.iter().map(|t| t.to_json(local)).collect::<Vec<_>>(),
+                "timezone": local.name(),
+                "count": tasks.len(),
+                "source_errors": errors
+            }))
+        })
+        .await
+    }
+
+    async fn dry_run_with_params(&self, params: Self::Params) -> Result<serde_json::Value> {
+        let query = task_query(&params)?;
+        Ok(json!({
+            "description": "Would search tasks",
+            "query": query.to_string()
+        }))
+    }
+}
+
+/// A trimmed search text, unless it is empty
+fn text(value: &Option<String>) -> Option<String> {
+    value
+        .as_deref()
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .map(ToOwned::to_owned)
+}
+
+fn no_criteria(kind: &str) -> anyhow::Error {
+    anyhow::anyhow!(
+        "At least one search criterion is required to search {}",
+        kind
+    )
+}
+
+/// All the given criteria, which contacts have to match at once
+fn contact_query(params: &SearchContactsParams) -> Result<ContactQuery> {
+    [
+        text(&params.text).map(|text| ContactQuery::Contains(ContactField::AnyField, text)),
+        text(&params.name).map(|name| {
+            ContactQuery::Or(vec![
+                ContactQuery::Contains(ContactField::FullName, name.clone()),
+                ContactQuery::Contains(ContactField::Nickname, name),
+            ])
+        }),
+        text(&params.name_prefix)
+            .map(|prefix| ContactQuery::BeginsWith(ContactField::FullName, prefix)),
+        text(&params.email).map(|email| ContactQuery::Contains(ContactField::Email, email)),
+        text(&params.phone).map(|phone| ContactQuery::Contains(ContactField::Phone, phone)),
+        text(&params.organization)
+            .map(|organization| ContactQuery::Contains(ContactField::Organization, organization)),
+        text(&params.category)
+            .map(|category| ContactQuery::Contains(ContactField::Category, category)),
+    ]
+    .into_iter()
+    .flatten()
+    .reduce(ContactQuery::and)
+    .ok_or_else(|| no_criteria("contacts"))
+}
+
+/// All the given criteria, which events have to match at once
+fn event_query(params: &SearchEventsParams) -> Result<CalendarQuery> {
+    [
+        text(&params.text).map(|text| CalendarQuery::Contains(ComponentField::Any, text)),
+        text(&params.summary)
+            .map(|summary| CalendarQuery::Contains(ComponentField::Summary, summary)),
+        text(&params.location)
+            .map(|location| CalendarQuery::Contains(ComponentField::Location, location)),
+        text(&params.attendee).map(|person| {
+            CalendarQuery::Or(vec![
+                CalendarQuery::Contains(ComponentField::Attendee, person.clone()),
+                CalendarQuery::Contains(ComponentField::Organizer, person),
+            ])
+        }),
+        text(&params.category).map(CalendarQuery::HasCategory),
+    ]
+    .into_iter()
+    .flatten()
+    .reduce(CalendarQuery::and)
+    .ok_or_else(|| no_criteria("events"))
+}
+
+/// All the given criteria, which tasks have to match at once
+fn task_query(params: &SearchTasksParams) -> Result<CalendarQuery> {
+    let query = [
+        text(&params.text).map(|text| CalendarQuery::Contains(ComponentField::Any, text)),
+        text(&params.summary)
+            .map(|summary| CalendarQuery::Contains(ComponentField::Summary, summary)),
+        text(&params.category).map(CalendarQuery::HasCategory),
+    ]
+    .into_iter()
+    .flatten()
+    .reduce(CalendarQuery::and)
+    .ok_or_else(|| no_criteria("tasks"))?;
+
+    Ok(match params.include_completed.unwrap_or(false) {
+        true => query,
+        false => query.and(CalendarQuery::Completed.not()),
+    })
+}
+
+/// The range to search, defaulting to the one of the calendar resource.
+/// Dates cover the whole local day, so `to` includes its day.
+pub(crate) fn event_range(
+    from: Option<&str>,
+    to: Option<&str>,
+    local: chrono_tz::Tz,
+    now: DateTime<Utc>,
+) -> Result<(DateTime<Utc>, DateTime<Utc>)> {
+    let config = crate::config::CONFIG.get_calendar_config();
+    let parse = |value: &str, end: bool| -> Result<DateTime<Utc>> {
+        match NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+            Ok(date) if end => Ok(local_midnight(date + Duration::days(1), local)),
+            Ok(date) => Ok(local_midnight(date, local)),
+            Err(_) => parse_date_time(value, Some(local)),
+        }
+    };
+
+    let start = match from.filter(|from| !from.is_empty()) {
+        Some(from) => parse(from, false)?,
+        None => now - Duration::days(config.days_behind as i64),
+    };
+    let end = match to.filter(|to| !to.is_empty()) {
+        Some(to) => parse(to, true)?,
+        None => start.max(now) + Duration::days(config.days_ahead as i64),
+    };
+
+    if end <= start {
+        anyhow::bail!("The end of the range must come after its start");
+    }
+    Ok((start, end))
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::mcp::ToolParams;
+
+    #[test]
+    fn test_contact_query() {
+        let params = SearchContactsParams::extract_params(&json!({
+            "name": "Bob",
+            "name_prefix": "Ann",
+            "email": " example.org ",
+            "phone": ""
+        }))
+        .unwrap();
+        assert_eq!(
+            contact_query(&params).unwrap().to_string(),
+            r#"(and (or (contains "full_name" "Bob") (contains "nickname" "Bob")) (beginswith "full_name" "Ann") (contains "email" "example.org"))"#
+        );
+
+        let params = SearchContactsParams::extract_params(&json!({"phone": " "})).unwrap();
+        assert!(contact_query(&params).is_err());
+    }
+
+    #[test]
+    fn test_event_and_task_queries() {
+        let params = SearchEventsParams::extract_params(&json!({
+            "summary": "standup",
+            "category": "Work"
+        }))
+        .unwrap();
+        assert_eq!(
+            event_query(&params).unwrap().to_string(),
+            r#"(and (contains? "summary" "standup") (has-categories? "Work"))"#
+        );
+
+        let local = chrono_tz::Europe::Paris;
+        let params = SearchEventsParams::extract_params(&json!({
+            "summary": "standup",
+            "from": "2024-07-10",
+            "to": "2024-07-11"
+        }))
+        .unwrap();
+        let (start, end) = event_range(
+            params.from.as_deref(),
+            params.to.as_deref(),
+            local,
+            Utc::now(),
+        )
+        .unwrap();
+        assert_eq!(
+            start,
+            "2024-07-09T22:00:00Z".parse::<DateTime<Utc>>().unwrap()
+        );
+        assert_eq!(
+            end,
+            "2024-07-11T22:00:00Z".parse::<DateTime<Utc>>().unwrap()
+        );
+
+        let params = SearchTasksParams::extract_params(&json!({"text": "invoice"})).unwrap();
+        assert_eq!(
+            task_query(&params).unwrap().to_string(),
+            r#"(and (contains? "any" "invoice") (not (is-completed?)))"#
+        );
+
+        let params =
+            SearchTasksParams::extract_params(&json!({"include_completed": true})).unwrap();
+        assert!(task_query(&params).is_err());
+    }
+}
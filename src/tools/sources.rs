@@ -0,0 +1,218 @@
+use anyhow::Result;
+use serde_json::{json, Value};
+
+use crate::{
+    gnome::evolution::source_manager::{
+        apply_changes, create_source, display_name, find_created_source, local_source_type,
+        new_source_data, remove_source, validate_color, write_source, SourceChanges,
+    },
+    mcp::ToolProvider,
+    tool_params,
+};
+
+#[derive(Default)]
+pub struct SourceManagement;
+
+tool_params! {
+    SourceParams,
+    required(action: string, "Action to perform: 'create', 'update', 'delete'"),
+    optional(source: string, "UID or display name of a source created by this server (required for update and delete)"),
+    optional(kind: string, "Kind of source to create: 'calendar', 'task_list', 'memo_list', 'address_book'"),
+    optional(display_name: string, "Name of the source (required for create)"),
+    optional(color: string, "Color as #rrggbb, for calendars, task lists and memo lists"),
+    optional(enabled: bool, "Enable (true) or disable (false) the source")
+}
+
+impl ToolProvider for SourceManagement {
+    const NAME: &'static str = "source_management";
+    const DESCRIPTION: &'static str = "Create local calendars, task lists, memo lists and address books in Evolution Data Server, and rename, recolor, enable, disable or delete the ones created this way. Actions: create, update, delete";
+    type Params = SourceParams;
+
+    async fn execute_with_params(&self, params: Self::Params) -> Result<Value> {
+        Self::execute_with_result(|| manage_source(&params)).await
+    }
+
+    async fn dry_run_with_params(&self, params: Self::Params) -> Result<Value> {
+        match params.action.as_str() {
+            "create" => {
+                let (kind, display_name, data) = new_source(&params)?;
+                Ok(json!({
+                    "description": format!("Would create {} '{}'", kind, display_name),
+                    "data": data
+                }))
+            }
+            "update" => {
+                let changes = source_changes(&params)?;
+                let connection = zbus::Connection::session().await?;
+                let object = find_created_source(&connection, required_source(&params)?).await?;
+                Ok(json!({
+                    "description": format!("Would update source '{}'", display_name(&object)),
+                    "uid": object.uid,
+                    "data": apply_changes(&object.data, &changes)?
+                }))
+            }
+            "delete" => {
+                let connection = zbus::Connection::session().await?;
+                let object = find_created_source(&connection, required_source(&params)?).await?;
+                Ok(json!({
+                    "description": format!(
+                        "Would delete source '{}' and everything stored in it",
+                        display_name(&object)
+                    ),
+                    "uid": object.uid
+                }))
+            }
+            _ => Err(unknown_action(&params.action)),
+        }
+    }
+}
+
+async fn manage_source(params: &SourceParams) -> Result<Value> {
+    let connection = zbus::Connection::session().await?;
+    match params.action.as_str() {
+        "create" => {
+            let (kind, display_name, data) = new_source(params)?;
+            let uid = create_source(&connection, data).await?;
+            Ok(json!({
+                "uid": uid,
+                "kind": kind,
+                "display_name": display_name
+            }))
+        }
+        "update" => {
+            let changes = source_changes(params)?;
+            let object = find_created_source(&connection, required_source(params)?).await?;
+            write_source(
+                &connection,
+                &object,
+                &apply_changes(&object.data, &changes)?,
+            )
+            .await?;
+            Ok(json!({
+                "uid": object.uid,
+                "display_name": changes.display_name.unwrap_or_else(|| display_name(&object))
+            }))
+        }
+        "delete" => {
+            let object = find_created_source(&connection, required_source(params)?).await?;
+            remove_source(&connection, &object).await?;
+            Ok(json!({
+                "uid": object.uid,
+                "display_name": display_name(&object)
+            }))
+        }
+        _ => Err(unknown_action(&params.action)),
+    }
+}
+
+fn unknown_action(action: &str) -> anyhow::Error {
+    anyhow::anyhow!(
+        "Unknown action: {}. Available: create, update, delete",
+        action
+    )
+}
+
+fn required_source(params: &SourceParams) -> Result<&str> {
+    params
+        .source
+        .as_deref()
+        .ok_or_else(|| anyhow::anyhow!("source required for {} action", params.action))
+}
+
+/// Kind, name and keyfile of the source to create
+fn new_source(params: &SourceParams) -> Result<(&str, &str, String)> {
+    let kind = params
+        .kind
+        .as_deref()
+        .ok_or_else(|| anyhow::anyhow!("kind required for create action"))?;
+    let display_name = params
+        .display_name
+        .as_deref()
+        .filter(|name| !name.trim().is_empty())
+        .ok_or_else(|| anyhow::anyhow!("display_name required for create action"))?;
+    if params.enabled == Some(false) {
+        anyhow::bail!("New sources are created enabled");
+    }
+    let source_type = local_source_type(kind)?;
+    if let Some(color) = &params.color {
+        if kind == "address_book" {
+            anyhow::bail!("Address books have no color");
+        }
+        validate_color(color)?;
+    }
+    let data = new_source_data(&source_type, display_name, params.color.as_deref());
+    Ok((kind, display_name, data))
+}
+
+fn source_changes(params: &SourceParams) -> Result<SourceChanges> {
+    if params.kind.is_some() {
+        anyhow::bail!("The kind of a source can't be changed");
+    }
+    if let Some(color) = &params.color {
+        validate_color(color)?;
+    }
+    if params
+        .display_name
+        .as_deref()
+        .is_some_and(|name| name.trim().is_empty())
+    {
+        anyhow::bail!("display_name cannot be empty");
+    }
+    let changes = SourceChanges {
+        display_name: params.display_name.clone(),
+        color: params.color.clone(),
+        enabled: params.enabled,
+    };
+    if changes.display_name.is_none() && changes.color.is_none() && changes.enabled.is_none() {
+        anyhow::bail!("Nothing to update: give display_name, color or enabled");
+    }
+    Ok(changes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn params(action: &str) -> SourceParams {
+        SourceParams {
+            action: action.to_string(),
+            source: None,
+            kind: None,
+            display_name: None,
+            color: None,
+            enabled: None,
+        }
+    }
+
+    #[test]
+    fn test_new_source() {
+        let mut create = params("create");
+        assert!(new_source(&create).is_err());
+        create.kind = Some("calendar".to_string());
+        create.display_name = Some("Garden".to_string());
+        create.color = Some("#73d216".to_string());
+        let (kind, name, data) = new_source(&create).unwrap();
+        assert_eq!((kind, name), ("calendar", "Garden"));
+        assert!(data.contains("[Calendar]"));
+        assert!(data.contains("Color=#73d216"));
+
+        create.kind = Some("address_book".to_string());
+        assert!(new_source(&create).is_err());
+        create.color = None;
+        create.enabled = Some(false);
+        assert!(new_source(&create).is_err());
+    }
+
+    #[test]
+    fn test_source_changes() {
+        let mut update = params("update");
+        assert!(source_changes(&update).is_err());
+        update.enabled = Some(false);
+        assert_eq!(source_changes(&update).unwrap().enabled, Some(false));
+        update.color = Some("green".to_string());
+        assert!(source_changes(&update).is_err());
+        update.color = None;
+        update.display_name = Some(" ".to_string());
+        assert!(source_changes(&update).is_err());
+    }
+}
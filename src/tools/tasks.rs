@@ -0,0 +1,445 @@
+use std::str::FromStr;
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde_json::{json, Value};
+
+use crate::{
+    gnome::evolution::{local_timezone, CalendarQuery, SourceFilter, SourceInfo, Task},
+    mcp::ToolProvider,
+    tool_params,
+    tools::calendar::{event_timezone, is_date, parse_date, parse_date_time, parse_reminders},
+};
+
+#[derive(Default)]
+pub struct TaskManagement;
+
+tool_params! {
+    TaskParams,
+    required(action: string, "Action to perform: 'create', 'complete', 'update', 'delete'"),
+    optional(uid: string, "Task UID (required for complete, update and delete)"),
+    optional(summary: string, "Task summary (required for create)"),
+    optional(description: string, "Task description, empty to clear it"),
+    optional(due: string, "Due time, RFC 3339 or local time (2024-07-15T17:00), or a date (2024-07-15), empty to clear it"),
+    optional(priority: i64, "Priority from 1 (highest) to 9 (lowest), 0 for none"),
+    optional(reminders: string, "Comma-separated minutes before the due time to show a reminder, e.g. 30,1440 (create only)"),
+    optional(parent: string, "UID of the parent task, making this task a subtask in the parent's task list, empty to detach it"),
+    optional(task_list: string, "Task list UID or display name (default: the default task list when creating, all task lists otherwise)")
+}
+
+impl ToolProvider for TaskManagement {
+    const NAME: &'static str = "task_management";
+    const DESCRIPTION: &'static str =
+        "Manage Evolution Data Server tasks. Actions: create, complete, update, delete";
+    type Params = TaskParams;
+
+    async fn execute_with_params(&self, params: Self::Params) -> Result<Value> {
+        Self::execute_with_result(|| manage_task(&params)).await
+    }
+
+    async fn dry_run_with_params(&self, params: Self::Params) -> Result<Value> {
+        let connection = zbus::Connection::session().await?;
+        match params.action.as_str() {
+            "create" => {
+                let task = new_task(&params)?;
+                let task_list = parent_task_list(&connection, &task, &params).await?;
+                let (source, _proxy) =
+                    Task::writable_task_list(&connection, task_list.as_deref()).await?;
+                Ok(json!({
+                    "description": format!(
+                        "Would create task '{}' in {}",
+                        task.summary.as_deref().unwrap_or_default(),
+                        source.display_name
+                    ),
+                    "task_list": source.display_name,
+                    "task": task.to_json(local_timezone().await)
+                }))
+            }
+            "complete" | "update" | "delete" => {
+                let uid = required_uid(&params)?;
+                let (source, proxy, stored) =
+                    Task::find(&connection, uid, params.task_list.as_deref()).await?;
+                crate::gnome::evolution::ensure_writable(&proxy, &source).await?;
+
+                let task = Task::from_str(&stored)?;
+                let summary = task.summary.clone().unwrap_or_else(|| task.uid.clone());
+                let task = match params.action.as_str() {
+                    "delete" => task,
+                    _ => changed_task(task, &params, Utc::now())?,
+                };
+                let mut former_parents = Vec::new();
+                if params.action != "delete" && params.parent.is_some() {
+                    check_parent(&connection, &task, &source).await?;
+                    former_parents = former_parents_of(&task, &source)
+                        .await?
+                        .into_iter()
+                        .map(|parent| parent.uid)
+                        .collect();
+                }
+                Ok(json!({
+                    "description": format!(
+                        "Would {} task '{}' in {}",
+                        params.action, summary, source.display_name
+                    ),
+                    "task_list": source.display_name,
+                    "task": task.to_json(local_timezone().await),
+                    "former_parents": former_parents
+                }))
+            }
+            _ => Err(unknown_action(&params.action)),
+        }
+    }
+}
+
+async fn manage_task(params: &TaskParams) -> Result<Value> {
+    match params.action.as_str() {
+        "create" => {
+            let task = new_task(params)?;
+            let connection = zbus::Connection::session().await?;
+            let task_list = parent_task_list(&connection, &task, params).await?;
+            let uid = task.create(task_list.as_deref()).await?;
+            Ok(json!({
+                "uid": uid,
+                "summary": task.summary
+            }))
+        }
+        "complete" | "update" => {
+            let uid = required_uid(params)?;
+            let connection = zbus::Connection::session().await?;
+            let (source, proxy, stored) =
+                Task::find(&connection, uid, params.task_list.as_deref()).await?;
+            let task = changed_task(Task::from_str(&stored)?, params, Utc::now())?;
+            if params.parent.is_some() {
+                check_parent(&connection, &task, &source).await?;
+            }
+            task.modify(&source, &proxy, &stored).await?;
+            let mut former_parents = Vec::new();
+            if params.parent.is_some() {
+                for parent in former_parents_of(&task, &source).await? {
+                    let (_source, _proxy, stored) =
+                        Task::find(&connection, &parent.uid, Some(&source.uid)).await?;
+                    let mut parent = Task::from_str(&stored)?;
+                    parent.children.retain(|child| *child != task.uid);
+                    parent.modify(&source, &proxy, &stored).await?;
+                    former_parents.push(parent.uid);
+                }
+            }
+            Ok(json!({
+                "uid": task.uid,
+                "task_list": source.display_name,
+                "task": task.to_json(local_timezone().await),
+                "former_parents": former_parents
+            }))
+        }
+        "delete" => {
+            let uid = required_uid(params)?;
+            let connection = zbus::Connection::session().await?;
+            let (source, proxy, stored) =
+                Task::find(&connection, uid, params.task_list.as_deref()).await?;
+            let task = Task::from_str(&stored)?;
+            task.remove(&source, &proxy).await?;
+            Ok(json!({
+                "uid": task.uid,
+                "task_list": source.display_name
+            }))
+        }
+        _ => Err(unknown_action(&params.action)),
+    }
+}
+
+fn unknown_action(action: &str) -> anyhow::Error {
+    anyhow::anyhow!(
+        "Unknown action: {}. Available: create, complete, update, delete",
+        action
+    )
+}
+
+fn required_uid(params: &TaskParams) -> Result<&str> {
+    params
+        .uid
+        .as_deref()
+        .ok_or_else(|| anyhow::anyhow!("uid required for {} action", params.action))
+}
+
+/// Longest chain of parents followed when looking for a cycle
+const MAX_TASK_DEPTH: usize = 32;
+
+/// The task list to create a task in: the one of its parent, unless a task
+/// list is given, in which case the parent must be in it
+async fn parent_task_list(
+    connection: &zbus::Connection,
+    task: &Task,
+    params: &TaskParams,
+) -> Result<Option<String>> {
+    let Some(parent) = task.parent.as_deref() else {
+        return Ok(params.task_list.clone());
+    };
+    let (source, _proxy, _stored) = Task::find(connection, parent, params.task_list.as_deref())
+        .await
+        .map_err(|e| anyhow::anyhow!("Parent task '{}' not found: {}", parent, e))?;
+    Ok(Some(source.uid))
+}
+
+/// Check that the new parent of a task is in its task list and isn't one of
+/// its own subtasks
+async fn check_parent(
+    connection: &zbus::Connection,
+    task: &Task,
+    source: &SourceInfo,
+) -> Result<()> {
+    let Some(mut parent) = task.parent.clone() else {
+        return Ok(());
+    };
+    for _ in 0..MAX_TASK_DEPTH {
+        if parent == task.uid {
+            anyhow::bail!("Task '{}' cannot be a subtask of its own subtask", task.uid);
+        }
+        let stored = match Task::find(connection, &parent, Some(&source.uid)).await {
+            Ok((_source, _proxy, stored)) => stored,
+            Err(_) if Some(&parent) == task.parent.as_ref() => anyhow::bail!(
+                "Parent task '{}' not found in {}",
+                parent,
+                source.display_name
+            ),
+            // The chain of parents ends on a task that was deleted
+            Err(_) => return Ok(()),
+        };
+        match Task::from_str(&stored)?.parent {
+            Some(next) => parent = next,
+            None => return Ok(()),
+        }
+    }
+    Ok(())
+}
+
+/// The tasks of the task list still listing `task` among their children
+/// although it was detached from them or moved under another parent
+async fn former_parents_of(task: &Task, source: &SourceInfo) -> Result<Vec<Task>> {
+    let results = Task::search(
+        &CalendarQuery::All,
+        &SourceFilter::only(Some(source.uid.as_str())),
+    )
+    .await?;
+    if let Some(error) = results.errors.first() {
+        anyhow::bail!(
+            "Failed to read {} for the former parents of '{}': {}",
+            error.display_name,
+            task.uid,
+            error.error
+        );
+    }
+    Ok(listing_as_child(results.items, task))
+}
+
+fn listing_as_child(tasks: Vec<Task>, task: &Task) -> Vec<Task> {
+    tasks
+        .into_iter()
+        .filter(|other| other.children.contains(&task.uid))
+        .filter(|other| Some(&other.uid) != task.parent.as_ref())
+        .collect()
+}
+
+fn new_task(params: &TaskParams) -> Result<Task> {
+    let summary = params
+        .summary
+        .clone()
+        .filter(|summary| !summary.is_empty())
+        .ok_or_else(|| anyhow::anyhow!("summary required for create action"))?;
+
+    let task = Task {
+        summary: Some(summary.clone()),
+        status: "NEEDS-ACTION".to_string(),
+        alarms: parse_reminders(params.reminders.as_deref(), true, &summary)?,
+        ..Default::default()
+    };
+    let task = apply_fields(task, params)?;
+    if !task.alarms.is_empty() && task.due_date.is_none() {
+        anyhow::bail!("reminders require a due time");
+    }
+    Ok(task)
+}
+
+/// Apply a complete or update action to a stored task
+fn changed_task(task: Task, params: &TaskParams, now: DateTime<Utc>) -> Result<Task> {
+    if params.reminders.is_some() {
+        anyhow::bail!("reminders can only be set when creating a task");
+    }
+    let mut task = apply_fields(task, params)?;
+    if params.action == "complete" {
+        task.complete(now);
+    }
+    Ok(task)
+}
+
+fn apply_fields(mut task: Task, params: &TaskParams) -> Result<Task> {
+    if let Some(summary) = &params.summary {
+        if summary.is_empty() {
+            anyhow::bail!("summary cannot be empty");
+        }
+        task.summary = Some(summary.clone());
+    }
+    if let Some(description) = &params.description {
+        task.description = Some(description.clone()).filter(|d| !d.is_empty());
+    }
+    if let Some(due) = &params.due {
+        task.due_date = match due.as_str() {
+            "" => None,
+            due if is_date(due) => Some(parse_date(due)?),
+            due => Some(parse_date_time(due, event_timezone(None)?)?),
+        };
+        task.all_day = is_date(due);
+    }
+    if let Some(priority) = params.priority {
+        if !(0..=9).contains(&priority) {
+            anyhow::bail!("priority must be between 0 and 9");
+        }
+        task.priority = (priority > 0).then_some(priority as u32);
+    }
+    if let Some(parent) = &params.parent {
+        if !task.uid.is_empty() && *parent == task.uid {
+            anyhow::bail!("A task cannot be its own parent");
+        }
+        task.parent = Some(parent.clone()).filter(|p| !p.is_empty());
+    }
+    Ok(task)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::mcp::ToolParams;
+
+    fn params(arguments: Value) -> TaskParams {
+        TaskParams::extract_params(&arguments).unwrap()
+    }
+
+    #[test]
+    fn test_new_task() {
+        let task = new_task(&params(json!({
+            "action": "create",
+            "summary": "Renew passport",
+            "due": "2024-09-01T12:00:00Z",
+            "priority": 3
+        })))
+        .unwrap();
+        assert_eq!(task.summary.as_deref(), Some("Renew passport"));
+        assert_eq!(task.status, "NEEDS-ACTION");
+        assert_eq!(task.due_date, "2024-09-01T12:00:00Z".parse().ok());
+        assert_eq!(task.priority, Some(3));
+        assert!(task.alarms.is_empty());
+        assert_eq!(task.parent, None);
+
+        let task = new_task(&params(json!({
+            "action": "create",
+            "summary": "Book photo booth",
+            "parent": "todo-1"
+        })))
+        .unwrap();
+        assert_eq!(task.parent.as_deref(), Some("todo-1"));
+        assert!(task.to_ical().contains("RELATED-TO:todo-1\r\n"));
+
+        let task = new_task(&params(json!({
+            "action": "create",
+            "summary": "Renew passport",
+            "due": "2024-09-01T12:00:00Z",
+            "reminders": "1440"
+        })))
+        .unwrap();
+        assert_eq!(
+            task.alarm_times()[0].1,
+            "2024-08-31T12:00:00Z".parse::<DateTime<Utc>>().unwrap()
+        );
+        assert!(task.to_ical().contains("TRIGGER;RELATED=END:-PT1440M"));
+
+        assert!(new_task(&params(json!({"action": "create"}))).is_err());
+        assert!(new_task(&params(json!({
+            "action": "create",
+            "summary": "Renew passport",
+            "reminders": "30"
+        })))
+        .is_err());
+        assert!(new_task(&params(json!({
+            "action": "create",
+            "summary": "Renew passport",
+            "priority": 12
+        })))
+        .is_err());
+    }
+
+    #[test]
+    fn test_changed_task() {
+        let stored = Task {
+            uid: "todo-1".to_string(),
+            summary: Some("Renew passport".to_string()),
+            status: "NEEDS-ACTION".to_string(),
+            due_date: "2024-09-01T12:00:00Z".parse().ok(),
+            priority: Some(3),
+            ..Default::default()
+        };
+        let now = "2024-08-20T09:00:00Z".parse().unwrap();
+
+        let completed = changed_task(
+            stored.clone(),
+            &params(json!({"action": "complete", "uid": "todo-1"})),
+            now,
+        )
+        .unwrap();
+        assert!(completed.is_completed());
+        assert_eq!(completed.completed_date, Some(now));
+        assert_eq!(completed.percent_complete, Some(100));
+
+        let updated = changed_task(
+            stored,
+            &params(json!({"action": "update", "uid": "todo-1", "due": "", "priority": 0})),
+            now,
+        )
+        .unwrap();
+        assert!(!updated.is_completed());
+        assert_eq!(updated.due_date, None);
+        assert_eq!(updated.priority, None);
+
+        let moved = changed_task(
+            updated,
+            &params(json!({"action": "update", "uid": "todo-1", "parent": "todo-0"})),
+            now,
+        )
+        .unwrap();
+        assert_eq!(moved.parent.as_deref(), Some("todo-0"));
+        let detached = changed_task(
+            moved.clone(),
+            &params(json!({"action": "update", "uid": "todo-1", "parent": ""})),
+            now,
+        )
+        .unwrap();
+        assert_eq!(detached.parent, None);
+
+        // The former parent no longer lists the detached task as a child
+        let stored_parent = "BEGIN:VTODO\r\nUID:todo-0\r\nSUMMARY:Move\r\n\
+                             RELATED-TO;RELTYPE=CHILD:todo-1\r\n\
+                             RELATED-TO;RELTYPE=CHILD:todo-2\r\nEND:VTODO\r\n";
+        let former = Task::from_str(stored_parent).unwrap();
+        let sibling = Task {
+            uid: "todo-2".to_string(),
+            parent: Some("todo-0".to_string()),
+            ..Default::default()
+        };
+        let mut released = listing_as_child(vec![former, sibling.clone()], &detached);
+        assert_eq!(released.len(), 1);
+        released[0].children.retain(|child| *child != detached.uid);
+        let patched = released[0].patch_ical(stored_parent).unwrap();
+        assert!(!patched.contains(":todo-1\r\n"));
+        assert!(patched.contains("RELATED-TO;RELTYPE=CHILD:todo-2\r\n"));
+        assert!(
+            listing_as_child(vec![Task::from_str(stored_parent).unwrap()], &sibling).is_empty()
+        );
+
+        assert!(changed_task(
+            moved,
+            &params(json!({"action": "update", "uid": "todo-1", "parent": "todo-1"})),
+            now,
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,96 @@
+use anyhow::Result;
+use serde_json::json;
+
+use crate::{
+    mcp::ToolProvider,
+    tool_params,
+    undo::{PreviousState, UndoEntry},
+};
+
+#[derive(Default)]
+pub struct Undo;
+
+tool_params! {
+    UndoParams,
+    optional(count: i64 = 1, "Number of recent changes to roll back, newest first (default: 1)")
+}
+
+impl ToolProvider for Undo {
+    const NAME: &'static str = "undo_last_action";
+    const DESCRIPTION: &'static str = "Roll back the most recent wallpaper, volume, quick settings or window geometry change made through this server";
+    type Params = UndoParams;
+
+    async fn execute_with_params(&self, params: Self::Params) -> Result<serde_json::Value> {
+        Self::execute_with_result(|| undo(params.count.max(1) as usize)).await
+    }
+
+    async fn dry_run_with_params(&self, params: Self::Params) -> Result<serde_json::Value> {
+        let pending = crate::undo::history()
+            .into_iter()
+            .take(params.count.max(1) as usize)
+            .collect::<Vec<_>>();
+        if pending.is_empty() {
+            return Err(anyhow::anyhow!("Nothing to undo"));
+        }
+
+        Ok(json!({
+            "description": format!("Would undo {} change(s)", pending.len()),
+            "entries": pending
+        }))
+    }
+}
+
+async fn undo(count: usize) -> Result<serde_json::Value> {
+    let mut undone = Vec::new();
+    for _ in 0..count {
+        let Some(entry) = crate::undo::pop() else {
+            break;
+        };
+        if let Err(err) = apply(&entry).await {
+            let description = entry.description.clone();
+            crate::undo::restore(entry);
+            return Err(anyhow::anyhow!(
+                "Failed to undo '{}' after {} successful step(s): {}",
+                description,
+                undone.len(),
+                err
+            ));
+        }
+        undone.push(entry);
+    }
+
+    if undone.is_empty() {
+        return Err(anyhow::anyhow!("Nothing to undo"));
+    }
+    Ok(json!({
+        "undone": undone,
+        "remaining": crate::undo::history().len()
+    }))
+}
+
+async fn apply(entry: &UndoEntry) -> Result<()> {
+    match &entry.previous {
+        PreviousState::Wallpaper {
+            picture_uri,
+            picture_uri_dark,
+        } => crate::tools::wallpaper::restore_wallpaper(picture_uri, picture_uri_dark.as_deref()),
+        PreviousState::Volume { level, muted } => {
+            crate::tools::audio::restore_volume(*level, *muted).await
+        }
+        PreviousState::QuickSetting { setting, enabled } => {
+            crate::tools::quick_settings::execute_boolean_toggle(setting, *enabled).await?;
+            Ok(())
+        }
+        PreviousState::WindowGeometry {
+            window_id,
+            x,
+            y,
+            width,
+            height,
+        } => {
+            crate::tools::window_management::restore_geometry(window_id, *x, *y, *width, *height)
+                .await?;
+            Ok(())
+        }
+    }
+}
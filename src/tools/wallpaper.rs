@@ -1,6 +1,7 @@
 use anyhow::Result;
+use gio::prelude::*;
 
-use crate::{mcp::ToolProvider, tool_params};
+use crate::{mcp::ToolProvider, tool_params, undo::PreviousState};
 
 #[derive(Default)]
 pub struct Wallpaper;
@@ -16,27 +17,44 @@ impl ToolProvider for Wallpaper {
     type Params = WallpaperParams;
 
     async fn execute_with_params(&self, params: Self::Params) -> Result<serde_json::Value> {
-        // Validate file exists and is an image
-        validate_image_file(&params.image_path)?;
-
-        // Convert to file:// URI format
-        let image_uri = if params.image_path.starts_with("file://") {
-            params.image_path.clone()
-        } else {
-            format!(
-                "file://{}",
-                std::path::Path::new(&params.image_path)
-                    .canonicalize()?
-                    .display()
-            )
-        };
+        let image_uri = image_uri(&params.image_path)?;
 
         Self::execute_with_message(
-            || set_wallpaper(&image_uri),
+            || async {
+                let previous = current_wallpaper();
+                set_wallpaper(&image_uri).await?;
+                if let Some(previous) = previous {
+                    crate::undo::record(Self::NAME, previous);
+                }
+                Ok(())
+            },
             format!("Wallpaper set to: {}", params.image_path),
         )
         .await
     }
+
+    async fn dry_run_with_params(&self, params: Self::Params) -> Result<serde_json::Value> {
+        let image_uri = image_uri(&params.image_path)?;
+        Ok(serde_json::json!({
+            "description": format!("Would set wallpaper to: {image_uri}"),
+            "uri": image_uri
+        }))
+    }
+}
+
+fn image_uri(image_path: &str) -> Result<String> {
+    // Validate file exists and is an image
+    validate_image_file(image_path)?;
+
+    // Convert to file:// URI format
+    if image_path.starts_with("file://") {
+        Ok(image_path.to_owned())
+    } else {
+        Ok(format!(
+            "file://{}",
+            std::path::Path::new(image_path).canonicalize()?.display()
+        ))
+    }
 }
 
 fn validate_image_file(image_path: &str) -> Result<()> {
@@ -72,3 +90,33 @@ async fn set_wallpaper(image_uri: &str) -> Result<()> {
         .response()?;
     Ok(())
 }
+
+const BACKGROUND_SCHEMA: &str = "org.gnome.desktop.background";
+
+fn background_settings() -> Option<gio::Settings> {
+    gio::SettingsSchemaSource::default()?.lookup(BACKGROUND_SCHEMA, true)?;
+    Some(gio::Settings::new(BACKGROUND_SCHEMA))
+}
+
+/// Wallpaper currently configured for the light and dark styles
+fn current_wallpaper() -> Option<PreviousState> {
+    let settings = background_settings()?;
+    let has_dark = settings
+        .settings_schema()
+        .is_some_and(|schema| schema.has_key("picture-uri-dark"));
+
+    Some(PreviousState::Wallpaper {
+        picture_uri: settings.string("picture-uri").to_string(),
+        picture_uri_dark: has_dark.then(|| settings.string("picture-uri-dark").to_string()),
+    })
+}
+
+pub(crate) fn restore_wallpaper(picture_uri: &str, picture_uri_dark: Option<&str>) -> Result<()> {
+    let settings = background_settings()
+        .ok_or_else(|| anyhow::anyhow!("Schema {} is not installed", BACKGROUND_SCHEMA))?;
+    settings.set_string("picture-uri", picture_uri)?;
+    if let Some(picture_uri_dark) = picture_uri_dark {
+        settings.set_string("picture-uri-dark", picture_uri_dark)?;
+    }
+    Ok(())
+}
@@ -1,6 +1,6 @@
 use anyhow::Result;
 
-use crate::{mcp::ToolProvider, tool_params};
+use crate::{mcp::ToolProvider, tool_params, undo::PreviousState};
 
 async fn eval_shell_script(shell_proxy: &zbus::Proxy<'_>, script: &str) -> Result<String> {
     let response = shell_proxy.call_method("Eval", &(script,)).await
@@ -44,13 +44,242 @@ impl ToolProvider for WindowManagement {
     async fn execute_with_params(&self, params: Self::Params) -> Result<serde_json::Value> {
         Self::execute_with_result(|| execute_window_action(params)).await
     }
+
+    async fn dry_run_with_params(&self, params: Self::Params) -> Result<serde_json::Value> {
+        let action = WindowAction::from_params(params)?;
+        let connection = zbus::Connection::session().await?;
+        let shell_proxy = connect_shell(&connection).await?;
+
+        // Reading state has no side effects, so report the real answer
+        if action.is_read_only() {
+            return Ok(run_window_action(&shell_proxy, action).await?.into());
+        }
+
+        let mut effect = serde_json::json!({
+            "description": format!("Would {}", action.describe()),
+        });
+        if let Some(window_id) = action.window_id() {
+            effect["window"] = describe_window(&shell_proxy, window_id).await?;
+        }
+        if let Some(workspace) = action.workspace() {
+            ensure_workspace_exists(&shell_proxy, workspace).await?;
+            effect["workspace"] = workspace.into();
+        }
+        Ok(effect)
+    }
 }
 
-async fn execute_window_action(params: WindowManagementParams) -> Result<String> {
-    let connection = zbus::Connection::session().await?;
+/// A validated window management request
+enum WindowAction {
+    List,
+    Focus {
+        window_id: String,
+    },
+    Close {
+        window_id: String,
+    },
+    Minimize {
+        window_id: String,
+    },
+    Maximize {
+        window_id: String,
+    },
+    SwitchWorkspace {
+        workspace: i32,
+    },
+    MoveToWorkspace {
+        window_id: String,
+        workspace: i32,
+    },
+    GetGeometry {
+        window_id: String,
+    },
+    SetGeometry {
+        window_id: String,
+        x: i32,
+        y: i32,
+        width: i32,
+        height: i32,
+    },
+    SetPosition {
+        window_id: String,
+        x: i32,
+        y: i32,
+    },
+    SetSize {
+        window_id: String,
+        width: i32,
+        height: i32,
+    },
+    Snap {
+        window_id: String,
+        position: String,
+    },
+}
+
+impl WindowAction {
+    fn from_params(params: WindowManagementParams) -> Result<Self> {
+        let action = params.action.as_str();
+        let window_id = || {
+            params
+                .window_id
+                .clone()
+                .ok_or_else(|| anyhow::anyhow!("window_id required for {action} action"))
+        };
+        let workspace = || {
+            params
+                .workspace
+                .map(|ws| ws as i32)
+                .ok_or_else(|| anyhow::anyhow!("workspace required for {action} action"))
+        };
+        let coordinate = |value: Option<i64>, name: &str| {
+            value
+                .map(|v| v as i32)
+                .ok_or_else(|| anyhow::anyhow!("{name} required for {action} action"))
+        };
+
+        Ok(match action {
+            "list" => Self::List,
+            "focus" => Self::Focus {
+                window_id: window_id()?,
+            },
+            "close" => Self::Close {
+                window_id: window_id()?,
+            },
+            "minimize" => Self::Minimize {
+                window_id: window_id()?,
+            },
+            "maximize" => Self::Maximize {
+                window_id: window_id()?,
+            },
+            "switch_workspace" => Self::SwitchWorkspace {
+                workspace: workspace()?,
+            },
+            "move_to_workspace" => Self::MoveToWorkspace {
+                window_id: window_id()?,
+                workspace: workspace()?,
+            },
+            "get_geometry" => Self::GetGeometry {
+                window_id: window_id()?,
+            },
+            "set_geometry" => Self::SetGeometry {
+                window_id: window_id()?,
+                x: coordinate(params.x, "x")?,
+                y: coordinate(params.y, "y")?,
+                width: coordinate(params.width, "width")?,
+                height: coordinate(params.height, "height")?,
+            },
+            "set_position" => Self::SetPosition {
+                window_id: window_id()?,
+                x: coordinate(params.x, "x")?,
+                y: coordinate(params.y, "y")?,
+            },
+            "set_size" => Self::SetSize {
+                window_id: window_id()?,
+                width: coordinate(params.width, "width")?,
+                height: coordinate(params.height, "height")?,
+            },
+            "snap" => {
+                let window_id = window_id()?;
+                let position = params
+                    .position
+                    .clone()
+                    .ok_or_else(|| anyhow::anyhow!("position required for snap action"))?;
+                if position != "left" && position != "right" {
+                    return Err(anyhow::anyhow!(
+                        "Invalid position: {}. Use 'left' or 'right'",
+                        position
+                    ));
+                }
+                Self::Snap {
+                    window_id,
+                    position,
+                }
+            }
+            _ => return Err(anyhow::anyhow!("Unknown action: {}. Available: list, focus, close, minimize, maximize, switch_workspace, move_to_workspace, get_geometry, set_geometry, set_position, set_size, snap", action)),
+        })
+    }
+
+    fn is_read_only(&self) -> bool {
+        matches!(self, Self::List | Self::GetGeometry { .. })
+    }
+
+    fn changes_geometry(&self) -> bool {
+        matches!(
+            self,
+            Self::SetGeometry { .. }
+                | Self::SetPosition { .. }
+                | Self::SetSize { .. }
+                | Self::Snap { .. }
+        )
+    }
+
+    fn window_id(&self) -> Option<&str> {
+        match self {
+            Self::List | Self::SwitchWorkspace { .. } => None,
+            Self::Focus { window_id }
+            | Self::Close { window_id }
+            | Self::Minimize { window_id }
+            | Self::Maximize { window_id }
+            | Self::MoveToWorkspace { window_id, .. }
+            | Self::GetGeometry { window_id }
+            | Self::SetGeometry { window_id, .. }
+            | Self::SetPosition { window_id, .. }
+            | Self::SetSize { window_id, .. }
+            | Self::Snap { window_id, .. } => Some(window_id),
+        }
+    }
+
+    fn workspace(&self) -> Option<i32> {
+        match self {
+            Self::SwitchWorkspace { workspace } | Self::MoveToWorkspace { workspace, .. } => {
+                Some(*workspace)
+            }
+            _ => None,
+        }
+    }
+
+    fn describe(&self) -> String {
+        match self {
+            Self::List => "list windows".to_owned(),
+            Self::Focus { window_id } => format!("focus window {window_id}"),
+            Self::Close { window_id } => format!("close window {window_id}"),
+            Self::Minimize { window_id } => format!("minimize window {window_id}"),
+            Self::Maximize { window_id } => {
+                format!("toggle maximization of window {window_id}")
+            }
+            Self::SwitchWorkspace { workspace } => format!("switch to workspace {workspace}"),
+            Self::MoveToWorkspace {
+                window_id,
+                workspace,
+            } => format!("move window {window_id} to workspace {workspace}"),
+            Self::GetGeometry { window_id } => format!("read the geometry of window {window_id}"),
+            Self::SetGeometry {
+                window_id,
+                x,
+                y,
+                width,
+                height,
+            } => format!("move window {window_id} to {x},{y} and resize it to {width}x{height}"),
+            Self::SetPosition { window_id, x, y } => {
+                format!("move window {window_id} to {x},{y}")
+            }
+            Self::SetSize {
+                window_id,
+                width,
+                height,
+            } => format!("resize window {window_id} to {width}x{height}"),
+            Self::Snap {
+                window_id,
+                position,
+            } => format!("snap window {window_id} to the {position} half of its monitor"),
+        }
+    }
+}
 
+async fn connect_shell(connection: &zbus::Connection) -> Result<zbus::Proxy<'static>> {
     let shell_proxy = zbus::Proxy::new(
-        &connection,
+        connection,
         "org.gnome.Shell",
         "/org/gnome/Shell",
         "org.gnome.Shell",
@@ -63,64 +292,141 @@ async fn execute_window_action(params: WindowManagementParams) -> Result<String>
         .await
         .map_err(|e| anyhow::anyhow!("Cannot connect to GNOME Shell: {}", e))?;
 
-    match params.action.as_str() {
-        "list" => list_windows(&shell_proxy).await,
-        "focus" => {
-            let id = params.window_id.ok_or_else(|| anyhow::anyhow!("window_id required for focus action"))?;
-            focus_window(&shell_proxy, &id).await
-        },
-        "close" => {
-            let id = params.window_id.ok_or_else(|| anyhow::anyhow!("window_id required for close action"))?;
-            close_window(&shell_proxy, &id).await
-        },
-        "minimize" => {
-            let id = params.window_id.ok_or_else(|| anyhow::anyhow!("window_id required for minimize action"))?;
-            minimize_window(&shell_proxy, &id).await
-        },
-        "maximize" => {
-            let id = params.window_id.ok_or_else(|| anyhow::anyhow!("window_id required for maximize action"))?;
-            maximize_window(&shell_proxy, &id).await
-        },
-        "switch_workspace" => {
-            let ws = params.workspace.ok_or_else(|| anyhow::anyhow!("workspace required for switch_workspace action"))?;
-            switch_workspace(&shell_proxy, ws as i32).await
-        },
-        "move_to_workspace" => {
-            let id = params.window_id.ok_or_else(|| anyhow::anyhow!("window_id required for move_to_workspace action"))?;
-            let ws = params.workspace.ok_or_else(|| anyhow::anyhow!("workspace required for move_to_workspace action"))?;
-            move_window_to_workspace(&shell_proxy, &id, ws as i32).await
-        },
-        "get_geometry" => {
-            let id = params.window_id.ok_or_else(|| anyhow::anyhow!("window_id required for get_geometry action"))?;
-            get_window_geometry(&shell_proxy, &id).await
-        },
-        "set_geometry" => {
-            let id = params.window_id.ok_or_else(|| anyhow::anyhow!("window_id required for set_geometry action"))?;
-            let x_val = params.x.ok_or_else(|| anyhow::anyhow!("x required for set_geometry action"))?;
-            let y_val = params.y.ok_or_else(|| anyhow::anyhow!("y required for set_geometry action"))?;
-            let w_val = params.width.ok_or_else(|| anyhow::anyhow!("width required for set_geometry action"))?;
-            let h_val = params.height.ok_or_else(|| anyhow::anyhow!("height required for set_geometry action"))?;
-            set_window_geometry(&shell_proxy, &id, x_val as i32, y_val as i32, w_val as i32, h_val as i32).await
-        },
-        "set_position" => {
-            let id = params.window_id.ok_or_else(|| anyhow::anyhow!("window_id required for set_position action"))?;
-            let x_val = params.x.ok_or_else(|| anyhow::anyhow!("x required for set_position action"))?;
-            let y_val = params.y.ok_or_else(|| anyhow::anyhow!("y required for set_position action"))?;
-            set_window_position(&shell_proxy, &id, x_val as i32, y_val as i32).await
-        },
-        "set_size" => {
-            let id = params.window_id.ok_or_else(|| anyhow::anyhow!("window_id required for set_size action"))?;
-            let w_val = params.width.ok_or_else(|| anyhow::anyhow!("width required for set_size action"))?;
-            let h_val = params.height.ok_or_else(|| anyhow::anyhow!("height required for set_size action"))?;
-            set_window_size(&shell_proxy, &id, w_val as i32, h_val as i32).await
-        },
-        "snap" => {
-            let id = params.window_id.ok_or_else(|| anyhow::anyhow!("window_id required for snap action"))?;
-            let pos = params.position.ok_or_else(|| anyhow::anyhow!("position required for snap action"))?;
-            snap_window(&shell_proxy, &id, &pos).await
-        },
-        _ => Err(anyhow::anyhow!("Unknown action: {}. Available: list, focus, close, minimize, maximize, switch_workspace, move_to_workspace, get_geometry, set_geometry, set_position, set_size, snap", params.action)),
+    Ok(shell_proxy)
+}
+
+async fn execute_window_action(params: WindowManagementParams) -> Result<String> {
+    let action = WindowAction::from_params(params)?;
+    let connection = zbus::Connection::session().await?;
+    let shell_proxy = connect_shell(&connection).await?;
+
+    // Remember where the window was so the move can be undone
+    let previous = match action.window_id() {
+        Some(window_id) if action.changes_geometry() => describe_window(&shell_proxy, window_id)
+            .await
+            .ok()
+            .and_then(|window| previous_geometry(window_id, &window["frame"])),
+        _ => None,
+    };
+
+    let result = run_window_action(&shell_proxy, action).await?;
+    if let Some(previous) = previous {
+        crate::undo::record(WindowManagement::NAME, previous);
+    }
+    Ok(result)
+}
+
+fn previous_geometry(window_id: &str, frame: &serde_json::Value) -> Option<PreviousState> {
+    let field = |name: &str| frame.get(name)?.as_i64().map(|v| v as i32);
+    Some(PreviousState::WindowGeometry {
+        window_id: window_id.to_owned(),
+        x: field("x")?,
+        y: field("y")?,
+        width: field("width")?,
+        height: field("height")?,
+    })
+}
+
+pub(crate) async fn restore_geometry(
+    window_id: &str,
+    x: i32,
+    y: i32,
+    width: i32,
+    height: i32,
+) -> Result<String> {
+    let connection = zbus::Connection::session().await?;
+    let shell_proxy = connect_shell(&connection).await?;
+    // Fail instead of reporting success for a window that has since closed
+    describe_window(&shell_proxy, window_id).await?;
+    set_window_geometry(&shell_proxy, window_id, x, y, width, height).await
+}
+
+async fn run_window_action(shell_proxy: &zbus::Proxy<'_>, action: WindowAction) -> Result<String> {
+    match action {
+        WindowAction::List => list_windows(shell_proxy).await,
+        WindowAction::Focus { window_id } => focus_window(shell_proxy, &window_id).await,
+        WindowAction::Close { window_id } => close_window(shell_proxy, &window_id).await,
+        WindowAction::Minimize { window_id } => minimize_window(shell_proxy, &window_id).await,
+        WindowAction::Maximize { window_id } => maximize_window(shell_proxy, &window_id).await,
+        WindowAction::SwitchWorkspace { workspace } => {
+            switch_workspace(shell_proxy, workspace).await
+        }
+        WindowAction::MoveToWorkspace {
+            window_id,
+            workspace,
+        } => move_window_to_workspace(shell_proxy, &window_id, workspace).await,
+        WindowAction::GetGeometry { window_id } => {
+            get_window_geometry(shell_proxy, &window_id).await
+        }
+        WindowAction::SetGeometry {
+            window_id,
+            x,
+            y,
+            width,
+            height,
+        } => set_window_geometry(shell_proxy, &window_id, x, y, width, height).await,
+        WindowAction::SetPosition { window_id, x, y } => {
+            set_window_position(shell_proxy, &window_id, x, y).await
+        }
+        WindowAction::SetSize {
+            window_id,
+            width,
+            height,
+        } => set_window_size(shell_proxy, &window_id, width, height).await,
+        WindowAction::Snap {
+            window_id,
+            position,
+        } => snap_window(shell_proxy, &window_id, &position).await,
+    }
+}
+
+/// Title, class, workspace and frame of a window, failing if it doesn't exist
+async fn describe_window(
+    shell_proxy: &zbus::Proxy<'_>,
+    window_id: &str,
+) -> Result<serde_json::Value> {
+    let script = format!(
+        r#"
+        let windows = global.get_window_actors()
+            .map(w => w.get_meta_window())
+            .filter(w => w.get_id() === {window_id});
+        if (windows.length > 0) {{
+            let window = windows[0];
+            let rect = window.get_frame_rect();
+            JSON.stringify({{
+                id: window.get_id(),
+                title: window.get_title(),
+                wm_class: window.get_wm_class(),
+                workspace: window.get_workspace().index(),
+                frame: {{ x: rect.x, y: rect.y, width: rect.width, height: rect.height }}
+            }});
+        }} else {{
+            'window not found';
+        }}
+    "#
+    );
+
+    let result = eval_shell_script(shell_proxy, &script).await?;
+    if result == "window not found" {
+        return Err(anyhow::anyhow!("Window {} not found", window_id));
     }
+    Ok(serde_json::from_str(&result)?)
+}
+
+async fn ensure_workspace_exists(
+    shell_proxy: &zbus::Proxy<'_>,
+    workspace_index: i32,
+) -> Result<()> {
+    let script = "global.workspace_manager.get_n_workspaces().toString();";
+    let count: i32 = eval_shell_script(shell_proxy, script).await?.parse()?;
+    if workspace_index < 0 || workspace_index >= count {
+        return Err(anyhow::anyhow!(
+            "Workspace {} not found ({} workspaces available)",
+            workspace_index,
+            count
+        ));
+    }
+    Ok(())
 }
 
 async fn list_windows(shell_proxy: &zbus::Proxy<'_>) -> Result<String> {
@@ -438,3 +744,37 @@ async fn snap_window(
     let result = eval_shell_script(shell_proxy, &script).await?;
     Ok(format!("Window {window_id} {result}"))
 }
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::mcp::ToolParams;
+
+    fn action_from(arguments: serde_json::Value) -> Result<WindowAction> {
+        WindowAction::from_params(WindowManagementParams::extract_params(&arguments)?)
+    }
+
+    #[test]
+    fn test_window_action_validation() {
+        let action = action_from(json!({"action": "close", "window_id": "42"})).unwrap();
+        assert_eq!(action.window_id(), Some("42"));
+        assert!(!action.is_read_only());
+        assert_eq!(action.describe(), "close window 42");
+
+        let error = action_from(json!({"action": "set_size", "window_id": "42", "width": 800}))
+            .err()
+            .unwrap();
+        assert_eq!(error.to_string(), "height required for set_size action");
+
+        assert!(
+            action_from(json!({"action": "snap", "window_id": "42", "position": "top"})).is_err()
+        );
+        assert!(action_from(json!({"action": "teleport"})).is_err());
+
+        let action = action_from(json!({"action": "list"})).unwrap();
+        assert!(action.is_read_only());
+        assert_eq!(action.window_id(), None);
+    }
+}
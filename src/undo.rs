@@ -0,0 +1,180 @@
+use std::{
+    collections::VecDeque,
+    sync::{LazyLock, Mutex},
+};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+static STACK: LazyLock<Mutex<UndoStack>> = LazyLock::new(Default::default);
+
+/// State captured right before a tool overwrote it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum PreviousState {
+    Wallpaper {
+        picture_uri: String,
+        picture_uri_dark: Option<String>,
+    },
+    Volume {
+        level: f64,
+        muted: bool,
+    },
+    QuickSetting {
+        setting: String,
+        enabled: bool,
+    },
+    WindowGeometry {
+        window_id: String,
+        x: i32,
+        y: i32,
+        width: i32,
+        height: i32,
+    },
+}
+
+impl PreviousState {
+    pub fn describe(&self) -> String {
+        match self {
+            Self::Wallpaper { picture_uri, .. } => format!("Restore wallpaper to {picture_uri}"),
+            Self::Volume { level, muted } => format!(
+                "Restore volume to {level}%{}",
+                if *muted { " (muted)" } else { "" }
+            ),
+            Self::QuickSetting { setting, enabled } => format!(
+                "{} {setting}",
+                if *enabled { "Re-enable" } else { "Disable" }
+            ),
+            Self::WindowGeometry {
+                window_id,
+                x,
+                y,
+                width,
+                height,
+            } => format!("Move window {window_id} back to {x},{y} at {width}x{height}"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UndoEntry {
+    pub id: u64,
+    pub timestamp: DateTime<Utc>,
+    pub tool: String,
+    pub description: String,
+    pub previous: PreviousState,
+}
+
+/// Bounded stack of reversible changes, newest at the back
+#[derive(Debug, Default)]
+pub struct UndoStack {
+    entries: VecDeque<UndoEntry>,
+    next_id: u64,
+}
+
+impl UndoStack {
+    pub fn push(&mut self, tool: &str, previous: PreviousState, capacity: usize) -> u64 {
+        self.next_id += 1;
+        self.entries.push_back(UndoEntry {
+            id: self.next_id,
+            timestamp: Utc::now(),
+            tool: tool.to_owned(),
+            description: previous.describe(),
+            previous,
+        });
+        while self.entries.len() > capacity {
+            self.entries.pop_front();
+        }
+        self.next_id
+    }
+
+    /// Put back an entry whose undo failed, so it can be retried
+    pub fn restore(&mut self, entry: UndoEntry) {
+        self.entries.push_back(entry);
+    }
+
+    pub fn pop(&mut self) -> Option<UndoEntry> {
+        self.entries.pop_back()
+    }
+
+    /// Entries from newest to oldest
+    pub fn history(&self) -> Vec<UndoEntry> {
+        self.entries.iter().rev().cloned().collect()
+    }
+}
+
+fn stack() -> std::sync::MutexGuard<'static, UndoStack> {
+    STACK.lock().unwrap_or_else(|err| err.into_inner())
+}
+
+/// Remember the state a tool is about to overwrite
+pub fn record(tool: &str, previous: PreviousState) {
+    let capacity = crate::config::CONFIG.get_undo_config().max_entries;
+    stack().push(tool, previous, capacity);
+}
+
+pub fn pop() -> Option<UndoEntry> {
+    stack().pop()
+}
+
+pub fn restore(entry: UndoEntry) {
+    stack().restore(entry);
+}
+
+pub fn history() -> Vec<UndoEntry> {
+    stack().history()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn volume(level: f64) -> PreviousState {
+        PreviousState::Volume {
+            level,
+            muted: false,
+        }
+    }
+
+    #[test]
+    fn test_stack_order_and_capacity() {
+        let mut stack = UndoStack::default();
+        stack.push("set_volume", volume(10.0), 2);
+        stack.push("set_volume", volume(20.0), 2);
+        let id = stack.push("set_volume", volume(30.0), 2);
+        assert_eq!(id, 3);
+
+        let history = stack.history();
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[0].previous, volume(30.0));
+        assert_eq!(history[1].previous, volume(20.0));
+
+        let entry = stack.pop().unwrap();
+        assert_eq!(entry.id, 3);
+        stack.restore(entry);
+        assert_eq!(stack.pop().unwrap().id, 3);
+        assert_eq!(stack.pop().unwrap().id, 2);
+        assert!(stack.pop().is_none());
+    }
+
+    #[test]
+    fn test_previous_state_serialization() {
+        let state = PreviousState::WindowGeometry {
+            window_id: "42".to_owned(),
+            x: 0,
+            y: 32,
+            width: 800,
+            height: 600,
+        };
+        let json = serde_json::to_value(&state).unwrap();
+        assert_eq!(json["kind"], "window_geometry");
+        assert_eq!(json["width"], 800);
+        assert_eq!(state.describe(), "Move window 42 back to 0,32 at 800x600");
+
+        let state = PreviousState::QuickSetting {
+            setting: "wifi".to_owned(),
+            enabled: true,
+        };
+        assert_eq!(state.describe(), "Re-enable wifi");
+    }
+}